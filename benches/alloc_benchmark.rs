@@ -0,0 +1,82 @@
+use blake2::Blake2s256;
+use criterion::{criterion_group, criterion_main, measurement::WallTime, BatchSize, BenchmarkGroup, BenchmarkId, Criterion};
+use jmt::storage::NodeBatch;
+use jmt::{JellyfishMerkleTree, KeyHash, SimpleHasher};
+use sha2::Sha256;
+use tree_bench::alloc::{self, AllocStats, CountingAllocator};
+use tree_bench::hash::Blake3;
+use tree_bench::store::{BTreeMapTreeStore, InMemoryTreeStore, TreeWriter};
+use tree_bench::workload;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const VALUE_LEN: usize = 8;
+
+fn key_value_pairs(size: usize) -> Vec<(KeyHash, Option<Vec<u8>>)> {
+    workload::pairs(size, VALUE_LEN)
+        .into_iter()
+        .map(|(key, value)| (KeyHash::with::<Sha256>(&key), Some(value)))
+        .collect()
+}
+
+// Counts what `f` allocates, with everything it is handed built beforehand
+fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, AllocStats) {
+    alloc::reset();
+    let result = f();
+    (result, alloc::snapshot())
+}
+
+fn report(name: &str, stats: AllocStats) {
+    println!("jmt_alloc/{}: {} allocations, {} bytes", name, stats.allocations, stats.bytes);
+}
+
+fn bench_put_value_set<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    for size in [10, 100, 1000].iter() {
+        let store = InMemoryTreeStore::new();
+        let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, H> = JellyfishMerkleTree::new(&store);
+        let pairs = key_value_pairs(*size);
+        let (_, stats) = count_allocations(|| jmt.put_value_set(pairs, 0).unwrap());
+        report(&format!("put_value_set/{}/{}", hasher, size), stats);
+
+        group.bench_with_input(
+            BenchmarkId::new(format!("put_value_set/{}", hasher), *size),
+            size,
+            |b, &size| {
+                b.iter_batched(
+                    || key_value_pairs(size),
+                    |pairs| jmt.put_value_set(pairs, 0).unwrap(),
+                    BatchSize::SmallInput,
+                );
+            },
+        );
+    }
+}
+
+// The same node batch written to each backend, so only the store's own encoding differs
+fn report_write_node_batch(size: usize) {
+    let source = InMemoryTreeStore::new();
+    let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Sha256> = JellyfishMerkleTree::new(&source);
+    let (_root, batch) = jmt.put_value_set(key_value_pairs(size), 0).unwrap();
+
+    fn write<S: TreeWriter>(store: S, node_batch: &NodeBatch) -> AllocStats {
+        count_allocations(|| store.write_node_batch(node_batch).unwrap()).1
+    }
+    report(&format!("write_node_batch/memory/{}", size), write(InMemoryTreeStore::new(), &batch.node_batch));
+    report(&format!("write_node_batch/btree/{}", size), write(BTreeMapTreeStore::new(), &batch.node_batch));
+}
+
+fn jmt_alloc_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("jmt_alloc");
+    bench_put_value_set::<Sha256>(&mut group, "sha256");
+    bench_put_value_set::<Blake2s256>(&mut group, "blake2s");
+    bench_put_value_set::<Blake3>(&mut group, "blake3");
+    group.finish();
+
+    for size in [10, 100, 1000] {
+        report_write_node_batch(size);
+    }
+}
+
+criterion_group!(benches, jmt_alloc_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,60 @@
+use criterion::{criterion_group, criterion_main, measurement::WallTime, BatchSize, BenchmarkGroup, BenchmarkId, Criterion};
+use jmt::{JellyfishMerkleTree, KeyHash, OwnedValue};
+use sha2::Sha256;
+use tree_bench::store::{Bincode, InMemoryTreeStore, NodeCodec, Postcard, TreeWriter};
+use tree_bench::workload;
+
+const VALUE_LEN: usize = 8;
+
+fn value_set(size: usize) -> Vec<(KeyHash, Option<OwnedValue>)> {
+    workload::pairs(size, VALUE_LEN)
+        .into_iter()
+        .map(|(key, value)| (KeyHash::with::<Sha256>(&key), Some(value)))
+        .collect()
+}
+
+// Inserts `size` keys in one commit into a store encoding with `C`
+fn bench_codec_insert<C: NodeCodec>(group: &mut BenchmarkGroup<'_, WallTime>, codec: &str) {
+    for size in [10, 100, 1000].iter() {
+        // Sized once up front, from the same batch every iteration writes
+        let store = InMemoryTreeStore::<C>::with_codec();
+        let jmt: JellyfishMerkleTree<'_, _, Sha256> = JellyfishMerkleTree::new(&store);
+        let (_root, batch) = jmt.put_value_set(value_set(*size), 0).unwrap();
+        let node_bytes: usize = batch
+            .node_batch
+            .nodes()
+            .values()
+            .map(|node| C::serialize(node).unwrap().len())
+            .sum();
+        println!(
+            "jmt_codec_insert/{}/{}: {} nodes in {} bytes",
+            codec,
+            size,
+            batch.node_batch.nodes().len(),
+            node_bytes
+        );
+
+        group.bench_with_input(BenchmarkId::new(format!("insert/{}", codec), *size), size, |b, &size| {
+            b.iter_batched(
+                || (InMemoryTreeStore::<C>::with_codec(), value_set(size)),
+                |(store, value_set)| {
+                    let jmt: JellyfishMerkleTree<'_, _, Sha256> = JellyfishMerkleTree::new(&store);
+                    let (_root, batch) = jmt.put_value_set(value_set, 0).unwrap();
+                    store.write_node_batch(&batch.node_batch).unwrap();
+                    store
+                },
+                BatchSize::SmallInput,
+            );
+        });
+    }
+}
+
+fn jmt_codec_insert_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("jmt_codec_insert");
+    bench_codec_insert::<Bincode>(&mut group, "bincode");
+    bench_codec_insert::<Postcard>(&mut group, "postcard");
+    group.finish();
+}
+
+criterion_group!(benches, jmt_codec_insert_benchmark);
+criterion_main!(benches);
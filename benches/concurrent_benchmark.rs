@@ -0,0 +1,64 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use jmt::{JellyfishMerkleTree, KeyHash};
+use sha2::Sha256;
+use std::thread;
+use tree_bench::store::{ConcurrentTreeStore, InMemoryTreeStore, TreeReader, TreeWriter};
+use tree_bench::workload;
+
+const VALUE_LEN: usize = 8;
+const TREE_SIZE: usize = 1000;
+const READS_PER_THREAD: usize = 100;
+
+fn populate<S: TreeReader + TreeWriter>(store: &S) -> Vec<KeyHash> {
+    let jmt: JellyfishMerkleTree<'_, S, Sha256> = JellyfishMerkleTree::new(store);
+    let pairs: Vec<(KeyHash, Option<Vec<u8>>)> = workload::pairs(TREE_SIZE, VALUE_LEN)
+        .into_iter()
+        .map(|(key, value)| (KeyHash::with::<Sha256>(&key), Some(value)))
+        .collect();
+    let key_hashes = pairs.iter().map(|(key_hash, _)| *key_hash).collect();
+    let (_root, batch) = jmt.put_value_set(pairs, 0).unwrap();
+    store.write_node_batch(&batch.node_batch).unwrap();
+    key_hashes
+}
+
+// Every thread proves its own run of keys, so the threads only share the store
+fn read_concurrently<S: TreeReader + Sync>(store: &S, key_hashes: &[KeyHash], threads: usize) {
+    thread::scope(|scope| {
+        for t in 0..threads {
+            scope.spawn(move || {
+                let jmt: JellyfishMerkleTree<'_, S, Sha256> = JellyfishMerkleTree::new(store);
+                for i in 0..READS_PER_THREAD {
+                    let key_hash = key_hashes[(t * READS_PER_THREAD + i) % key_hashes.len()];
+                    jmt.get_with_proof(key_hash, 0).unwrap();
+                }
+            });
+        }
+    });
+}
+
+fn jmt_concurrent_read_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("jmt_concurrent_read");
+
+    let rwlock = InMemoryTreeStore::new();
+    let key_hashes = populate(&rwlock);
+    let dashmap = ConcurrentTreeStore::new();
+    populate(&dashmap);
+
+    for threads in [1, 2, 4, 8].iter() {
+        group.throughput(Throughput::Elements((threads * READS_PER_THREAD) as u64));
+        group.bench_with_input(
+            BenchmarkId::new("get_with_proof/rwlock", threads),
+            threads,
+            |b, &threads| b.iter(|| read_concurrently(&rwlock, &key_hashes, threads)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("get_with_proof/dashmap", threads),
+            threads,
+            |b, &threads| b.iter(|| read_concurrently(&dashmap, &key_hashes, threads)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, jmt_concurrent_read_benchmark);
+criterion_main!(benches);
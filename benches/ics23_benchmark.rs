@@ -0,0 +1,51 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use jmt::{ics23_spec, JellyfishMerkleTree, KeyHash};
+use sha2::Sha256;
+use tree_bench::proof::ics23_proof_size;
+use tree_bench::store::{InMemoryTreeStore, PreimageStore, TreeWriter};
+use tree_bench::workload;
+
+const VALUE_LEN: usize = 8;
+
+// ICS23's JMT spec fixes Sha256 for both the tree and the key hash, so only that hasher applies
+fn jmt_ics23_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("jmt_ics23");
+    let spec = ics23_spec();
+
+    for size in [10, 100, 1000].iter() {
+        // `get_with_ics23_proof` needs key preimages for its exclusion-proof path
+        let store = PreimageStore::new(InMemoryTreeStore::new());
+        let jmt: JellyfishMerkleTree<'_, PreimageStore<InMemoryTreeStore>, Sha256> = JellyfishMerkleTree::new(&store);
+        let mut value_set = Vec::with_capacity(*size);
+        for (key, value) in workload::pairs(*size, VALUE_LEN) {
+            let key_hash = KeyHash::with::<Sha256>(&key);
+            store.record_preimage(key_hash, key).unwrap();
+            value_set.push((key_hash, Some(value)));
+        }
+        let (root, batch) = jmt.put_value_set(value_set, 0).unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+        let root = root.0.to_vec();
+
+        let key = workload::key(0);
+        let key_hash = KeyHash::with::<Sha256>(&key);
+        let (value, proof) = jmt.get_with_ics23_proof(key.clone(), 0).unwrap();
+        let value = value.unwrap();
+        assert!(ics23::verify_membership::<ics23::HostFunctionsManager>(&proof, &spec, &root, &key, &value));
+        println!("jmt_ics23/{}: proof is {} bytes", size, ics23_proof_size(&proof));
+
+        // The native proof alone, so the gap to `get_with_ics23_proof` is the conversion cost
+        group.bench_with_input(BenchmarkId::new("get_with_proof", *size), size, |b, _| {
+            b.iter(|| jmt.get_with_proof(key_hash, 0).unwrap());
+        });
+        group.bench_with_input(BenchmarkId::new("get_with_ics23_proof", *size), size, |b, _| {
+            b.iter(|| jmt.get_with_ics23_proof(key.clone(), 0).unwrap());
+        });
+        group.bench_with_input(BenchmarkId::new("verify_membership", *size), size, |b, _| {
+            b.iter(|| ics23::verify_membership::<ics23::HostFunctionsManager>(&proof, &spec, &root, &key, &value));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, jmt_ics23_benchmark);
+criterion_main!(benches);
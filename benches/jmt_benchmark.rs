@@ -1,210 +1,2870 @@
-use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
-use jmt::{JellyfishMerkleTree, storage::{TreeReader, TreeWriter, NodeBatch}, KeyHash, Version};
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
-use anyhow;
-use bincode;
+use criterion::measurement::{Measurement, ValueFormatter, WallTime};
+use criterion::{criterion_main, AxisScale, BatchSize, BenchmarkGroup, BenchmarkId, Criterion, PlotConfiguration, Throughput};
+use jmt::proof::{SparseMerkleProof, SparseMerkleRangeProof};
+use jmt::storage::{Node, NodeKey, StaleNodeIndex};
+use jmt::restore::{JellyfishMerkleRestore, StateSnapshotReceiver};
+use jmt::{JellyfishMerkleTree, KeyHash, OwnedValue, RootHash, SimpleHasher, Version};
 use blake2::Blake2s256;
-use sha2::{Digest, Sha256};
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::env;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tree_bench::hash::Blake3;
+use tree_bench::store::{dedup_value_set, export_leaves, get_many_with_proof, replay, Bincode, BTreeMapTreeStore, BoundedTreeStore, BufferedTreeStore, CachingReader, InMemoryTreeStore, InstrumentedReader, LatencyTreeStore, NodeCodec, RecordingTreeStore, ShardedTreeStore, TreeReader, TreeStats, TreeWriter, ValueStore};
+use tree_bench::proof::{proof_size, proof_to_json, sibling_count, verify_batch};
+use tree_bench::report;
+use tree_bench::workload::{self, commit_streamed, CommitStrategy, Delete, Get, Insert, KeyDistribution, Op, Update, ValueSet, Workload};
 
-struct InMemoryTreeStore {
-    store: Arc<RwLock<HashMap<Vec<u8>, Vec<u8>>>>,
-}
+// Value length used by the benchmarks that do not sweep value size
+const VALUE_LEN: usize = 8;
 
-impl InMemoryTreeStore {
-    fn new() -> Self {
-        Self {
-            store: Arc::new(RwLock::new(HashMap::new())),
-        }
-    }
+// Criterion takes one throughput per benchmark: the keys a run touches, or its value bytes
+#[derive(Clone, Copy)]
+enum Rate {
+    Elements,
+    Bytes,
 }
 
-impl TreeReader for InMemoryTreeStore {
-    fn get_node_option(
-        &self,
-        node_key: &jmt::storage::NodeKey,
-    ) -> Result<Option<jmt::storage::Node>, anyhow::Error> {
-        let store = self.store.read().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
-        let key_bytes = bincode::serialize(node_key)?;
-        match store.get(&key_bytes) {
-            Some(bytes) => Ok(Some(bincode::deserialize(bytes)?)),
-            None => Ok(None),
-        }
+// Counted from the batch `run` works through, so the rate always matches what is timed
+fn throughput(batch: &ValueSet, rate: Rate) -> Throughput {
+    match rate {
+        Rate::Elements => Throughput::Elements(batch.len() as u64),
+        Rate::Bytes => Throughput::Bytes(batch.iter().flat_map(|(_key_hash, value)| value).map(|value| value.len() as u64).sum()),
     }
+}
 
-    fn get_value_option(
-        &self,
-        _version: Version,
-        key_hash: KeyHash,
-    ) -> Result<Option<Vec<u8>>, anyhow::Error> {
-        let store = self.store.read().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
-        match store.get(&key_hash.0.to_vec()) {
-            Some(value) => Ok(Some(value.clone())),
-            None => Ok(None),
-        }
+// Times `workload.run` over 10, 100 and 1000 keys. The starting tree is set up once per size,
+// and each iteration writes to its own snapshot of it
+fn run_workload<W: Workload, H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, workload: &W, hasher: &str, rate: Rate) {
+    for size in [10, 100, 1000].iter() {
+        sample_for_size(group, *size);
+        let template = InMemoryTreeStore::new();
+        workload.setup::<_, H>(&template, *size).unwrap();
+        group.throughput(throughput(&workload.prepare(*size), rate));
+        group.bench_with_input(
+            BenchmarkId::new(workload.id(hasher), *size),
+            size,
+            |b, &size| {
+                b.iter_batched(
+                    || (template.clone_snapshot().unwrap(), workload.prepare(size)),
+                    |(store, batch)| {
+                        workload.run::<_, H>(&store, batch).unwrap();
+                        store
+                    },
+                    BatchSize::SmallInput,
+                );
+            },
+        );
     }
+}
 
-    fn get_rightmost_leaf(
-        &self,
-    ) -> Result<Option<(jmt::storage::NodeKey, jmt::storage::LeafNode)>, anyhow::Error> {
-        // Simplified implementation
-        Ok(None)
+// Like `run_workload`, for workloads that leave the tree untouched: every iteration reads the
+// same store, and its inputs are prepared once
+fn run_read_workload<W: Workload, H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, workload: &W, hasher: &str, rate: Rate) {
+    for size in [10, 100, 1000].iter() {
+        sample_for_size(group, *size);
+        let store = InMemoryTreeStore::new();
+        workload.setup::<_, H>(&store, *size).unwrap();
+        let batch = workload.prepare(*size);
+        group.throughput(throughput(&batch, rate));
+        group.bench_with_input(
+            BenchmarkId::new(workload.id(hasher), *size),
+            size,
+            |b, _| {
+                b.iter_batched(|| batch.clone(), |batch| workload.run::<_, H>(&store, batch).unwrap(), BatchSize::SmallInput);
+            },
+        );
     }
 }
 
-impl TreeWriter for InMemoryTreeStore {
-    fn write_node_batch(&self, node_batch: &NodeBatch) -> Result<(), anyhow::Error> {
-        let mut store = self.store.write().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
-        
-        for (node_key, node) in node_batch.nodes() {
-            let key_bytes = bincode::serialize(node_key)?;
-            let node_bytes = bincode::serialize(node)?;
-            store.insert(key_bytes, node_bytes);
-        }
-        
-        for ((_, key_hash), value_option) in node_batch.values() {
-            if let Some(value) = value_option {
-                store.insert(key_hash.0.to_vec(), value.clone());
-            } else {
-                store.remove(&key_hash.0.to_vec());
+// The tree hasher is the generic parameter; keys are always hashed with Sha256 so that
+// every hasher sees the same tree shape.
+fn bench_insert<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    for strategy in CommitStrategy::ALL {
+        for distribution in KeyDistribution::ALL {
+            for value_len in [8, 256, 1024] {
+                // Value size is swept here, so bytes/sec compares across it where ops/sec cannot
+                run_workload::<_, H>(group, &Insert { strategy, distribution, value_len }, hasher, Rate::Bytes);
             }
         }
-        
-        Ok(())
     }
 }
 
 fn jmt_insert_benchmark(c: &mut Criterion) {
-    let mut group = c.benchmark_group("jmt_insert");
-    
+    let mut group = benchmark_group(c, "jmt_insert");
+    bench_insert::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_insert::<Blake2s256>(&mut group, "blake2s");
+        bench_insert::<Blake3>(&mut group, "blake3");
+        #[cfg(feature = "keccak")]
+        bench_insert::<tree_bench::hash::Keccak256>(&mut group, "keccak256");
+    }
+    group.finish();
+    export_results("jmt_insert", &BTreeMap::new());
+}
+
+fn bench_get<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    for distribution in KeyDistribution::ALL {
+        run_read_workload::<_, H>(group, &Get { distribution, value_len: VALUE_LEN }, hasher, Rate::Elements);
+    }
+}
+
+fn jmt_get_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_get");
+    bench_get::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_get::<Blake2s256>(&mut group, "blake2s");
+        bench_get::<Blake3>(&mut group, "blake3");
+        #[cfg(feature = "keccak")]
+        bench_get::<tree_bench::hash::Keccak256>(&mut group, "keccak256");
+    }
+    group.finish();
+    export_results("jmt_get", &BTreeMap::new());
+}
+
+fn jmt_update_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_update");
+    let update = Update { value_len: VALUE_LEN };
+    run_workload::<_, Sha256>(&mut group, &update, "sha256", Rate::Elements);
+    if every_hasher() {
+        run_workload::<_, Blake2s256>(&mut group, &update, "blake2s", Rate::Elements);
+        run_workload::<_, Blake3>(&mut group, &update, "blake3", Rate::Elements);
+    }
+    group.finish();
+    export_results("jmt_update", &BTreeMap::new());
+}
+
+fn jmt_delete_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_delete");
+    let delete = Delete { value_len: VALUE_LEN };
+    run_workload::<_, Sha256>(&mut group, &delete, "sha256", Rate::Elements);
+    if every_hasher() {
+        run_workload::<_, Blake2s256>(&mut group, &delete, "blake2s", Rate::Elements);
+        run_workload::<_, Blake3>(&mut group, &delete, "blake3", Rate::Elements);
+    }
+    group.finish();
+    export_results("jmt_delete", &BTreeMap::new());
+}
+
+// Builds a tree of `size` keys committed at version 0 and returns it with its key hashes
+fn populated_store<H: SimpleHasher>(size: usize) -> (InMemoryTreeStore, Vec<KeyHash>) {
+    let store = InMemoryTreeStore::new();
+    let key_hashes: Vec<KeyHash> = (0..size)
+        .map(|i| KeyHash::with::<Sha256>(workload::key(i)))
+        .collect();
+    let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, H> = JellyfishMerkleTree::new(&store);
+    let (_root, batch) = jmt.put_value_set(
+        key_hashes.iter().enumerate().map(|(i, key_hash)| (*key_hash, Some(workload::value(i, VALUE_LEN)))),
+        0
+    ).unwrap();
+    store.write_node_batch(&batch.node_batch).unwrap();
+    (store, key_hashes)
+}
+
+fn bench_verify<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
     for size in [10, 100, 1000].iter() {
+        sample_for_size(group, *size);
+        group.bench_with_input(
+            BenchmarkId::new(format!("verify/{}", hasher), *size),
+            size,
+            |b, &size| {
+                let (store, key_hashes) = populated_store::<H>(size);
+                let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, H> = JellyfishMerkleTree::new(&store);
+                let key_hash = key_hashes[0];
+                let (value, proof) = jmt.get_with_proof(key_hash, 0).unwrap();
+                let root_hash = jmt.get_root_hash(0).unwrap();
+                proof.verify(root_hash, key_hash, value.as_ref()).expect("proof must verify");
+
+                b.iter(|| proof.verify(root_hash, key_hash, value.as_ref()));
+            },
+        );
         group.bench_with_input(
-            BenchmarkId::new("insert", *size),
+            BenchmarkId::new(format!("verify_wrong_root/{}", hasher), *size),
             size,
             |b, &size| {
+                let (store, key_hashes) = populated_store::<H>(size);
+                let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, H> = JellyfishMerkleTree::new(&store);
+                let key_hash = key_hashes[0];
+                let (value, proof) = jmt.get_with_proof(key_hash, 0).unwrap();
+                let wrong_root = tampered(jmt.get_root_hash(0).unwrap());
+                proof.verify(wrong_root, key_hash, value.as_ref()).expect_err("proof must not verify");
+
+                b.iter(|| proof.verify(wrong_root, key_hash, value.as_ref()));
+            },
+        );
+    }
+}
+
+// A root that differs from `root_hash` in a single bit
+fn tampered(root_hash: RootHash) -> RootHash {
+    let mut bytes = root_hash.0;
+    bytes[0] ^= 1;
+    RootHash(bytes)
+}
+
+fn jmt_verify_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_verify");
+    bench_verify::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_verify::<Blake2s256>(&mut group, "blake2s");
+        bench_verify::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_verify", &BTreeMap::new());
+}
+
+// Number of never-inserted keys probed per iteration of the non-membership benchmark
+const ABSENT_KEYS: usize = 100;
+
+fn bench_nonmembership<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    for size in [10, 100, 1000].iter() {
+        sample_for_size(group, *size);
+        let (store, _key_hashes) = populated_store::<H>(*size);
+        let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, H> = JellyfishMerkleTree::new(&store);
+        let root_hash = jmt.get_root_hash(0).unwrap();
+        let absent_key_hashes: Vec<KeyHash> = (0..ABSENT_KEYS)
+            .map(|i| KeyHash::with::<Sha256>(format!("absent{}", i)))
+            .collect();
+        let proofs: Vec<_> = absent_key_hashes
+            .iter()
+            .map(|key_hash| {
+                let (value, proof) = jmt.get_with_proof(*key_hash, 0).unwrap();
+                assert!(value.is_none(), "absent key must not have a value");
+                proof.verify_nonexistence(root_hash, *key_hash).expect("non-inclusion proof must verify");
+                proof
+            })
+            .collect();
+
+        group.bench_with_input(
+            BenchmarkId::new(format!("generate/{}", hasher), *size),
+            size,
+            |b, _| {
                 b.iter(|| {
-                    // Create a fresh tree store for each benchmark iteration
-                    let store = InMemoryTreeStore::new();
-                    let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Blake2s256> = JellyfishMerkleTree::new(&store);
-                    let pairs: Vec<(Vec<u8>, Vec<u8>)> = (0..size)
-                        .map(|i| (format!("key{}", i).into_bytes(), format!("value{}", i).into_bytes()))
-                        .collect();
-                    
-                    for (key, value) in pairs {
-                        let mut hasher = Sha256::new();
-                        hasher.update(&key);
-                        let hash_bytes: [u8; 32] = hasher.finalize().into();
-                        let key_hash = KeyHash(hash_bytes);
-                        let (_new_root, _proof) = jmt.put_value_set(
-                            vec![(key_hash, Some(value))],
-                            0
-                        ).unwrap();
+                    for key_hash in &absent_key_hashes {
+                        let _result = jmt.get_with_proof(*key_hash, 0).unwrap();
+                    }
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new(format!("verify/{}", hasher), *size),
+            size,
+            |b, _| {
+                b.iter(|| {
+                    for (key_hash, proof) in absent_key_hashes.iter().zip(&proofs) {
+                        proof.verify_nonexistence(root_hash, *key_hash).unwrap();
+                    }
+                });
+            },
+        );
+        let wrong_root = tampered(root_hash);
+        group.bench_with_input(
+            BenchmarkId::new(format!("verify_wrong_root/{}", hasher), *size),
+            size,
+            |b, _| {
+                b.iter(|| {
+                    for (key_hash, proof) in absent_key_hashes.iter().zip(&proofs) {
+                        proof.verify_nonexistence(wrong_root, *key_hash).unwrap_err();
                     }
                 });
             },
         );
     }
+}
+
+fn jmt_nonmembership_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_nonmembership");
+    bench_nonmembership::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_nonmembership::<Blake2s256>(&mut group, "blake2s");
+        bench_nonmembership::<Blake3>(&mut group, "blake3");
+    }
     group.finish();
+    export_results("jmt_nonmembership", &BTreeMap::new());
 }
 
-fn jmt_get_benchmark(c: &mut Criterion) {
-    let mut group = c.benchmark_group("jmt_get");
-    
-    for size in [10, 100, 1000].iter() {
+// Tree the range proofs are taken over; ranges always start at the leftmost key
+const RANGE_TREE_SIZE: usize = 1000;
+
+fn bench_range<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    let (store, mut key_hashes) = populated_store::<H>(RANGE_TREE_SIZE);
+    let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, H> = JellyfishMerkleTree::new(&store);
+    key_hashes.sort();
+
+    for range_len in [10, 50, 100].iter() {
+        sample_for_size(group, *range_len);
+        // A range proof covers every key up to and including the rightmost key it proves
+        let rightmost_key_hash = key_hashes[*range_len - 1];
+        let proof = jmt.get_range_proof(rightmost_key_hash, 0).unwrap();
+        println!(
+            "jmt_range/{}/{}: range proof is {} bytes",
+            hasher,
+            range_len,
+            bincode::serialize(&proof).unwrap().len()
+        );
+
         group.bench_with_input(
-            BenchmarkId::new("get", *size),
-            size,
-            |b, &size| {
+            BenchmarkId::new(format!("range/{}", hasher), *range_len),
+            range_len,
+            |b, _| {
+                b.iter(|| jmt.get_range_proof(rightmost_key_hash, 0).unwrap());
+            },
+        );
+    }
+}
+
+fn jmt_range_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_range");
+    bench_range::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_range::<Blake2s256>(&mut group, "blake2s");
+        bench_range::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_range", &BTreeMap::new());
+}
+
+const CACHE_TREE_SIZE: usize = 1000;
+
+// Proves every key through `reader`, as one pass of a read-heavy client
+fn prove_all<R: TreeReader, H: SimpleHasher>(reader: &R, key_hashes: &[KeyHash]) {
+    let jmt: JellyfishMerkleTree<'_, R, H> = JellyfishMerkleTree::new(reader);
+    for key_hash in key_hashes {
+        jmt.get_with_proof(*key_hash, 0).unwrap();
+    }
+}
+
+fn bench_cache<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    sample_for_size(group, CACHE_TREE_SIZE);
+    let (store, key_hashes) = populated_store::<H>(CACHE_TREE_SIZE);
+    // Roomy enough for every node of the tree, so a warmed cache never evicts
+    let cached = CachingReader::new(store, NonZeroUsize::new(4 * CACHE_TREE_SIZE).unwrap());
+    prove_all::<_, H>(&cached, &key_hashes);
+
+    group.throughput(Throughput::Elements(key_hashes.len() as u64));
+    group.bench_function(BenchmarkId::new(format!("get_with_proof/cold/{}", hasher), CACHE_TREE_SIZE), |b| {
+        b.iter(|| prove_all::<_, H>(cached.inner(), &key_hashes))
+    });
+    group.bench_function(BenchmarkId::new(format!("get_with_proof/warm/{}", hasher), CACHE_TREE_SIZE), |b| {
+        b.iter(|| prove_all::<_, H>(&cached, &key_hashes))
+    });
+}
+
+fn jmt_cache_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_cache");
+    bench_cache::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_cache::<Blake2s256>(&mut group, "blake2s");
+        bench_cache::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_cache", &BTreeMap::new());
+}
+
+const CONCURRENT_TREE_SIZE: usize = 1000;
+
+fn bench_concurrent_get<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    sample_for_size(group, CONCURRENT_TREE_SIZE);
+    let (store, key_hashes) = populated_store::<H>(CONCURRENT_TREE_SIZE);
+    let store = Arc::new(store);
+    let key_hashes = Arc::new(key_hashes);
+
+    group.throughput(Throughput::Elements(CONCURRENT_TREE_SIZE as u64));
+    for threads in [1, 2, 4, 8].iter() {
+        group.bench_with_input(
+            BenchmarkId::new(format!("get_with_proof/{}", hasher), *threads),
+            threads,
+            |b, &threads| {
+                // Every key is proven once per iteration, split into one disjoint chunk per thread
+                let chunk_len = CONCURRENT_TREE_SIZE.div_ceil(threads);
                 b.iter(|| {
-                    // Create a JMT with pre-populated data
-                    let store = InMemoryTreeStore::new();
-                    let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Blake2s256> = JellyfishMerkleTree::new(&store);
-                    
-                    // Pre-populate the tree
-                    let keys: Vec<Vec<u8>> = (0..size).map(|i| format!("key{}", i).into_bytes()).collect();
-                    let values: Vec<Vec<u8>> = (0..size).map(|i| format!("value{}", i).into_bytes()).collect();
-                    
-                    let key_val_pairs: Vec<_> = keys.iter().cloned()
-                        .zip(values.iter().cloned().map(Some))
-                        .map(|(k, v)| {
-                            let mut hasher = Sha256::new();
-                            hasher.update(&k);
-                            let hash_bytes: [u8; 32] = hasher.finalize().into();
-                            (KeyHash(hash_bytes), v)
+                    let handles: Vec<_> = (0..threads)
+                        .map(|t| {
+                            let store = Arc::clone(&store);
+                            let key_hashes = Arc::clone(&key_hashes);
+                            thread::spawn(move || {
+                                let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, H> = JellyfishMerkleTree::new(&store);
+                                for key_hash in key_hashes.chunks(chunk_len).nth(t).unwrap_or_default() {
+                                    jmt.get_with_proof(*key_hash, 0).unwrap();
+                                }
+                            })
                         })
                         .collect();
-                    
-                    let (_root, _batch) = jmt.put_value_set(
-                        key_val_pairs,
-                        0
-                    ).unwrap();
-
-                    for key in &keys {
-                        let mut hasher = Sha256::new();
-                        hasher.update(key);
-                        let hash_bytes: [u8; 32] = hasher.finalize().into();
-                        let key_hash = KeyHash(hash_bytes);
-                        let _result = jmt.get_with_proof(key_hash, 0).unwrap();
+                    for handle in handles {
+                        handle.join().unwrap();
                     }
                 });
             },
         );
     }
+}
+
+fn jmt_concurrent_get_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_concurrent_get");
+    bench_concurrent_get::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_concurrent_get::<Blake2s256>(&mut group, "blake2s");
+        bench_concurrent_get::<Blake3>(&mut group, "blake3");
+    }
     group.finish();
+    export_results("jmt_concurrent_get", &BTreeMap::new());
 }
 
-fn jmt_update_benchmark(c: &mut Criterion) {
-    let mut group = c.benchmark_group("jmt_update");
-    
-    for size in [10, 100, 1000].iter() {
+fn bench_proof_size<H: SimpleHasher>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    hasher: &str,
+    tree_stats: &mut BTreeMap<usize, TreeStats>,
+) {
+    for size in sizes([10, 100, 1000, 10000]).iter() {
+        sample_for_size(group, *size);
+        let (store, key_hashes) = populated_store::<H>(*size);
+        let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, H> = JellyfishMerkleTree::new(&store);
+        let key_hash = key_hashes[0];
+
+        // Sized once up front, so the measurement never touches the timed loop
+        let (_value, proof) = jmt.get_with_proof(key_hash, 0).unwrap();
+        println!("jmt_proof/{}/{}: proof is {} bytes", hasher, size, proof_size(&proof).unwrap());
+        let stats = store.tree_stats().unwrap();
+        println!(
+            "jmt_proof/{}/{}: height {}, {} internal nodes, {} leaves",
+            hasher, size, stats.height, stats.internal_nodes, stats.leaf_nodes
+        );
+        tree_stats.insert(*size, stats);
+        // Entry n counts internal nodes with n children
+        println!("jmt_proof/{}/{}: fanout {:?}", hasher, size, store.fanout_histogram().unwrap());
+        let bytes = store.estimated_size_bytes().unwrap();
+        println!("jmt_proof/{}/{}: ~{} bytes stored, {} per key", hasher, size, bytes, bytes / size);
+
         group.bench_with_input(
-            BenchmarkId::new("update", *size),
+            BenchmarkId::new(format!("get_with_proof/{}", hasher), *size),
             size,
-            |b, &size| {
+            |b, _| {
+                b.iter(|| jmt.get_with_proof(key_hash, 0).unwrap());
+            },
+        );
+    }
+}
+
+fn jmt_proof_size_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_proof");
+    // Keys are hashed with Sha256 for every hasher, so the shape only depends on size
+    let mut tree_stats = BTreeMap::new();
+    bench_proof_size::<Sha256>(&mut group, "sha256", &mut tree_stats);
+    if every_hasher() {
+        bench_proof_size::<Blake2s256>(&mut group, "blake2s", &mut tree_stats);
+        bench_proof_size::<Blake3>(&mut group, "blake3", &mut tree_stats);
+    }
+    group.finish();
+    export_results("jmt_proof", &tree_stats);
+}
+
+const MIXED_OPS: usize = 100;
+
+// Runs `ops` over a tree committed at version 0, each write committing the next version
+fn run_mixed_ops<S: TreeReader + TreeWriter, H: SimpleHasher>(store: &S, key_hashes: &[KeyHash], ops: &[Op]) {
+    let jmt: JellyfishMerkleTree<'_, S, H> = JellyfishMerkleTree::new(store);
+    let mut version: Version = 0;
+    for op in ops {
+        match *op {
+            Op::Read(i) => {
+                jmt.get_with_proof(key_hashes[i], version).unwrap();
+            }
+            Op::Write(i) => {
+                version += 1;
+                let value = workload::value(i + version as usize, VALUE_LEN);
+                let (_root, batch) = jmt.put_value_set(vec![(key_hashes[i], Some(value))], version).unwrap();
+                store.write_node_batch(&batch.node_batch).unwrap();
+            }
+        }
+    }
+}
+
+fn bench_mixed<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    for read_percent in [90, 50] {
+        for size in [100, 1000].iter() {
+            sample_for_size(group, *size);
+            let ops = workload::mixed_ops(workload::BENCH_SEED, MIXED_OPS, *size, read_percent);
+            let (template, key_hashes) = populated_store::<H>(*size);
+            group.throughput(Throughput::Elements(ops.len() as u64));
+            group.bench_with_input(
+                BenchmarkId::new(format!("mixed/{}/read_{}", hasher, read_percent), *size),
+                size,
+                |b, _| {
+                    // Writes mutate the tree, so every iteration starts from its own snapshot of
+                    // one built once, which copies bytes rather than hashing the tree again
+                    b.iter_batched(
+                        || template.clone_snapshot().unwrap(),
+                        |store| {
+                            run_mixed_ops::<_, H>(&store, &key_hashes, &ops);
+                            store
+                        },
+                        BatchSize::PerIteration,
+                    );
+                },
+            );
+        }
+    }
+}
+
+fn jmt_mixed_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_mixed");
+    bench_mixed::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_mixed::<Blake2s256>(&mut group, "blake2s");
+        bench_mixed::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_mixed", &BTreeMap::new());
+}
+
+const HISTORY_TREE_SIZE: usize = 100;
+const HISTORY_READS: usize = 100;
+
+fn bench_history<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    sample_for_size(group, HISTORY_READS);
+    for depth in [1, 10, 100].iter() {
+        // Every version overwrites the whole key set, so each one stores a full copy of the tree
+        let store = InMemoryTreeStore::new();
+        let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, H> = JellyfishMerkleTree::new(&store);
+        let key_hashes: Vec<KeyHash> = (0..HISTORY_TREE_SIZE)
+            .map(|i| KeyHash::with::<Sha256>(workload::key(i)))
+            .collect();
+        for version in 0..*depth as Version {
+            let (_root, batch) = jmt.put_value_set(
+                key_hashes.iter().enumerate().map(|(i, key_hash)| (*key_hash, Some(workload::value(i + version as usize, VALUE_LEN)))),
+                version
+            ).unwrap();
+            store.write_node_batch(&batch.node_batch).unwrap();
+        }
+
+        let latest = *depth as Version - 1;
+        let keys = workload::random_indices(workload::BENCH_SEED, HISTORY_READS, HISTORY_TREE_SIZE);
+        let versions = workload::random_indices(workload::BENCH_SEED + 1, HISTORY_READS, *depth);
+        group.throughput(Throughput::Elements(keys.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::new(format!("history/{}/random_version", hasher), *depth),
+            depth,
+            |b, _| {
                 b.iter(|| {
-                    // Create a JMT with pre-populated data
-                    let store = InMemoryTreeStore::new();
-                    let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Blake2s256> = JellyfishMerkleTree::new(&store);
-                    
-                    // Pre-populate the tree
-                    let keys: Vec<Vec<u8>> = (0..size).map(|i| format!("key{}", i).into_bytes()).collect();
-                    let values: Vec<Vec<u8>> = (0..size).map(|i| format!("value{}", i).into_bytes()).collect();
-                    
-                    let key_val_pairs: Vec<_> = keys.iter().cloned()
-                        .zip(values.iter().cloned().map(Some))
-                        .map(|(k, v)| {
-                            let mut hasher = Sha256::new();
-                            hasher.update(&k);
-                            let hash_bytes: [u8; 32] = hasher.finalize().into();
-                            (KeyHash(hash_bytes), v)
-                        })
-                        .collect();
-                    
-                    let (_root, _batch) = jmt.put_value_set(
-                        key_val_pairs,
-                        0
-                    ).unwrap();
-
-                    let update_pairs: Vec<_> = keys.iter().cloned()
-                        .zip((0..size).map(|i| Some(format!("updated_value{}", i).into_bytes())))
-                        .map(|(k, v)| {
-                            let mut hasher = Sha256::new();
-                            hasher.update(&k);
-                            let hash_bytes: [u8; 32] = hasher.finalize().into();
-                            (KeyHash(hash_bytes), v)
-                        })
-                        .collect();
-                        
-                    let (_new_root, _batch) = jmt.put_value_set(
-                        update_pairs,
-                        1
-                    ).unwrap();
+                    for (i, version) in keys.iter().zip(&versions) {
+                        jmt.get_with_proof(key_hashes[*i], *version as Version).unwrap();
+                    }
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new(format!("history/{}/latest", hasher), *depth),
+            depth,
+            |b, _| {
+                b.iter(|| {
+                    for i in &keys {
+                        jmt.get_with_proof(key_hashes[*i], latest).unwrap();
+                    }
                 });
             },
         );
     }
+}
+
+fn jmt_history_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_history");
+    bench_history::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_history::<Blake2s256>(&mut group, "blake2s");
+        bench_history::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_history", &BTreeMap::new());
+}
+
+const RESTORE_TREE_SIZE: usize = 4096;
+
+type SnapshotChunk<H> = (Vec<(KeyHash, OwnedValue)>, SparseMerkleRangeProof<H>);
+
+// Splits the tree's leaves, in key order, into chunks that each carry the range proof a
+// snapshot sender would attach
+fn snapshot_chunks<H: SimpleHasher>(store: InMemoryTreeStore, chunk_len: usize) -> Vec<SnapshotChunk<H>> {
+    let store = Arc::new(store);
+    let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, H> = JellyfishMerkleTree::new(&store);
+    export_leaves(Arc::clone(&store), 0, NonZeroUsize::new(chunk_len).unwrap())
+        .unwrap()
+        .map(|chunk| {
+            let chunk = chunk.unwrap();
+            let proof = jmt.get_range_proof(chunk.last().unwrap().0, 0).unwrap();
+            (chunk, proof)
+        })
+        .collect()
+}
+
+fn restore<S, H>(chunks: Vec<SnapshotChunk<H>>, root_hash: RootHash) -> Arc<S>
+where
+    S: TreeReader + TreeWriter + Default + 'static,
+    H: SimpleHasher,
+{
+    let store = Arc::new(S::default());
+    let mut restore = JellyfishMerkleRestore::<H>::new(Arc::clone(&store), 0, root_hash).unwrap();
+    for (chunk, proof) in chunks {
+        restore.add_chunk(chunk, proof).unwrap();
+    }
+    restore.finish().unwrap();
+    store
+}
+
+fn bench_restore_into<S, H>(group: &mut BenchmarkGroup<'_, WallTime>, name: &str, chunks: &[SnapshotChunk<H>], root_hash: RootHash, chunk_len: usize)
+where
+    S: TreeReader + TreeWriter + Default + 'static,
+    H: SimpleHasher,
+{
+    // A restore that ends on any other root would make the timings meaningless
+    let restored = restore::<S, H>(chunks.to_vec(), root_hash);
+    let jmt: JellyfishMerkleTree<'_, S, H> = JellyfishMerkleTree::new(&restored);
+    assert_eq!(jmt.get_root_hash(0).unwrap(), root_hash);
+
+    group.bench_with_input(BenchmarkId::new(name, chunk_len), &chunk_len, |b, _| {
+        b.iter_batched(|| chunks.to_vec(), |chunks| restore::<S, H>(chunks, root_hash), BatchSize::PerIteration);
+    });
+}
+
+fn bench_restore<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    sample_for_size(group, RESTORE_TREE_SIZE);
+    for chunk_len in [64, 256, 1024] {
+        let (store, _key_hashes) = populated_store::<H>(RESTORE_TREE_SIZE);
+        let root_hash = JellyfishMerkleTree::<'_, InMemoryTreeStore, H>::new(&store).get_root_hash(0).unwrap();
+        let chunks = snapshot_chunks::<H>(store, chunk_len);
+
+        bench_restore_into::<InMemoryTreeStore, H>(group, &format!("restore/{}/memory", hasher), &chunks, root_hash, chunk_len);
+        bench_restore_into::<BTreeMapTreeStore, H>(group, &format!("restore/{}/btree", hasher), &chunks, root_hash, chunk_len);
+    }
+}
+
+fn jmt_restore_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_restore");
+    bench_restore::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_restore::<Blake2s256>(&mut group, "blake2s");
+        bench_restore::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_restore", &BTreeMap::new());
+}
+
+const PRUNE_TREE_SIZE: usize = 1000;
+const PRUNE_KEYS_PER_ROUND: usize = 100;
+
+// Rewrites a slice of the keys each round and gathers the nodes every commit made stale
+fn updated_store<H: SimpleHasher>(rounds: usize) -> (InMemoryTreeStore, Vec<Vec<StaleNodeIndex>>) {
+    let (store, key_hashes) = populated_store::<H>(PRUNE_TREE_SIZE);
+    let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, H> = JellyfishMerkleTree::new(&store);
+    let stale_per_round = (1..=rounds)
+        .map(|round| {
+            let start = (round * PRUNE_KEYS_PER_ROUND) % PRUNE_TREE_SIZE;
+            let (_root, batch) = jmt.put_value_set(
+                key_hashes[start..start + PRUNE_KEYS_PER_ROUND].iter().map(|key_hash| (*key_hash, Some(workload::value(round, VALUE_LEN)))),
+                round as Version
+            ).unwrap();
+            store.write_node_batch(&batch.node_batch).unwrap();
+            batch.stale_node_index_batch.into_iter().collect()
+        })
+        .collect();
+    (store, stale_per_round)
+}
+
+fn bench_prune<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    for rounds in [10, 50, 100].iter() {
+        sample_for_size(group, *rounds * PRUNE_KEYS_PER_ROUND);
+        let (template, stale_per_round) = updated_store::<H>(*rounds);
+        let counts: Vec<usize> = stale_per_round.iter().map(Vec::len).collect();
+        println!(
+            "jmt_prune/{}/{}: {} stale nodes, per round {:?}",
+            hasher,
+            rounds,
+            counts.iter().sum::<usize>(),
+            counts
+        );
+
+        // Pruning removes nodes, so every iteration prunes its own snapshot of the updated tree
+        group.bench_with_input(
+            BenchmarkId::new(format!("prune/{}", hasher), *rounds),
+            rounds,
+            |b, _| {
+                b.iter_batched(
+                    || template.clone_snapshot().unwrap(),
+                    |store| {
+                        store.prune(stale_per_round.iter().flatten()).unwrap();
+                        store
+                    },
+                    BatchSize::PerIteration,
+                );
+            },
+        );
+    }
+}
+
+fn jmt_prune_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_prune");
+    bench_prune::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_prune::<Blake2s256>(&mut group, "blake2s");
+        bench_prune::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_prune", &BTreeMap::new());
+}
+
+// A balance, a nonce and a code hash: the shape of a typical account record
+type Account = (u64, u64, [u8; 32]);
+
+fn accounts(size: usize) -> Vec<(KeyHash, Option<Account>)> {
+    (0..size)
+        .map(|i| (KeyHash::with::<Sha256>(workload::key(i)), Some((i as u64, 0, [i as u8; 32]))))
+        .collect()
+}
+
+// Serialization does not depend on the tree hasher, so only Sha256 trees are built here
+fn jmt_typed_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_typed");
+
+    for size in [10, 100, 1000].iter() {
+        sample_for_size(&mut group, *size);
+        // Encoding alone, then inserting already-encoded values, then both through `ValueStore`
+        group.bench_with_input(BenchmarkId::new("serialize", *size), size, |b, &size| {
+            b.iter_batched(
+                || accounts(size),
+                |values| ValueStore::<InMemoryTreeStore, Account>::encode(values).unwrap(),
+                BatchSize::SmallInput,
+            );
+        });
+        group.bench_with_input(BenchmarkId::new("insert_encoded", *size), size, |b, &size| {
+            b.iter_batched(
+                || (InMemoryTreeStore::new(), ValueStore::<InMemoryTreeStore, Account>::encode(accounts(size)).unwrap()),
+                |(store, value_set)| {
+                    let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Sha256> = JellyfishMerkleTree::new(&store);
+                    let (_root, batch) = jmt.put_value_set(value_set, 0).unwrap();
+                    store.write_node_batch(&batch.node_batch).unwrap();
+                    store
+                },
+                BatchSize::SmallInput,
+            );
+        });
+        group.bench_with_input(BenchmarkId::new("insert_typed", *size), size, |b, &size| {
+            b.iter_batched(
+                || (ValueStore::<_, Account>::new(InMemoryTreeStore::new()), accounts(size)),
+                |(store, values)| {
+                    store.put_values::<Sha256, _>(values, 0).unwrap();
+                    store
+                },
+                BatchSize::SmallInput,
+            );
+        });
+    }
+    group.finish();
+    export_results("jmt_typed", &BTreeMap::new());
+}
+
+const CODEC_TREE_SIZE: usize = 1000;
+
+// The two shapes the store encodes: the root of a 1000-key tree, which has all 16 children, and
+// one of its leaves
+fn representative_nodes() -> [(&'static str, Node); 2] {
+    let (store, _key_hashes) = populated_store::<Sha256>(CODEC_TREE_SIZE);
+    let root = store.get_node(&NodeKey::new(0, std::iter::empty().collect())).unwrap();
+    assert!(matches!(&root, Node::Internal(internal) if internal.children_sorted().count() == 16));
+    let (_node_key, leaf) = store.get_rightmost_leaf().unwrap().unwrap();
+    [("internal", root), ("leaf", Node::Leaf(leaf))]
+}
+
+fn jmt_codec_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_codec");
+    sample_for_size(&mut group, 1);
+
+    for (node_type, node) in representative_nodes() {
+        let bytes = bincode::serialize(&node).unwrap();
+        println!("jmt_codec/{}: {} bytes", node_type, bytes.len());
+
+        group.bench_function(BenchmarkId::new("serialize", node_type), |b| {
+            b.iter(|| bincode::serialize(&node).unwrap())
+        });
+        group.bench_function(BenchmarkId::new("deserialize", node_type), |b| {
+            b.iter(|| bincode::deserialize::<Node>(&bytes).unwrap())
+        });
+    }
+    group.finish();
+    export_results("jmt_codec", &BTreeMap::new());
+}
+
+const BATCH_TREE_SIZE: usize = 1000;
+
+fn bench_batch_get<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    let (store, key_hashes) = populated_store::<H>(BATCH_TREE_SIZE);
+    for batch_len in [10, 100, 1000] {
+        sample_for_size(group, batch_len);
+        let batch = &key_hashes[..batch_len];
+        group.throughput(Throughput::Elements(batch.len() as u64));
+        group.bench_function(BenchmarkId::new(format!("get_with_proof/{}", hasher), batch_len), |b| {
+            b.iter(|| prove_all::<_, H>(&store, batch))
+        });
+        group.bench_function(BenchmarkId::new(format!("get_many_with_proof/{}", hasher), batch_len), |b| {
+            b.iter(|| get_many_with_proof::<_, H>(&store, batch, 0).unwrap())
+        });
+    }
+}
+
+fn jmt_batch_get_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_batch_get");
+    bench_batch_get::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_batch_get::<Blake2s256>(&mut group, "blake2s");
+        bench_batch_get::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_batch_get", &BTreeMap::new());
+}
+
+const DEEP_TREE_SIZE: usize = 1000;
+
+fn bench_deep<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    sample_for_size(group, DEEP_TREE_SIZE);
+    for prefix_nibbles in [4, 8, 12] {
+        let value_set: Vec<(KeyHash, Option<OwnedValue>)> = workload::shared_prefix_key_hashes(DEEP_TREE_SIZE, prefix_nibbles)
+            .into_iter()
+            .enumerate()
+            .map(|(i, key_hash)| (key_hash, Some(workload::value(i, VALUE_LEN))))
+            .collect();
+        let key_hashes: Vec<KeyHash> = value_set.iter().map(|(key_hash, _)| *key_hash).collect();
+
+        let store = InMemoryTreeStore::new();
+        let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, H> = JellyfishMerkleTree::new(&store);
+        let (_root, batch) = jmt.put_value_set(value_set.clone(), 0).unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+        let stats = store.tree_stats().unwrap();
+        println!(
+            "jmt_deep/{}/prefix_{}: height {}, {} internal nodes, {} leaves",
+            hasher, prefix_nibbles, stats.height, stats.internal_nodes, stats.leaf_nodes
+        );
+
+        group.throughput(Throughput::Elements(key_hashes.len() as u64));
+        let parameter = format!("prefix_{}", prefix_nibbles);
+        group.bench_function(BenchmarkId::new(format!("insert/{}", hasher), &parameter), |b| {
+            b.iter_batched(
+                || (InMemoryTreeStore::new(), value_set.clone()),
+                |(store, value_set)| {
+                    let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, H> = JellyfishMerkleTree::new(&store);
+                    let (_root, batch) = jmt.put_value_set(value_set, 0).unwrap();
+                    store.write_node_batch(&batch.node_batch).unwrap();
+                    store
+                },
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_function(BenchmarkId::new(format!("get/{}", hasher), &parameter), |b| {
+            b.iter(|| prove_all::<_, H>(&store, &key_hashes))
+        });
+    }
+}
+
+fn jmt_deep_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_deep");
+    bench_deep::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_deep::<Blake2s256>(&mut group, "blake2s");
+        bench_deep::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_deep", &BTreeMap::new());
+}
+
+const ZIPF_TREE_SIZE: usize = 1000;
+const ZIPF_LOOKUPS: usize = 1000;
+// Well under the tree's node count, so only a skewed workload keeps its hot paths cached
+const ZIPF_CACHE_NODES: usize = 256;
+
+fn bench_zipf<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    sample_for_size(group, ZIPF_LOOKUPS);
+    for skew in [0.8, 0.99, 1.2] {
+        let (store, key_hashes) = populated_store::<H>(ZIPF_TREE_SIZE);
+        let lookups: Vec<KeyHash> = workload::zipf_indices(workload::BENCH_SEED, ZIPF_LOOKUPS, ZIPF_TREE_SIZE, skew)
+            .into_iter()
+            .map(|i| key_hashes[i])
+            .collect();
+        let cached = CachingReader::new(store, NonZeroUsize::new(ZIPF_CACHE_NODES).unwrap());
+        // The first pass starts from an empty cache, and leaves it warm for the timed passes
+        prove_all::<_, H>(&cached, &lookups);
+        println!(
+            "jmt_zipf/{}/skew_{}: {:.1}% cache hits",
+            hasher,
+            skew,
+            100.0 * cached.hits() as f64 / (cached.hits() + cached.misses()) as f64
+        );
+
+        group.throughput(Throughput::Elements(lookups.len() as u64));
+        let parameter = format!("skew_{}", skew);
+        group.bench_function(BenchmarkId::new(format!("get_with_proof/{}", hasher), &parameter), |b| {
+            b.iter(|| prove_all::<_, H>(cached.inner(), &lookups))
+        });
+        group.bench_function(BenchmarkId::new(format!("cached/{}", hasher), &parameter), |b| {
+            b.iter(|| prove_all::<_, H>(&cached, &lookups))
+        });
+    }
+}
+
+fn jmt_zipf_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_zipf");
+    bench_zipf::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_zipf::<Blake2s256>(&mut group, "blake2s");
+        bench_zipf::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_zipf", &BTreeMap::new());
+}
+
+const NODE_READ_LOOKUPS: usize = 100;
+
+// Node reads depend only on the tree's shape, which keys hashed with Sha256 fix for every hasher
+fn jmt_node_reads_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_node_reads");
+
+    for size in sizes([10, 100, 1000, 10000]).iter() {
+        sample_for_size(&mut group, *size);
+        let (store, key_hashes) = populated_store::<Sha256>(*size);
+        let lookups: Vec<KeyHash> = workload::random_indices(workload::BENCH_SEED, NODE_READ_LOOKUPS, *size)
+            .into_iter()
+            .map(|i| key_hashes[i])
+            .collect();
+        let instrumented = InstrumentedReader::new(store);
+
+        // Counted once up front, since the timed passes would keep adding to the counters
+        prove_all::<_, Sha256>(&instrumented, &lookups);
+        let counts = instrumented.counts();
+        println!(
+            "jmt_node_reads/{}: {:.2} node reads and {:.2} value reads per get",
+            size,
+            counts.nodes as f64 / NODE_READ_LOOKUPS as f64,
+            counts.values as f64 / NODE_READ_LOOKUPS as f64
+        );
+
+        group.throughput(Throughput::Elements(lookups.len() as u64));
+        group.bench_with_input(BenchmarkId::new("get_with_proof", *size), size, |b, _| {
+            b.iter(|| prove_all::<_, Sha256>(&instrumented, &lookups))
+        });
+    }
     group.finish();
+    export_results("jmt_node_reads", &BTreeMap::new());
+}
+
+const WRITE_AMP_TREE_SIZE: usize = 1000;
+
+fn jmt_write_amp_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_write_amp");
+    let (template, key_hashes) = populated_store::<Sha256>(WRITE_AMP_TREE_SIZE);
+
+    for update_len in [1, 10, 100].iter() {
+        sample_for_size(&mut group, *update_len);
+        let updates: Vec<(KeyHash, Option<OwnedValue>)> = key_hashes[..*update_len]
+            .iter()
+            .enumerate()
+            .map(|(i, key_hash)| (*key_hash, Some(workload::value(WRITE_AMP_TREE_SIZE + i, VALUE_LEN))))
+            .collect();
+        let update = |store: &InstrumentedReader<InMemoryTreeStore>, updates| {
+            let jmt: JellyfishMerkleTree<'_, _, Sha256> = JellyfishMerkleTree::new(store);
+            let (_root, batch) = jmt.put_value_set(updates, 1).unwrap();
+            store.write_node_batch(&batch.node_batch).unwrap();
+        };
+
+        let instrumented = InstrumentedReader::new(template.clone_snapshot().unwrap());
+        update(&instrumented, updates.clone());
+        let counts = instrumented.write_counts();
+        println!(
+            "jmt_write_amp/{}: {} nodes written for {} values, {:.2} nodes per value",
+            update_len,
+            counts.nodes,
+            counts.values,
+            counts.amplification().unwrap()
+        );
+
+        group.throughput(Throughput::Elements(updates.len() as u64));
+        group.bench_with_input(BenchmarkId::new("update", *update_len), update_len, |b, _| {
+            b.iter_batched(
+                || (InstrumentedReader::new(template.clone_snapshot().unwrap()), updates.clone()),
+                |(store, updates)| {
+                    update(&store, updates);
+                    store
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+    export_results("jmt_write_amp", &BTreeMap::new());
+}
+
+fn bench_read<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    for size in [10, 100, 1000].iter() {
+        sample_for_size(group, *size);
+        let (store, key_hashes) = populated_store::<H>(*size);
+        let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, H> = JellyfishMerkleTree::new(&store);
+
+        group.throughput(Throughput::Elements(key_hashes.len() as u64));
+        group.bench_with_input(BenchmarkId::new(format!("with_proof/{}", hasher), *size), size, |b, _| {
+            b.iter(|| prove_all::<_, H>(&store, &key_hashes))
+        });
+        // jmt's `get` skips the tree entirely and reads the value straight from the store
+        group.bench_with_input(BenchmarkId::new(format!("no_proof/{}", hasher), *size), size, |b, _| {
+            b.iter(|| {
+                for key_hash in &key_hashes {
+                    jmt.get(*key_hash, 0).unwrap();
+                }
+            })
+        });
+    }
+}
+
+fn jmt_read_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_read");
+    bench_read::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_read::<Blake2s256>(&mut group, "blake2s");
+        bench_read::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_read", &BTreeMap::new());
+}
+
+const VERSIONS_TREE_SIZE: usize = 1000;
+
+// Commits every key of `value_set`, spread evenly over `versions` consecutive versions
+fn commit_across_versions<H: SimpleHasher>(store: &InMemoryTreeStore, value_set: Vec<(KeyHash, Option<OwnedValue>)>, versions: usize) {
+    let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, H> = JellyfishMerkleTree::new(store);
+    let chunk_len = value_set.len().div_ceil(versions);
+    for (version, chunk) in value_set.chunks(chunk_len).enumerate() {
+        let (_root, batch) = jmt.put_value_set(chunk.to_vec(), version as Version).unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+    }
+}
+
+fn bench_versions<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    sample_for_size(group, VERSIONS_TREE_SIZE);
+    let value_set: Vec<(KeyHash, Option<OwnedValue>)> = (0..VERSIONS_TREE_SIZE)
+        .map(|i| (KeyHash::with::<Sha256>(workload::key(i)), Some(workload::value(i, VALUE_LEN))))
+        .collect();
+
+    for versions in [1, 10, 100, 1000].iter() {
+        let store = InMemoryTreeStore::new();
+        commit_across_versions::<H>(&store, value_set.clone(), *versions);
+        let stats = store.tree_stats().unwrap();
+        println!(
+            "jmt_versions/{}/{}: {} stored nodes ({} internal, {} leaves)",
+            hasher,
+            versions,
+            stats.internal_nodes + stats.leaf_nodes,
+            stats.internal_nodes,
+            stats.leaf_nodes
+        );
+
+        group.throughput(Throughput::Elements(value_set.len() as u64));
+        group.bench_with_input(BenchmarkId::new(format!("commit/{}", hasher), *versions), versions, |b, &versions| {
+            b.iter_batched(
+                || (InMemoryTreeStore::new(), value_set.clone()),
+                |(store, value_set)| {
+                    commit_across_versions::<H>(&store, value_set, versions);
+                    store
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+}
+
+fn jmt_versions_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_versions");
+    bench_versions::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_versions::<Blake2s256>(&mut group, "blake2s");
+        bench_versions::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_versions", &BTreeMap::new());
+}
+
+// About 1 MB of values in every configuration, from a few large values to many small ones
+const SHAPES: [(usize, usize); 4] = [(100, 10_240), (1_000, 1_024), (10_000, 100), (100_000, 10)];
+
+fn jmt_shape_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_shape");
+    for (key_count, value_len) in SHAPES.into_iter().filter(|(key_count, _value_len)| runs_size(*key_count)) {
+        sample_for_size(&mut group, key_count);
+        let parameter = format!("{}x{}", key_count, value_len);
+        let insert = Insert { strategy: CommitStrategy::Batched, distribution: KeyDistribution::Sequential, value_len };
+        let get = Get { distribution: KeyDistribution::Sequential, value_len };
+
+        group.throughput(Throughput::Bytes((key_count * value_len) as u64));
+        group.bench_function(BenchmarkId::new("insert", &parameter), |b| {
+            b.iter_batched(
+                || (InMemoryTreeStore::new(), insert.prepare(key_count)),
+                |(store, batch)| {
+                    insert.run::<_, Sha256>(&store, batch).unwrap();
+                    store
+                },
+                BatchSize::LargeInput,
+            )
+        });
+
+        let store = InMemoryTreeStore::new();
+        get.setup::<_, Sha256>(&store, key_count).unwrap();
+        let batch = get.prepare(key_count);
+        group.bench_function(BenchmarkId::new("get", &parameter), |b| {
+            b.iter_batched(|| batch.clone(), |batch| get.run::<_, Sha256>(&store, batch).unwrap(), BatchSize::LargeInput)
+        });
+    }
+    group.finish();
+    export_results("jmt_shape", &BTreeMap::new());
+}
+
+const DEDUP_BATCH_LEN: usize = 1000;
+
+// A batch of DEDUP_BATCH_LEN writes in which `duplicate_percent` of them repeat an earlier key
+fn value_set_with_duplicates(duplicate_percent: usize) -> ValueSet {
+    let distinct = DEDUP_BATCH_LEN - DEDUP_BATCH_LEN * duplicate_percent / 100;
+    (0..DEDUP_BATCH_LEN)
+        .map(|i| (KeyHash::with::<Sha256>(workload::key(i % distinct)), Some(workload::value(i, VALUE_LEN))))
+        .collect()
+}
+
+fn jmt_dedup_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_dedup");
+    sample_for_size(&mut group, DEDUP_BATCH_LEN);
+    for duplicate_percent in [0, 10, 50] {
+        let value_set = value_set_with_duplicates(duplicate_percent);
+        let parameter = format!("dup_{}", duplicate_percent);
+        group.throughput(Throughput::Elements(value_set.len() as u64));
+
+        group.bench_function(BenchmarkId::new("dedup", &parameter), |b| {
+            b.iter_batched(|| value_set.clone(), dedup_value_set, BatchSize::SmallInput)
+        });
+        // jmt resolves the duplicates itself, so this is the baseline the explicit pass adds to
+        group.bench_function(BenchmarkId::new("put_value_set", &parameter), |b| {
+            b.iter_batched(
+                || (InMemoryTreeStore::new(), value_set.clone()),
+                |(store, value_set)| {
+                    let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&store);
+                    tree.put_value_set(value_set, 0).unwrap()
+                },
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_function(BenchmarkId::new("dedup_then_put", &parameter), |b| {
+            b.iter_batched(
+                || (InMemoryTreeStore::new(), value_set.clone()),
+                |(store, value_set)| {
+                    let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&store);
+                    tree.put_value_set(dedup_value_set(value_set), 0).unwrap()
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+    export_results("jmt_dedup", &BTreeMap::new());
+}
+
+fn jmt_proof_json_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_proof_json");
+    for size in [10, 100, 1000] {
+        sample_for_size(&mut group, size);
+        let (store, key_hashes) = populated_store::<Sha256>(size);
+        let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Sha256> = JellyfishMerkleTree::new(&store);
+        let (_value, proof) = jmt.get_with_proof(key_hashes[0], 0).unwrap();
+        println!(
+            "jmt_proof_json/{}: {} bytes as bincode, {} bytes as JSON",
+            size,
+            bincode::serialize(&proof).unwrap().len(),
+            serde_json::to_vec(&proof_to_json(&proof)).unwrap().len()
+        );
+
+        group.bench_function(BenchmarkId::new("bincode", size), |b| b.iter(|| bincode::serialize(&proof).unwrap()));
+        group.bench_function(BenchmarkId::new("json", size), |b| {
+            b.iter(|| serde_json::to_vec(&proof_to_json(&proof)).unwrap())
+        });
+    }
+    group.finish();
+    export_results("jmt_proof_json", &BTreeMap::new());
+}
+
+const INCREMENTAL_BASE_SIZE: usize = 100_000;
+
+// The pairs for keys `range`, each value shifted by `value_offset` so that overwrites differ
+// from what the key was first written with
+fn value_set_for(range: std::ops::Range<usize>, value_offset: usize) -> ValueSet {
+    range
+        .map(|i| (KeyHash::with::<Sha256>(workload::key(i)), Some(workload::value(value_offset + i, VALUE_LEN))))
+        .collect()
+}
+
+fn bench_incremental<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    let base = InMemoryTreeStore::new();
+    commit_streamed::<_, H>(&base, workload::value_set_iter(INCREMENTAL_BASE_SIZE, VALUE_LEN), 0).unwrap();
+
+    for delta in [1, 10, 100, 1000] {
+        sample_for_size(group, delta);
+        // Keys past the end of the base tree, so every one of them is a fresh insert
+        let value_set = value_set_for(INCREMENTAL_BASE_SIZE..INCREMENTAL_BASE_SIZE + delta, 0);
+        group.throughput(Throughput::Elements(delta as u64));
+        // Every iteration commits the same keys as version 1 on top of version 0, and rewriting
+        // identical nodes leaves the store as it was, so the base never grows between iterations
+        group.bench_function(BenchmarkId::new(format!("insert/{}", hasher), delta), |b| {
+            b.iter_batched(
+                || value_set.clone(),
+                |value_set| commit_streamed::<_, H>(&base, value_set, 1).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_function(BenchmarkId::new(format!("into_empty/{}", hasher), delta), |b| {
+            b.iter_batched(
+                || (InMemoryTreeStore::new(), value_set.clone()),
+                |(store, value_set)| {
+                    commit_streamed::<_, H>(&store, value_set, 0).unwrap();
+                    store
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+}
+
+fn jmt_incremental_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_incremental");
+    bench_incremental::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_incremental::<Blake2s256>(&mut group, "blake2s");
+        bench_incremental::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_incremental", &BTreeMap::new());
+}
+
+fn bench_overwrite<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    for size in [10, 100, 1000] {
+        sample_for_size(group, size);
+        let (store, _key_hashes) = populated_store::<H>(size);
+        // Both commit `size` values as version 1 over the same base tree, and rewrite identical
+        // nodes on every iteration, so the store stays the same between iterations
+        let overwrites = value_set_for(0..size, size);
+        let fresh_keys = value_set_for(size..2 * size, 0);
+        group.throughput(Throughput::Elements(size as u64));
+
+        group.bench_function(BenchmarkId::new(format!("overwrite/{}", hasher), size), |b| {
+            b.iter_batched(
+                || overwrites.clone(),
+                |value_set| commit_streamed::<_, H>(&store, value_set, 1).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_function(BenchmarkId::new(format!("fresh_insert/{}", hasher), size), |b| {
+            b.iter_batched(
+                || fresh_keys.clone(),
+                |value_set| commit_streamed::<_, H>(&store, value_set, 1).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+}
+
+fn jmt_overwrite_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_overwrite");
+    bench_overwrite::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_overwrite::<Blake2s256>(&mut group, "blake2s");
+        bench_overwrite::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_overwrite", &BTreeMap::new());
+}
+
+const LATENCY_READS: usize = 10;
+
+fn jmt_latency_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_latency");
+    for size in sizes([100, 1000, 10000]) {
+        sample_for_size(&mut group, size);
+        for micros in [0, 10, 100] {
+            let (store, key_hashes) = populated_store::<Sha256>(size);
+            let reads: Vec<KeyHash> = workload::random_indices(workload::BENCH_SEED, LATENCY_READS, size)
+                .into_iter()
+                .map(|i| key_hashes[i])
+                .collect();
+            let slow = LatencyTreeStore::new(store, Duration::from_micros(micros));
+            group.throughput(Throughput::Elements(reads.len() as u64));
+            group.bench_function(BenchmarkId::new(format!("get_with_proof/{}us", micros), size), |b| {
+                b.iter(|| prove_all::<_, Sha256>(&slow, &reads))
+            });
+        }
+    }
+    group.finish();
+    export_results("jmt_latency", &BTreeMap::new());
+}
+
+const PROOF_CACHE_TREE_SIZE: usize = 10_000;
+
+fn jmt_proof_cache_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_proof_cache");
+    let (store, key_hashes) = populated_store::<Sha256>(PROOF_CACHE_TREE_SIZE);
+    let counting = InstrumentedReader::new(store);
+
+    for batch_len in sizes([1, 10, 100, 1000, 10000]) {
+        sample_for_size(&mut group, batch_len);
+        let batch: Vec<KeyHash> = workload::random_indices(workload::BENCH_SEED, batch_len, PROOF_CACHE_TREE_SIZE)
+            .into_iter()
+            .map(|i| key_hashes[i])
+            .collect();
+        counting.reset();
+        prove_all::<_, Sha256>(&counting, &batch);
+        let uncached = counting.counts().nodes;
+        counting.reset();
+        get_many_with_proof::<_, Sha256>(&counting, &batch, 0).unwrap();
+        let cached = counting.counts().nodes;
+        println!(
+            "jmt_proof_cache/{}: {} node reads uncached, {} cached ({:.1}% saved)",
+            batch_len,
+            uncached,
+            cached,
+            100.0 * (uncached - cached) as f64 / uncached as f64
+        );
+
+        // The cache lives for one `get_many_with_proof` call, so each iteration starts it cold
+        group.throughput(Throughput::Elements(batch_len as u64));
+        group.bench_function(BenchmarkId::new("uncached", batch_len), |b| {
+            b.iter(|| prove_all::<_, Sha256>(counting.inner(), &batch))
+        });
+        group.bench_function(BenchmarkId::new("cached", batch_len), |b| {
+            b.iter(|| get_many_with_proof::<_, Sha256>(counting.inner(), &batch, 0).unwrap())
+        });
+    }
+    group.finish();
+    export_results("jmt_proof_cache", &BTreeMap::new());
+}
+
+const CHURN_TREE_SIZE: usize = 1000;
+const CHURN_VERSIONS: usize = 10;
+
+// The live keys are a window of CHURN_TREE_SIZE over a universe twice that size. Each version
+// deletes the first `churned` keys of the window and inserts the `churned` keys just past its
+// end, so every deleted key is reinserted once the window wraps round
+fn churn_batches(churned: usize) -> Vec<ValueSet> {
+    let universe = 2 * CHURN_TREE_SIZE;
+    let key_hash = |i: usize| KeyHash::with::<Sha256>(workload::key(i % universe));
+    (0..CHURN_VERSIONS)
+        .map(|version| {
+            let start = version * churned;
+            let deletes = (start..start + churned).map(|i| (key_hash(i), None));
+            let inserts = (start + CHURN_TREE_SIZE..start + CHURN_TREE_SIZE + churned)
+                .map(|i| (key_hash(i), Some(workload::value(version, VALUE_LEN))));
+            deletes.chain(inserts).collect()
+        })
+        .collect()
+}
+
+// Commits `batches` as versions 1.. on top of `store` and returns the stale nodes each reported
+fn churn<H: SimpleHasher>(store: &InMemoryTreeStore, batches: Vec<ValueSet>) -> Vec<usize> {
+    let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, H> = JellyfishMerkleTree::new(store);
+    batches
+        .into_iter()
+        .enumerate()
+        .map(|(i, batch)| {
+            let (_root, update) = jmt.put_value_set(batch, i as Version + 1).unwrap();
+            store.write_node_batch(&update.node_batch).unwrap();
+            update.stale_node_index_batch.len()
+        })
+        .collect()
+}
+
+fn bench_churn<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    let (base, _key_hashes) = populated_store::<H>(CHURN_TREE_SIZE);
+    for churn_percent in [10, 50, 90] {
+        sample_for_size(group, CHURN_TREE_SIZE * churn_percent / 100);
+        let batches = churn_batches(CHURN_TREE_SIZE * churn_percent / 100);
+        let parameter = format!("churn_{}", churn_percent);
+        let stale = churn::<H>(&base.clone_snapshot().unwrap(), batches.clone());
+        println!("jmt_churn/{}/{}: {} stale nodes, per version {:?}", hasher, parameter, stale.iter().sum::<usize>(), stale);
+
+        group.throughput(Throughput::Elements(batches.iter().map(|batch| batch.len() as u64).sum()));
+        group.bench_function(BenchmarkId::new(format!("commit/{}", hasher), &parameter), |b| {
+            b.iter_batched(
+                || (base.clone_snapshot().unwrap(), batches.clone()),
+                |(store, batches)| {
+                    churn::<H>(&store, batches);
+                    store
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+}
+
+fn jmt_churn_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_churn");
+    bench_churn::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_churn::<Blake2s256>(&mut group, "blake2s");
+        bench_churn::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_churn", &BTreeMap::new());
+}
+
+// jmt sorts every batch by key hash before building nodes, so only that sort should differ
+fn jmt_order_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_order");
+    for size in sizes([100, 1000, 10000]) {
+        sample_for_size(&mut group, size);
+        let mut sorted: ValueSet = workload::value_set_iter(size, VALUE_LEN).collect();
+        sorted.sort_by_key(|(key_hash, _value)| *key_hash);
+        let mut shuffled = sorted.clone();
+        shuffled.shuffle(&mut ChaCha8Rng::seed_from_u64(workload::BENCH_SEED));
+
+        group.throughput(Throughput::Elements(size as u64));
+        for (order, value_set) in [("sorted", &sorted), ("shuffled", &shuffled)] {
+            group.bench_function(BenchmarkId::new(order, size), |b| {
+                b.iter_batched(
+                    || (InMemoryTreeStore::new(), value_set.clone()),
+                    |(store, value_set)| {
+                        commit_streamed::<_, Sha256>(&store, value_set, 0).unwrap();
+                        store
+                    },
+                    BatchSize::LargeInput,
+                )
+            });
+        }
+    }
+    group.finish();
+    export_results("jmt_order", &BTreeMap::new());
+}
+
+const BUCKET_TREE_SIZE: usize = 1000;
+
+fn bench_buckets<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    sample_for_size(group, BUCKET_TREE_SIZE);
+    for buckets in [1, 2, 4, 16] {
+        let key_hashes = workload::bucketed_key_hashes(BUCKET_TREE_SIZE, buckets);
+        let value_set: ValueSet = key_hashes
+            .iter()
+            .enumerate()
+            .map(|(i, key_hash)| (*key_hash, Some(workload::value(i, VALUE_LEN))))
+            .collect();
+
+        let store = InMemoryTreeStore::new();
+        commit_streamed::<_, H>(&store, value_set.clone(), 0).unwrap();
+        let stats = store.tree_stats().unwrap();
+        println!(
+            "jmt_buckets/{}/buckets_{}: height {}, {} internal nodes, {} leaves",
+            hasher, buckets, stats.height, stats.internal_nodes, stats.leaf_nodes
+        );
+
+        group.throughput(Throughput::Elements(key_hashes.len() as u64));
+        let parameter = format!("buckets_{}", buckets);
+        group.bench_function(BenchmarkId::new(format!("insert/{}", hasher), &parameter), |b| {
+            b.iter_batched(
+                || (InMemoryTreeStore::new(), value_set.clone()),
+                |(store, value_set)| {
+                    commit_streamed::<_, H>(&store, value_set, 0).unwrap();
+                    store
+                },
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_function(BenchmarkId::new(format!("get/{}", hasher), &parameter), |b| {
+            b.iter(|| prove_all::<_, H>(&store, &key_hashes))
+        });
+    }
+}
+
+fn jmt_buckets_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_buckets");
+    bench_buckets::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_buckets::<Blake2s256>(&mut group, "blake2s");
+        bench_buckets::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_buckets", &BTreeMap::new());
+}
+
+const VERIFY_BATCH_TREE_SIZE: usize = 1000;
+
+fn bench_verify_batch<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    let (store, key_hashes) = populated_store::<H>(VERIFY_BATCH_TREE_SIZE);
+    let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, H> = JellyfishMerkleTree::new(&store);
+    let root = jmt.get_root_hash(0).unwrap();
+    for batch_len in [10, 100, 1000] {
+        sample_for_size(group, batch_len);
+        let items: Vec<_> = workload::random_indices(workload::BENCH_SEED, batch_len, VERIFY_BATCH_TREE_SIZE)
+            .into_iter()
+            .map(|i| {
+                let (value, proof) = jmt.get_with_proof(key_hashes[i], 0).unwrap();
+                (key_hashes[i], value, proof)
+            })
+            .collect();
+        verify_batch(root, &items).unwrap();
+
+        group.throughput(Throughput::Elements(batch_len as u64));
+        group.bench_function(BenchmarkId::new(format!("loop/{}", hasher), batch_len), |b| {
+            b.iter(|| {
+                for (key_hash, value, proof) in &items {
+                    proof.verify(root, *key_hash, value.as_ref()).unwrap();
+                }
+            })
+        });
+        group.bench_function(BenchmarkId::new(format!("verify_batch/{}", hasher), batch_len), |b| {
+            b.iter(|| verify_batch(root, &items).unwrap())
+        });
+    }
+}
+
+fn jmt_verify_batch_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_verify_batch");
+    bench_verify_batch::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_verify_batch::<Blake2s256>(&mut group, "blake2s");
+        bench_verify_batch::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_verify_batch", &BTreeMap::new());
+}
+
+const NODE_BATCH_BASE_SIZE: usize = 10_000;
+
+// Batch contents depend only on which keys are committed, so one hasher covers them. Only
+// `put_value_set` is timed; the batch is never written, so every iteration sees the same base
+fn jmt_node_batch_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_node_batch");
+    let empty = InMemoryTreeStore::new();
+    let (base, _key_hashes) = populated_store::<Sha256>(NODE_BATCH_BASE_SIZE);
+    for size in [1, 10, 100, 1000] {
+        sample_for_size(&mut group, size);
+        let value_set = value_set_for(NODE_BATCH_BASE_SIZE..NODE_BATCH_BASE_SIZE + size, 0);
+        let targets = [("into_empty", &empty, 0), ("onto_10000", &base, 1)];
+        for (target, store, version) in targets {
+            let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Sha256> = JellyfishMerkleTree::new(store);
+            let (_root, batch) = jmt.put_value_set(value_set.clone(), version).unwrap();
+            println!(
+                "jmt_node_batch/{}/{}: {} nodes, {} values",
+                target,
+                size,
+                batch.node_batch.nodes().len(),
+                batch.node_batch.values().len()
+            );
+
+            group.throughput(Throughput::Elements(size as u64));
+            group.bench_function(BenchmarkId::new(target, size), |b| {
+                b.iter_batched(
+                    || value_set.clone(),
+                    |value_set| jmt.put_value_set(value_set, version).unwrap(),
+                    BatchSize::SmallInput,
+                )
+            });
+        }
+    }
+    group.finish();
+    export_results("jmt_node_batch", &BTreeMap::new());
+}
+
+const DATASET_READS: usize = 1000;
+
+// Only runs when a dataset file is named, since there is no real data to ship with the repo
+fn jmt_dataset_benchmark(c: &mut Criterion) {
+    let path = match std::env::var_os(workload::DATASET_VAR) {
+        Some(path) => path,
+        None => return,
+    };
+    let pairs = workload::load_pairs(&path, VALUE_LEN).unwrap();
+    assert!(!pairs.is_empty(), "{} names an empty dataset", workload::DATASET_VAR);
+    let size = pairs.len();
+    let store = InMemoryTreeStore::new();
+    workload::build_tree::<_, Sha256>(&store, &pairs).unwrap();
+    let stats = store.tree_stats().unwrap();
+    println!(
+        "jmt_dataset/{}: loaded from {}, height {}, {} internal nodes, {} leaves",
+        size,
+        path.to_string_lossy(),
+        stats.height,
+        stats.internal_nodes,
+        stats.leaf_nodes
+    );
+
+    let mut group = benchmark_group(c, "jmt_dataset");
+    sample_for_size(&mut group, size);
+    group.throughput(Throughput::Elements(size as u64));
+    group.bench_function(BenchmarkId::new("insert", size), |b| {
+        b.iter_batched(
+            InMemoryTreeStore::new,
+            |store| {
+                workload::build_tree::<_, Sha256>(&store, &pairs).unwrap();
+                store
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Sha256> = JellyfishMerkleTree::new(&store);
+    let reads: Vec<KeyHash> = workload::random_indices(workload::BENCH_SEED, DATASET_READS, size)
+        .into_iter()
+        .map(|i| KeyHash::with::<Sha256>(&pairs[i].0))
+        .collect();
+    sample_for_size(&mut group, DATASET_READS);
+    group.throughput(Throughput::Elements(DATASET_READS as u64));
+    group.bench_function(BenchmarkId::new("get", size), |b| {
+        b.iter(|| {
+            for key_hash in &reads {
+                jmt.get_with_proof(*key_hash, 0).unwrap();
+            }
+        })
+    });
+    group.finish();
+    let mut tree_stats = BTreeMap::new();
+    tree_stats.insert(size, stats);
+    export_results("jmt_dataset", &tree_stats);
+}
+
+const LAZY_PROOF_TREE_SIZE: usize = 10_000;
+const LAZY_PROOF_READS: usize = 1000;
+// Share of reads whose proof is eventually wanted
+const LAZY_PROOF_PERCENT: usize = 10;
+
+fn bench_lazy_proof<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    sample_for_size(group, LAZY_PROOF_READS);
+    let (store, key_hashes) = populated_store::<H>(LAZY_PROOF_TREE_SIZE);
+    let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, H> = JellyfishMerkleTree::new(&store);
+    let reads: Vec<KeyHash> = workload::random_indices(workload::BENCH_SEED, LAZY_PROOF_READS, LAZY_PROOF_TREE_SIZE)
+        .into_iter()
+        .map(|i| key_hashes[i])
+        .collect();
+    // Every tenth read, so the proved reads are spread through the run
+    let needs_proof = |i: usize| i.is_multiple_of(100 / LAZY_PROOF_PERCENT);
+
+    group.throughput(Throughput::Elements(LAZY_PROOF_READS as u64));
+    // Every read pays for a proof, wanted or not
+    group.bench_function(BenchmarkId::new(format!("eager/{}", hasher), LAZY_PROOF_PERCENT), |b| {
+        b.iter(|| {
+            for key_hash in &reads {
+                jmt.get_with_proof(*key_hash, 0).unwrap();
+            }
+        })
+    });
+    // The proved reads walk the tree a second time rather than reusing the value read
+    group.bench_function(BenchmarkId::new(format!("lazy/{}", hasher), LAZY_PROOF_PERCENT), |b| {
+        b.iter(|| {
+            for (i, key_hash) in reads.iter().enumerate() {
+                jmt.get(*key_hash, 0).unwrap();
+                if needs_proof(i) {
+                    jmt.get_with_proof(*key_hash, 0).unwrap();
+                }
+            }
+        })
+    });
+}
+
+fn jmt_lazy_proof_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_lazy_proof");
+    bench_lazy_proof::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_lazy_proof::<Blake2s256>(&mut group, "blake2s");
+        bench_lazy_proof::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_lazy_proof", &BTreeMap::new());
+}
+
+const DIFF_TREE_SIZE: usize = 10_000;
+
+// The diff reads only values, so the tree hasher does not matter
+fn jmt_diff_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_diff");
+    let (base, _key_hashes) = populated_store::<Sha256>(DIFF_TREE_SIZE);
+    for changed in [1, 10, 100, 1000] {
+        sample_for_size(&mut group, changed);
+        let store = base.clone_snapshot().unwrap();
+        commit_streamed::<_, Sha256>(&store, value_set_for(0..changed, DIFF_TREE_SIZE), 1).unwrap();
+        assert_eq!(store.diff(0, 1).unwrap().len(), changed);
+
+        group.throughput(Throughput::Elements(changed as u64));
+        group.bench_function(BenchmarkId::new("diff", changed), |b| b.iter(|| store.diff(0, 1).unwrap()));
+    }
+    group.finish();
+    export_results("jmt_diff", &BTreeMap::new());
+}
+
+// Every workload hashes keys with Sha256 before touching the tree, whatever the tree hasher.
+// Each iteration hashes one key, so the time is the per-key cost to subtract from a
+// per-element time elsewhere
+fn jmt_key_hash_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_key_hash");
+    sample_for_size(&mut group, 1);
+    for key_len in [16, 32, 256, 1024] {
+        let key = workload::value(key_len, key_len);
+        group.throughput(Throughput::Bytes(key_len as u64));
+        group.bench_function(BenchmarkId::new("sha256", key_len), |b| b.iter(|| KeyHash::with::<Sha256>(&key)));
+    }
+    group.finish();
+    export_results("jmt_key_hash", &BTreeMap::new());
+}
+
+// Commits one key per hash at version 0, then runs `ops` over the tree
+fn build_and_mix<S: TreeReader + TreeWriter>(store: &S, key_hashes: &[KeyHash], ops: &[Op]) {
+    let value_set = key_hashes.iter().enumerate().map(|(i, key_hash)| (*key_hash, Some(workload::value(i, VALUE_LEN))));
+    commit_streamed::<_, Sha256>(store, value_set, 0).unwrap();
+    run_mixed_ops::<_, Sha256>(store, key_hashes, ops);
+}
+
+// The log holds only store calls, so replaying it skips all hashing and tree logic; the tree
+// hasher of the recorded run only changes which nodes the log contains
+fn jmt_replay_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_replay");
+    for size in [100, 1000] {
+        sample_for_size(&mut group, size);
+        let key_hashes: Vec<KeyHash> = (0..size).map(|i| KeyHash::with::<Sha256>(workload::key(i))).collect();
+        let ops = workload::mixed_ops(workload::BENCH_SEED, MIXED_OPS, size, 90);
+        let recording = RecordingTreeStore::new(InMemoryTreeStore::new());
+        build_and_mix(&recording, &key_hashes, &ops);
+        let log = recording.drain_log().unwrap();
+        println!("jmt_replay/{}: {} store calls recorded", size, log.len());
+
+        // The initial commit and the 100 mixed operations, run for real on an unrecorded store
+        group.throughput(Throughput::Elements(log.len() as u64));
+        group.bench_function(BenchmarkId::new("direct", size), |b| {
+            b.iter_batched(
+                InMemoryTreeStore::new,
+                |store| {
+                    build_and_mix(&store, &key_hashes, &ops);
+                    store
+                },
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_function(BenchmarkId::new("replay", size), |b| {
+            b.iter_batched(
+                InMemoryTreeStore::new,
+                |store| {
+                    replay(&store, &log).unwrap();
+                    store
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+    export_results("jmt_replay", &BTreeMap::new());
+}
+
+const WRITER_COMMITS: usize = 64;
+const WRITER_COMMIT_KEYS: usize = 10;
+
+// Everything a writer does before it needs the lock: choosing keys, generating values, hashing
+fn writer_batch(commit: usize) -> ValueSet {
+    (0..WRITER_COMMIT_KEYS)
+        .map(|k| {
+            let i = (commit * WRITER_COMMIT_KEYS + k) % CONCURRENT_TREE_SIZE;
+            (KeyHash::with::<Sha256>(workload::key(i)), Some(workload::value(commit + i, VALUE_LEN)))
+        })
+        .collect()
+}
+
+// Splits the commits over `threads` writers that share one commit lock, and returns how long
+// they spent waiting for it in total
+fn commit_concurrently(store: &Arc<InMemoryTreeStore>, threads: usize) -> Duration {
+    let next_version = Arc::new(AtomicU64::new(1));
+    let commit_lock = Arc::new(Mutex::new(()));
+    let handles: Vec<_> = (0..threads)
+        .map(|t| {
+            let store = Arc::clone(store);
+            let next_version = Arc::clone(&next_version);
+            let commit_lock = Arc::clone(&commit_lock);
+            thread::spawn(move || {
+                let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Sha256> = JellyfishMerkleTree::new(&store);
+                let mut waited = Duration::ZERO;
+                for commit in (t..WRITER_COMMITS).step_by(threads) {
+                    let value_set = writer_batch(commit);
+                    let start = Instant::now();
+                    let _guard = commit_lock.lock().unwrap();
+                    waited += start.elapsed();
+                    // Taken under the lock, so versions are committed in the order they are handed out
+                    let version = next_version.fetch_add(1, Ordering::Relaxed);
+                    let (_root, batch) = jmt.put_value_set(value_set, version).unwrap();
+                    store.write_node_batch(&batch.node_batch).unwrap();
+                }
+                waited
+            })
+        })
+        .collect();
+    handles.into_iter().map(|handle| handle.join().unwrap()).sum()
+}
+
+// jmt commits must land in version order, so writers can only overlap on building their
+// batches; the tree hasher does not change that, so only Sha256 runs
+fn jmt_concurrent_commit_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_concurrent_commit");
+    sample_for_size(&mut group, WRITER_COMMITS * WRITER_COMMIT_KEYS);
+    let (base, _key_hashes) = populated_store::<Sha256>(CONCURRENT_TREE_SIZE);
+    group.throughput(Throughput::Elements(WRITER_COMMITS as u64));
+    for threads in [1, 2, 4, 8] {
+        let store = Arc::new(base.clone_snapshot().unwrap());
+        let start = Instant::now();
+        let waited = commit_concurrently(&store, threads);
+        let writer_time = start.elapsed() * threads as u32;
+        println!(
+            "jmt_concurrent_commit/{}: writers spent {:.0}% of their time waiting for the commit lock",
+            threads,
+            100.0 * waited.as_secs_f64() / writer_time.as_secs_f64()
+        );
+
+        group.bench_function(BenchmarkId::new("commit/sha256", threads), |b| {
+            b.iter_batched(
+                || Arc::new(base.clone_snapshot().unwrap()),
+                |store| {
+                    commit_concurrently(&store, threads);
+                    store
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+    export_results("jmt_concurrent_commit", &BTreeMap::new());
+}
+
+const STEADY_TREE_SIZE: usize = 1000;
+
+// Only `put_value_set` is timed. It never writes, so the empty store stays empty and the
+// steady-state base stays at version 0 for every iteration
+fn bench_first_insert<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    let empty = InMemoryTreeStore::new();
+    let (base, _key_hashes) = populated_store::<H>(STEADY_TREE_SIZE);
+    for batch_len in [1, 16, 256] {
+        sample_for_size(group, batch_len);
+        let value_set = value_set_for(STEADY_TREE_SIZE..STEADY_TREE_SIZE + batch_len, 0);
+        group.throughput(Throughput::Elements(batch_len as u64));
+        for (case, store, version) in [("first", &empty, 0), ("steady", &base, 1)] {
+            let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, H> = JellyfishMerkleTree::new(store);
+            group.bench_function(BenchmarkId::new(format!("{}/{}", case, hasher), batch_len), |b| {
+                b.iter_batched(
+                    || value_set.clone(),
+                    |value_set| jmt.put_value_set(value_set, version).unwrap(),
+                    BatchSize::SmallInput,
+                )
+            });
+        }
+    }
+}
+
+fn jmt_first_insert_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_first_insert");
+    bench_first_insert::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_first_insert::<Blake2s256>(&mut group, "blake2s");
+        bench_first_insert::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_first_insert", &BTreeMap::new());
+}
+
+// Versions committed per iteration, each rewriting every key of a `BOUNDED_KEYS`-key tree, and
+// how many of them the bounded store keeps readable
+const BOUNDED_VERSIONS: usize = 100;
+const BOUNDED_KEYS: usize = 100;
+const BOUNDED_WINDOW: Version = 10;
+
+// Commits the same run of versions into a store that keeps every version and into one that
+// evicts all but the newest `BOUNDED_WINDOW`, so the gap is what eviction costs per commit
+fn bench_bounded_commit<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    sample_for_size(group, BOUNDED_VERSIONS * BOUNDED_KEYS);
+    let value_sets: Vec<ValueSet> = (0..BOUNDED_VERSIONS).map(|version| value_set_for(0..BOUNDED_KEYS, version)).collect();
+    let commit_all = |commit: &dyn Fn(ValueSet, Version)| {
+        for (version, value_set) in value_sets.iter().enumerate() {
+            commit(value_set.clone(), version as Version);
+        }
+    };
+
+    let unbounded = InMemoryTreeStore::new();
+    let bounded = BoundedTreeStore::new(BOUNDED_WINDOW);
+    commit_all(&|value_set, version| {
+        commit_streamed::<_, H>(&unbounded, value_set, version).unwrap();
+    });
+    commit_all(&|value_set, version| {
+        let jmt: JellyfishMerkleTree<'_, BoundedTreeStore, H> = JellyfishMerkleTree::new(&bounded);
+        bounded.commit(&jmt.put_value_set(value_set, version).unwrap().1).unwrap();
+    });
+    println!(
+        "jmt_bounded_commit/{}/{}: {} bytes unbounded, {} bytes keeping {} versions",
+        hasher,
+        BOUNDED_VERSIONS,
+        unbounded.estimated_size_bytes().unwrap(),
+        bounded.inner().estimated_size_bytes().unwrap(),
+        BOUNDED_WINDOW
+    );
+
+    group.throughput(Throughput::Elements(BOUNDED_VERSIONS as u64));
+    group.bench_function(BenchmarkId::new(format!("unbounded/{}", hasher), BOUNDED_VERSIONS), |b| {
+        b.iter_batched(
+            InMemoryTreeStore::new,
+            |store| {
+                commit_all(&|value_set, version| {
+                    commit_streamed::<_, H>(&store, value_set, version).unwrap();
+                });
+                store
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function(BenchmarkId::new(format!("bounded/{}", hasher), BOUNDED_VERSIONS), |b| {
+        b.iter_batched(
+            || BoundedTreeStore::new(BOUNDED_WINDOW),
+            |store| {
+                let jmt: JellyfishMerkleTree<'_, BoundedTreeStore, H> = JellyfishMerkleTree::new(&store);
+                commit_all(&|value_set, version| {
+                    store.commit(&jmt.put_value_set(value_set, version).unwrap().1).unwrap();
+                });
+                store
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn jmt_bounded_commit_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_bounded_commit");
+    bench_bounded_commit::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_bounded_commit::<Blake2s256>(&mut group, "blake2s");
+        bench_bounded_commit::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_bounded_commit", &BTreeMap::new());
+}
+
+// Tree size and the fixed keys proven in it, for the cross-hasher proof comparison
+const HASHER_PROOF_TREE_SIZE: usize = 10000;
+const HASHER_PROOF_KEYS: usize = 1000;
+
+// Unlike every other group, keys are hashed with the tree's own hasher here, so each hasher's
+// output distribution shapes its tree and decides how many siblings a proof carries
+fn bench_hasher_proofs<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    sample_for_size(group, HASHER_PROOF_KEYS);
+    let store = InMemoryTreeStore::new();
+    let pairs = workload::pairs(HASHER_PROOF_TREE_SIZE, VALUE_LEN);
+    let key_hashes: Vec<KeyHash> = pairs.iter().map(|(key, _value)| KeyHash::with::<H>(key)).collect();
+    let value_set = key_hashes.iter().zip(pairs).map(|(key_hash, (_key, value))| (*key_hash, Some(value)));
+    commit_streamed::<_, H>(&store, value_set, 0).unwrap();
+    let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, H> = JellyfishMerkleTree::new(&store);
+    let proven = &key_hashes[..HASHER_PROOF_KEYS];
+
+    let (mut siblings, mut max_siblings, mut bytes) = (0, 0, 0);
+    for key_hash in proven {
+        let (_value, proof) = jmt.get_with_proof(*key_hash, 0).unwrap();
+        let count = sibling_count(&proof);
+        siblings += count;
+        max_siblings = max_siblings.max(count);
+        bytes += proof_size(&proof).unwrap();
+    }
+    println!(
+        "jmt_hasher_proofs/{}/{}: {:.2} siblings on average, {} at most, {:.1} bytes per proof",
+        hasher,
+        HASHER_PROOF_TREE_SIZE,
+        siblings as f64 / HASHER_PROOF_KEYS as f64,
+        max_siblings,
+        bytes as f64 / HASHER_PROOF_KEYS as f64
+    );
+
+    group.throughput(Throughput::Elements(HASHER_PROOF_KEYS as u64));
+    group.bench_function(BenchmarkId::new(format!("get_with_proof/{}", hasher), HASHER_PROOF_TREE_SIZE), |b| {
+        b.iter(|| {
+            for key_hash in proven {
+                jmt.get_with_proof(*key_hash, 0).unwrap();
+            }
+        })
+    });
+}
+
+fn jmt_hasher_proofs_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_hasher_proofs");
+    sample_for_size(&mut group, HASHER_PROOF_TREE_SIZE);
+    bench_hasher_proofs::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_hasher_proofs::<Blake2s256>(&mut group, "blake2s");
+        bench_hasher_proofs::<Blake3>(&mut group, "blake3");
+        #[cfg(feature = "keccak")]
+        bench_hasher_proofs::<tree_bench::hash::Keccak256>(&mut group, "keccak256");
+    }
+    group.finish();
+    export_results("jmt_hasher_proofs", &BTreeMap::new());
+}
+
+// Times `ABSENT_KEYS` proven lookups of present keys against as many never-inserted ones on the
+// same tree. A miss ends at the first empty slot or at a leaf holding another key, so it reads
+// no value and usually stops short of a hit's depth
+fn bench_miss<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    for size in sizes([10, 100, 1000, 10000]) {
+        sample_for_size(group, size);
+        let (store, key_hashes) = populated_store::<H>(size);
+        let counting = InstrumentedReader::new(store);
+        let jmt: JellyfishMerkleTree<'_, InstrumentedReader<InMemoryTreeStore>, H> = JellyfishMerkleTree::new(&counting);
+        let hits: Vec<KeyHash> = key_hashes.iter().cycle().take(ABSENT_KEYS).copied().collect();
+        let misses: Vec<KeyHash> = (0..ABSENT_KEYS)
+            .map(|i| KeyHash::with::<Sha256>(format!("absent{}", i)))
+            .collect();
+
+        for (case, lookups) in [("hit", &hits), ("miss", &misses)] {
+            counting.reset();
+            for key_hash in lookups {
+                let (value, _proof) = jmt.get_with_proof(*key_hash, 0).unwrap();
+                assert_eq!(value.is_some(), case == "hit", "{} on {:?}", case, key_hash);
+            }
+            let counts = counting.counts();
+            println!(
+                "jmt_miss/{}/{}/{}: {:.2} node reads and {:.2} value reads per lookup",
+                case,
+                hasher,
+                size,
+                counts.nodes as f64 / ABSENT_KEYS as f64,
+                counts.values as f64 / ABSENT_KEYS as f64
+            );
+        }
+
+        group.throughput(Throughput::Elements(ABSENT_KEYS as u64));
+        for (case, lookups) in [("hit", &hits), ("miss", &misses)] {
+            group.bench_function(BenchmarkId::new(format!("{}/{}", case, hasher), size), |b| {
+                b.iter(|| {
+                    for key_hash in lookups {
+                        jmt.get_with_proof(*key_hash, 0).unwrap();
+                    }
+                })
+            });
+        }
+    }
+}
+
+fn jmt_miss_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_miss");
+    bench_miss::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_miss::<Blake2s256>(&mut group, "blake2s");
+        bench_miss::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_miss", &BTreeMap::new());
+}
+
+// Walking leaves reads nodes but hashes nothing, so the hasher only decides the tree's shape,
+// which key hashing fixes the same for all of them; this runs with Sha256 alone
+fn jmt_export_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_export");
+    for size in sizes([1000, 10000, 100000]) {
+        sample_for_size(&mut group, size);
+        let store = Arc::new(InMemoryTreeStore::new());
+        commit_streamed::<_, Sha256>(&*store, workload::value_set_iter(size, VALUE_LEN), 0).unwrap();
+        group.throughput(Throughput::Elements(size as u64));
+        for chunk_len in [256, 1024, 4096] {
+            let chunk_len = NonZeroUsize::new(chunk_len).unwrap();
+            let export = || -> usize {
+                export_leaves(Arc::clone(&store), 0, chunk_len).unwrap().map(|chunk| chunk.unwrap().len()).sum()
+            };
+            // A partial walk would be timed as a fast one
+            assert_eq!(export(), size);
+            group.bench_function(BenchmarkId::new(format!("export/{}", chunk_len), size), |b| b.iter(export));
+        }
+    }
+    group.finish();
+    export_results("jmt_export", &BTreeMap::new());
+}
+
+// Individually timed gets per size: enough that p999 rests on ten samples
+const TAIL_SAMPLES: usize = 10000;
+
+// The nearest-rank percentile of ascending `timings`: the smallest one that at least
+// `quantile` of all the timings are no greater than
+fn percentile(timings: &[Duration], quantile: f64) -> Duration {
+    let rank = (quantile * timings.len() as f64).ceil() as usize;
+    timings[rank.clamp(1, timings.len()) - 1]
+}
+
+// Criterion summarizes a sample of many calls, which averages one slow call away, so each get
+// is timed on its own here. The Criterion run over the same lookups gives the mean to read the
+// percentiles against
+fn bench_get_tail<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    for size in sizes([10, 100, 1000, 10000]) {
+        sample_for_size(group, size);
+        let (store, key_hashes) = populated_store::<H>(size);
+        let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, H> = JellyfishMerkleTree::new(&store);
+        let lookups: Vec<KeyHash> = workload::random_indices(workload::BENCH_SEED, TAIL_SAMPLES, size)
+            .into_iter()
+            .map(|i| key_hashes[i])
+            .collect();
+
+        // One untimed pass first, so the timings start from warm caches
+        for key_hash in &lookups {
+            jmt.get_with_proof(*key_hash, 0).unwrap();
+        }
+        let mut timings: Vec<Duration> = lookups
+            .iter()
+            .map(|key_hash| {
+                let start = Instant::now();
+                jmt.get_with_proof(*key_hash, 0).unwrap();
+                start.elapsed()
+            })
+            .collect();
+        timings.sort_unstable();
+        println!(
+            "jmt_get_tail/{}/{}: p50 {:?}, p90 {:?}, p99 {:?}, p999 {:?}, max {:?}",
+            hasher,
+            size,
+            percentile(&timings, 0.5),
+            percentile(&timings, 0.9),
+            percentile(&timings, 0.99),
+            percentile(&timings, 0.999),
+            timings[timings.len() - 1]
+        );
+
+        let mut next = lookups.iter().cycle();
+        group.bench_function(BenchmarkId::new(format!("get_with_proof/{}", hasher), size), |b| {
+            b.iter(|| jmt.get_with_proof(*next.next().unwrap(), 0).unwrap())
+        });
+    }
+}
+
+fn jmt_get_tail_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_get_tail");
+    bench_get_tail::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_get_tail::<Blake2s256>(&mut group, "blake2s");
+        bench_get_tail::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_get_tail", &BTreeMap::new());
+}
+
+const HOT_TREE_SIZE: usize = 10000;
+const HOT_READS: usize = 1000;
+// Room for the hot key's path many times over, but for only a sliver of the paths that
+// `HOT_READS` distinct keys walk, so the cache helps one and thrashes on the other
+const HOT_CACHE_NODES: usize = 256;
+
+// Proves one key `HOT_READS` times, against as many distinct keys, with and without a node
+// cache. The cache's effect does not depend on the hasher, so this runs with Sha256 alone
+fn jmt_hot_key_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_hot_key");
+    sample_for_size(&mut group, HOT_READS);
+    let (store, key_hashes) = populated_store::<Sha256>(HOT_TREE_SIZE);
+    let cached = CachingReader::new(store, NonZeroUsize::new(HOT_CACHE_NODES).unwrap());
+    let hot = vec![key_hashes[0]; HOT_READS];
+    let distinct = &key_hashes[..HOT_READS];
+
+    for (case, lookups) in [("hot", &hot[..]), ("distinct", distinct)] {
+        // Warm the cache with one pass, then count a second one
+        prove_all::<_, Sha256>(&cached, lookups);
+        let (hits, misses) = (cached.hits(), cached.misses());
+        prove_all::<_, Sha256>(&cached, lookups);
+        let (hits, misses) = (cached.hits() - hits, cached.misses() - misses);
+        println!(
+            "jmt_hot_key/{}/{}: {:.1}% of node reads served by a {}-node cache",
+            case,
+            HOT_READS,
+            100.0 * hits as f64 / (hits + misses) as f64,
+            HOT_CACHE_NODES
+        );
+    }
+
+    group.throughput(Throughput::Elements(HOT_READS as u64));
+    for (case, lookups) in [("hot", &hot[..]), ("distinct", distinct)] {
+        group.bench_function(BenchmarkId::new(format!("{}/uncached", case), HOT_READS), |b| {
+            b.iter(|| prove_all::<_, Sha256>(cached.inner(), lookups))
+        });
+        group.bench_function(BenchmarkId::new(format!("{}/cached", case), HOT_READS), |b| {
+            b.iter(|| prove_all::<_, Sha256>(&cached, lookups))
+        });
+    }
+    group.finish();
+    export_results("jmt_hot_key", &BTreeMap::new());
+}
+
+const ROOT_ONLY_TREE_SIZE: usize = 10000;
+
+// `put_value_set` computes the new root and hands back the nodes without writing any, so it is
+// already the root-only path. It is timed against the same batch with its update proof, and
+// against a full commit that also writes the batch to a snapshot of the tree. Every batch
+// rewrites existing keys
+fn bench_root_only<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    let (base, _key_hashes) = populated_store::<H>(ROOT_ONLY_TREE_SIZE);
+    let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, H> = JellyfishMerkleTree::new(&base);
+    for batch_len in [1, 10, 100, 1000] {
+        sample_for_size(group, batch_len);
+        let value_set = value_set_for(0..batch_len, 1);
+        group.throughput(Throughput::Elements(batch_len as u64));
+        group.bench_function(BenchmarkId::new(format!("root/{}", hasher), batch_len), |b| {
+            b.iter_batched(|| value_set.clone(), |value_set| jmt.put_value_set(value_set, 1).unwrap(), BatchSize::SmallInput)
+        });
+        group.bench_function(BenchmarkId::new(format!("root_with_proof/{}", hasher), batch_len), |b| {
+            b.iter_batched(
+                || value_set.clone(),
+                |value_set| jmt.put_value_set_with_proof(value_set, 1).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_function(BenchmarkId::new(format!("commit/{}", hasher), batch_len), |b| {
+            b.iter_batched(
+                || (base.clone_snapshot().unwrap(), value_set.clone()),
+                |(store, value_set)| {
+                    commit_streamed::<_, H>(&store, value_set, 1).unwrap();
+                    store
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+}
+
+fn jmt_root_only_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_root_only");
+    bench_root_only::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_root_only::<Blake2s256>(&mut group, "blake2s");
+        bench_root_only::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_root_only", &BTreeMap::new());
+}
+
+const PROOF_REFRESH_TREE_SIZE: usize = 10000;
+
+// Siblings that differ between two proofs of the same key, compared through their JSON form
+fn stale_siblings<H: SimpleHasher>(old: &SparseMerkleProof<H>, new: &SparseMerkleProof<H>) -> usize {
+    let (old, new) = (proof_to_json(old), proof_to_json(new));
+    let (old, new) = (old["siblings"].as_array().unwrap(), new["siblings"].as_array().unwrap());
+    old.iter().zip(new).filter(|(old, new)| old != new).count() + old.len().abs_diff(new.len())
+}
+
+// jmt cannot patch a proof, so once one key changes, refreshing K cached proofs of unchanged
+// keys means regenerating all K. That is timed against the single-key commit that staled them
+fn bench_proof_refresh<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    let (base, key_hashes) = populated_store::<H>(PROOF_REFRESH_TREE_SIZE);
+    let change = value_set_for(0..1, 1);
+    let updated = base.clone_snapshot().unwrap();
+    commit_streamed::<_, H>(&updated, change.clone(), 1).unwrap();
+    let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, H> = JellyfishMerkleTree::new(&updated);
+
+    sample_for_size(group, 1);
+    group.throughput(Throughput::Elements(1));
+    group.bench_function(BenchmarkId::new(format!("commit/{}", hasher), 1), |b| {
+        b.iter_batched(
+            || (base.clone_snapshot().unwrap(), change.clone()),
+            |(store, change)| {
+                commit_streamed::<_, H>(&store, change, 1).unwrap();
+                store
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    for k in [1, 10, 100] {
+        sample_for_size(group, k);
+        let unchanged = &key_hashes[1..=k];
+        let stale: Vec<usize> = unchanged
+            .iter()
+            .map(|key_hash| stale_siblings(&jmt.get_with_proof(*key_hash, 0).unwrap().1, &jmt.get_with_proof(*key_hash, 1).unwrap().1))
+            .collect();
+        println!(
+            "jmt_proof_refresh/{}/{}: {} of {} cached proofs stale, {} stale siblings",
+            hasher,
+            k,
+            stale.iter().filter(|&&siblings| siblings > 0).count(),
+            k,
+            stale.iter().sum::<usize>()
+        );
+
+        group.throughput(Throughput::Elements(k as u64));
+        group.bench_function(BenchmarkId::new(format!("regenerate/{}", hasher), k), |b| {
+            b.iter(|| {
+                for key_hash in unchanged {
+                    jmt.get_with_proof(*key_hash, 1).unwrap();
+                }
+            })
+        });
+    }
+}
+
+fn jmt_proof_refresh_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_proof_refresh");
+    bench_proof_refresh::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_proof_refresh::<Blake2s256>(&mut group, "blake2s");
+        bench_proof_refresh::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_proof_refresh", &BTreeMap::new());
+}
+
+const FLUSH_TREE_SIZE: usize = 10000;
+const FLUSH_BATCH_LEN: usize = 100;
+
+// Commits `batches` onto `store` at versions 1 on, flushing after each one or once at the end
+fn commit_buffered<H: SimpleHasher>(store: &BufferedTreeStore<InMemoryTreeStore>, batches: Vec<ValueSet>, per_batch: bool) {
+    for (i, value_set) in batches.into_iter().enumerate() {
+        commit_streamed::<_, H>(store, value_set, i as Version + 1).unwrap();
+        if per_batch {
+            store.flush().unwrap();
+        }
+    }
+    if !per_batch {
+        store.flush().unwrap();
+    }
+}
+
+// Commits N batches of 100 updates into a 10,000-key tree through a `BufferedTreeStore`,
+// flushing after every batch or once after the last. `flush` times that one deferred flush
+// alone, with the N batches buffered beforehand
+fn bench_flush<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    let (base, _key_hashes) = populated_store::<H>(FLUSH_TREE_SIZE);
+    for batch_count in [1, 10, 100] {
+        sample_for_size(group, batch_count * FLUSH_BATCH_LEN);
+        let batches: Vec<ValueSet> = (0..batch_count)
+            .map(|i| value_set_for(i * FLUSH_BATCH_LEN..(i + 1) * FLUSH_BATCH_LEN, i + 1))
+            .collect();
+        group.throughput(Throughput::Elements((batch_count * FLUSH_BATCH_LEN) as u64));
+        for (strategy, per_batch) in [("per_batch", true), ("deferred", false)] {
+            group.bench_function(BenchmarkId::new(format!("{}/{}", strategy, hasher), batch_count), |b| {
+                b.iter_batched(
+                    || (BufferedTreeStore::new(base.clone_snapshot().unwrap()), batches.clone()),
+                    |(store, batches)| {
+                        commit_buffered::<H>(&store, batches, per_batch);
+                        store
+                    },
+                    BatchSize::LargeInput,
+                )
+            });
+        }
+        group.bench_function(BenchmarkId::new(format!("flush/{}", hasher), batch_count), |b| {
+            b.iter_batched(
+                || {
+                    let store = BufferedTreeStore::new(base.clone_snapshot().unwrap());
+                    for (i, value_set) in batches.iter().enumerate() {
+                        commit_streamed::<_, H>(&store, value_set.clone(), i as Version + 1).unwrap();
+                    }
+                    store
+                },
+                |store| {
+                    store.flush().unwrap();
+                    store
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+}
+
+fn jmt_flush_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_flush");
+    bench_flush::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_flush::<Blake2s256>(&mut group, "blake2s");
+        bench_flush::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_flush", &BTreeMap::new());
+}
+
+const VERSION_MAGNITUDE_COMMITS: u64 = 10;
+
+// Commits the same `size` keys at each of ten versions from `first`, rewriting every value
+fn commit_versions_from<C: NodeCodec>(store: &InMemoryTreeStore<C>, size: usize, first: Version) {
+    for i in 0..VERSION_MAGNITUDE_COMMITS {
+        commit_streamed::<_, Sha256>(store, value_set_for(0..size, i as usize), first + i).unwrap();
+    }
+}
+
+// Commits identical key sets at versions from 0 and from u64::MAX - 10, and prints what each
+// store holds afterwards. Versions sit in every node key and child pointer, so a varint codec
+// would store the large ones in more bytes; a fixed-width one should not care
+fn bench_version_magnitude<C: NodeCodec>(group: &mut BenchmarkGroup<'_, WallTime>, codec: &str) {
+    for size in [100, 1000] {
+        sample_for_size(group, size * VERSION_MAGNITUDE_COMMITS as usize);
+        group.throughput(Throughput::Elements(size as u64 * VERSION_MAGNITUDE_COMMITS));
+        let mut stored_bytes = Vec::new();
+        for (magnitude, first) in [("small", 0), ("large", Version::MAX - VERSION_MAGNITUDE_COMMITS)] {
+            let store = InMemoryTreeStore::<C>::with_codec();
+            commit_versions_from(&store, size, first);
+            stored_bytes.push(store.estimated_size_bytes().unwrap());
+
+            group.bench_function(BenchmarkId::new(format!("commit/{}/{}", codec, magnitude), size), |b| {
+                b.iter_batched(
+                    InMemoryTreeStore::<C>::with_codec,
+                    |store| {
+                        commit_versions_from(&store, size, first);
+                        store
+                    },
+                    BatchSize::LargeInput,
+                )
+            });
+        }
+        println!(
+            "jmt_version_magnitude/{}/{}: {} bytes stored at small versions, {} at large",
+            codec, size, stored_bytes[0], stored_bytes[1]
+        );
+    }
+}
+
+// Sha256 only: the encoding under test does not depend on the tree hasher
+fn jmt_version_magnitude_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_version_magnitude");
+    bench_version_magnitude::<Bincode>(&mut group, "bincode");
+    #[cfg(feature = "postcard")]
+    bench_version_magnitude::<tree_bench::store::Postcard>(&mut group, "postcard");
+    group.finish();
+    export_results("jmt_version_magnitude", &BTreeMap::new());
+}
+
+const SHARDED_TREE_SIZE: usize = 1000;
+const SHARDED_WRITER_COMMITS: usize = 10;
+
+// `readers` threads prove every key at version 0 between them, one slice each. With `writer`
+// set, one more thread commits 10 batches of 10 updates on top while they read
+fn read_while_writing<S: TreeReader + TreeWriter + Sync>(store: &S, key_hashes: &[KeyHash], readers: usize, writer: bool) {
+    thread::scope(|scope| {
+        if writer {
+            scope.spawn(|| {
+                for version in 1..=SHARDED_WRITER_COMMITS {
+                    let start = version * 10 % SHARDED_TREE_SIZE;
+                    commit_streamed::<_, Sha256>(store, value_set_for(start..start + 10, version), version as Version).unwrap();
+                }
+            });
+        }
+        for chunk in key_hashes.chunks(key_hashes.len().div_ceil(readers)) {
+            scope.spawn(move || {
+                let jmt: JellyfishMerkleTree<'_, S, Sha256> = JellyfishMerkleTree::new(store);
+                for key_hash in chunk {
+                    jmt.get_with_proof(*key_hash, 0).unwrap();
+                }
+            });
+        }
+    });
+}
+
+// `build` returns a fresh store holding the 1000-key tree, so every `read_write` iteration
+// starts its writer at version 1
+fn bench_sharded<S: TreeReader + TreeWriter + Sync>(group: &mut BenchmarkGroup<'_, WallTime>, name: &str, build: impl Fn() -> S) {
+    let key_hashes: Vec<KeyHash> = (0..SHARDED_TREE_SIZE).map(|i| KeyHash::with::<Sha256>(workload::key(i))).collect();
+    let store = build();
+    for threads in [1, 2, 4, 8] {
+        group.bench_function(BenchmarkId::new(format!("read/{}", name), threads), |b| {
+            b.iter(|| read_while_writing(&store, &key_hashes, threads, false))
+        });
+        group.bench_function(BenchmarkId::new(format!("read_write/{}", name), threads), |b| {
+            b.iter_batched(
+                &build,
+                |store| {
+                    read_while_writing(&store, &key_hashes, threads, true);
+                    store
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+}
+
+// Lock contention does not depend on the tree hasher, so only Sha256 runs
+fn jmt_sharded_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_sharded");
+    sample_for_size(&mut group, SHARDED_TREE_SIZE);
+    group.throughput(Throughput::Elements(SHARDED_TREE_SIZE as u64));
+    let (base, _key_hashes) = populated_store::<Sha256>(SHARDED_TREE_SIZE);
+    bench_sharded(&mut group, "single_lock", || base.clone_snapshot().unwrap());
+    bench_sharded(&mut group, "sharded", || {
+        let store = ShardedTreeStore::new();
+        commit_streamed::<_, Sha256>(&store, value_set_for(0..SHARDED_TREE_SIZE, 0), 0).unwrap();
+        store
+    });
+    group.finish();
+    export_results("jmt_sharded", &BTreeMap::new());
+}
+
+// Siblings jmt serializes as `Null`: empty subtrees, which verify without hashing
+fn empty_siblings<H: SimpleHasher>(proof: &SparseMerkleProof<H>) -> usize {
+    proof_to_json(proof)["siblings"].as_array().unwrap().iter().filter(|sibling| *sibling == "Null").count()
+}
+
+// Two keys whose hashes first differ at bit `depth - 1`, so each one's proof has `depth`
+// siblings: the other leaf at the bottom, and an empty subtree at every level above it
+fn sparse_store<H: SimpleHasher>(depth: usize) -> (InMemoryTreeStore, KeyHash) {
+    let key_hash = KeyHash([0; 32]);
+    let mut other = [0; 32];
+    other[(depth - 1) / 8] = 0x80 >> ((depth - 1) % 8);
+    let store = InMemoryTreeStore::new();
+    let value_set = vec![(key_hash, Some(workload::value(0, VALUE_LEN))), (KeyHash(other), Some(workload::value(1, VALUE_LEN)))];
+    commit_streamed::<_, H>(&store, value_set, 0).unwrap();
+    (store, key_hash)
+}
+
+// Verifying hashes one internal node per sibling, plus the sibling itself unless it is empty.
+// A proof from a dense tree is matched with one of the same depth from a two-key tree
+fn bench_sibling_emptiness<H: SimpleHasher>(group: &mut BenchmarkGroup<'_, WallTime>, hasher: &str) {
+    sample_for_size(group, 1);
+    for size in sizes([100, 10000]) {
+        let (dense, key_hashes) = populated_store::<H>(size);
+        let dense_jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, H> = JellyfishMerkleTree::new(&dense);
+        let dense_root = dense_jmt.get_root_hash(0).unwrap();
+        let (dense_value, dense_proof) = dense_jmt.get_with_proof(key_hashes[0], 0).unwrap();
+        let depth = sibling_count(&dense_proof);
+
+        let (sparse, sparse_key_hash) = sparse_store::<H>(depth);
+        let sparse_jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, H> = JellyfishMerkleTree::new(&sparse);
+        let sparse_root = sparse_jmt.get_root_hash(0).unwrap();
+        let (sparse_value, sparse_proof) = sparse_jmt.get_with_proof(sparse_key_hash, 0).unwrap();
+        assert_eq!(sibling_count(&sparse_proof), depth);
+        println!(
+            "jmt_sibling_emptiness/{}/{}: {} of {} siblings empty in the {}-key tree's proof, {} in the 2-key tree's",
+            hasher,
+            depth,
+            empty_siblings(&dense_proof),
+            depth,
+            size,
+            empty_siblings(&sparse_proof)
+        );
+
+        for (tree, root, key_hash, value, proof) in [
+            ("dense", dense_root, key_hashes[0], dense_value, dense_proof),
+            ("sparse", sparse_root, sparse_key_hash, sparse_value, sparse_proof),
+        ] {
+            proof.verify(root, key_hash, value.as_ref()).expect("proof must verify");
+            group.bench_function(BenchmarkId::new(format!("verify/{}/{}", hasher, tree), depth), |b| {
+                b.iter(|| proof.verify(root, key_hash, value.as_ref()))
+            });
+        }
+    }
+}
+
+fn jmt_sibling_emptiness_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_sibling_emptiness");
+    bench_sibling_emptiness::<Sha256>(&mut group, "sha256");
+    if every_hasher() {
+        bench_sibling_emptiness::<Blake2s256>(&mut group, "blake2s");
+        bench_sibling_emptiness::<Blake3>(&mut group, "blake3");
+    }
+    group.finish();
+    export_results("jmt_sibling_emptiness", &BTreeMap::new());
+}
+
+const PRELOAD_TREE_SIZE: usize = 10_000;
+// Room for every node a batch of 1000 proofs reads, so preloading evicts nothing
+const PRELOAD_CACHE_NODES: usize = 4 * PRELOAD_TREE_SIZE;
+const PRELOAD_READ_LATENCY: Duration = Duration::from_micros(10);
+
+// Sha256 only: the saving is in node reads, which don't depend on the hasher
+fn jmt_preload_benchmark(c: &mut Criterion) {
+    let mut group = benchmark_group(c, "jmt_preload");
+    let (store, key_hashes) = populated_store::<Sha256>(PRELOAD_TREE_SIZE);
+    let empty_cache = || {
+        CachingReader::new(
+            LatencyTreeStore::new(store.clone_snapshot().unwrap(), PRELOAD_READ_LATENCY),
+            NonZeroUsize::new(PRELOAD_CACHE_NODES).unwrap(),
+        )
+    };
+
+    for batch_len in [100, 1000] {
+        sample_for_size(&mut group, batch_len);
+        let batch: Vec<KeyHash> = workload::random_indices(workload::BENCH_SEED, batch_len, PRELOAD_TREE_SIZE)
+            .into_iter()
+            .map(|i| key_hashes[i])
+            .collect();
+        let counting = InstrumentedReader::new(store.clone_snapshot().unwrap());
+        prove_all::<_, Sha256>(&counting, &batch);
+        let cold = counting.counts().nodes;
+        counting.reset();
+        let counted_cache = CachingReader::new(counting, NonZeroUsize::new(PRELOAD_CACHE_NODES).unwrap());
+        counted_cache.preload(&batch, 0).unwrap();
+        let preloaded = counted_cache.inner().counts().nodes;
+        prove_all::<_, Sha256>(&counted_cache, &batch);
+        let total = counted_cache.inner().counts().nodes;
+        println!(
+            "jmt_preload/{}: {} node reads cold, {} preloaded and {} more while proving ({:.1}% saved)",
+            batch_len,
+            cold,
+            preloaded,
+            total - preloaded,
+            100.0 * (cold - total) as f64 / cold as f64
+        );
+
+        // Every node read pays the store's latency, so the two differ by the reads the shared
+        // paths save, less the cost of the read-ahead walk itself
+        let slow = LatencyTreeStore::new(store.clone_snapshot().unwrap(), PRELOAD_READ_LATENCY);
+        group.throughput(Throughput::Elements(batch_len as u64));
+        group.bench_function(BenchmarkId::new("cold", batch_len), |b| {
+            b.iter(|| prove_all::<_, Sha256>(&slow, &batch))
+        });
+        group.bench_function(BenchmarkId::new("preload", batch_len), |b| {
+            b.iter_batched_ref(
+                empty_cache,
+                |cached| {
+                    cached.preload(&batch, 0).unwrap();
+                    prove_all::<_, Sha256>(&*cached, &batch)
+                },
+                BatchSize::PerIteration,
+            )
+        });
+    }
+    group.finish();
+    export_results("jmt_preload", &BTreeMap::new());
+}
+
+// Node reads through the reader a benchmark last installed, as a Criterion measurement, so
+// reports and saved baselines track them the way they track time. Each tree size installs
+// its own reader before its benchmark runs
+#[derive(Clone, Default)]
+struct NodeReads {
+    reader: Arc<Mutex<Option<Arc<InstrumentedReader<InMemoryTreeStore>>>>>,
+}
+
+impl NodeReads {
+    fn install(&self, reader: Arc<InstrumentedReader<InMemoryTreeStore>>) {
+        *self.reader.lock().unwrap() = Some(reader);
+    }
+
+    fn count(&self) -> u64 {
+        self.reader.lock().unwrap().as_ref().map_or(0, |reader| reader.counts().nodes)
+    }
+}
+
+impl Measurement for NodeReads {
+    type Intermediate = u64;
+    type Value = u64;
+
+    fn start(&self) -> u64 {
+        self.count()
+    }
+
+    fn end(&self, start: u64) -> u64 {
+        self.count() - start
+    }
+
+    fn add(&self, v1: &u64, v2: &u64) -> u64 {
+        v1 + v2
+    }
+
+    fn zero(&self) -> u64 {
+        0
+    }
+
+    fn to_f64(&self, value: &u64) -> f64 {
+        *value as f64
+    }
+
+    fn formatter(&self) -> &dyn ValueFormatter {
+        &NodeReadsFormatter
+    }
+}
+
+// Reads are counted, not scaled, so only throughput changes the unit: reads per key or byte
+struct NodeReadsFormatter;
+
+impl ValueFormatter for NodeReadsFormatter {
+    fn scale_values(&self, _typical_value: f64, _values: &mut [f64]) -> &'static str {
+        "reads"
+    }
+
+    fn scale_throughputs(&self, _typical_value: f64, throughput: &Throughput, values: &mut [f64]) -> &'static str {
+        let (count, unit) = match throughput {
+            Throughput::Elements(elements) => (*elements, "reads/elem"),
+            Throughput::Bytes(bytes) | Throughput::BytesDecimal(bytes) => (*bytes, "reads/byte"),
+        };
+        for value in values {
+            *value /= count as f64;
+        }
+        unit
+    }
+
+    fn scale_for_machines(&self, _values: &mut [f64]) -> &'static str {
+        "reads"
+    }
+}
+
+// The `jmt_get` workloads measured in node reads instead of time, so a change to how many
+// nodes a proof fetches shows up in Criterion's comparisons. Node reads depend only on the
+// tree's shape, which keys hashed with Sha256 fix for every hasher, so Sha256 alone runs.
+// The group is not exported, since the exporter reads every estimate as nanoseconds
+fn jmt_get_node_reads_benchmark(c: &mut Criterion<NodeReads>, node_reads: &NodeReads) {
+    let mut group = benchmark_group(c, "jmt_get_node_reads");
+    for distribution in KeyDistribution::ALL {
+        let get = Get { distribution, value_len: VALUE_LEN };
+        for size in [10, 100, 1000] {
+            sample_for_size(&mut group, size);
+            let reader = Arc::new(InstrumentedReader::new(InMemoryTreeStore::new()));
+            get.setup::<_, Sha256>(&*reader, size).unwrap();
+            node_reads.install(Arc::clone(&reader));
+            let batch = get.prepare(size);
+            group.throughput(Throughput::Elements(batch.len() as u64));
+            group.bench_with_input(BenchmarkId::new(get.id("sha256"), size), &size, |b, _| {
+                b.iter_batched(|| batch.clone(), |batch| get.run::<_, Sha256>(&*reader, batch).unwrap(), BatchSize::SmallInput);
+            });
+        }
+    }
+    group.finish();
+}
+
+// The trees are deterministic, but shared CI runners drift by a few percent between runs, so
+// a change is only reported when it is significant at 1% and moves the mean by more than 3%
+const SIGNIFICANCE_LEVEL: f64 = 0.01;
+const NOISE_THRESHOLD: f64 = 0.03;
+
+// Sizes mostly step by powers of ten, so summary plots use a log axis to keep the small ones
+// from being squashed against the origin. Criterion's three-second warm-up is more than any
+// benchmark here needs to settle, and across every group it would dominate the suite's runtime
+const WARM_UP_TIME: Duration = Duration::from_secs(1);
+
+// The CI profile trades precision for a short run: ten samples in a short window, Sha256
+// alone, and no size above `CI_MAX_SIZE`
+const CI_WARM_UP_TIME: Duration = Duration::from_millis(200);
+const CI_MEASUREMENT_TIME: Duration = Duration::from_millis(500);
+const CI_MAX_SIZE: usize = 1000;
+
+fn ci_profile() -> bool {
+    match env::var(report::PROFILE_VAR) {
+        Ok(profile) if profile == "ci" => true,
+        Ok(profile) if profile == "full" => false,
+        Ok(profile) => panic!("{} must be `ci` or `full`, not `{}`", report::PROFILE_VAR, profile),
+        Err(_) => false,
+    }
+}
+
+// Whether the hashers other than Sha256 run
+fn every_hasher() -> bool {
+    !ci_profile()
+}
+
+// Whether the profile runs a benchmark of `size`
+fn runs_size(size: usize) -> bool {
+    !ci_profile() || size <= CI_MAX_SIZE
+}
+
+// The sizes of a sweep the profile runs
+fn sizes<const N: usize>(sizes: [usize; N]) -> Vec<usize> {
+    sizes.into_iter().filter(|size| runs_size(*size)).collect()
+}
+
+fn benchmark_group<'a, M: Measurement>(c: &'a mut Criterion<M>, name: &str) -> BenchmarkGroup<'a, M> {
+    let mut group = c.benchmark_group(name);
+    group
+        .significance_level(SIGNIFICANCE_LEVEL)
+        .noise_threshold(NOISE_THRESHOLD)
+        .warm_up_time(if ci_profile() { CI_WARM_UP_TIME } else { WARM_UP_TIME })
+        .plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic));
+    group
+}
+
+// Sampling by the work one iteration does: small inputs run in microseconds, so they can
+// afford more samples for tighter estimates, while a 10,000-key iteration takes long enough
+// that ten samples fill a longer window. It applies to every benchmark the group defines after
+// the call, so every function that defines benchmarks calls it first, and loops call it once
+// per size. The CI profile takes ten samples in a short window whatever the size
+fn sample_for_size<M: Measurement>(group: &mut BenchmarkGroup<'_, M>, size: usize) {
+    if ci_profile() {
+        group.sample_size(10).measurement_time(CI_MEASUREMENT_TIME);
+        return;
+    }
+    let (sample_size, measurement_secs) = match size {
+        0..=100 => (100, 2),
+        101..=1000 => (50, 3),
+        _ => (10, 5),
+    };
+    group
+        .sample_size(sample_size)
+        .measurement_time(Duration::from_secs(measurement_secs));
+}
+
+// Applied after the command line, whose `--save-baseline` always defaults to `base`, so the
+// environment always wins. Comparing is strict: a missing baseline fails rather than saving one
+fn with_baseline<M: Measurement>(criterion: Criterion<M>) -> Criterion<M> {
+    if let Ok(name) = env::var(report::BASELINE_VAR) {
+        criterion.retain_baseline(name, true)
+    } else if let Ok(name) = env::var(report::SAVE_BASELINE_VAR) {
+        criterion.save_baseline(name)
+    } else {
+        criterion
+    }
+}
+
+// Writes the group's results to `$TREE_BENCH_RESULTS/<group>.json` when that is set
+fn export_results(group: &str, tree_stats: &BTreeMap<usize, TreeStats>) {
+    if let Some(path) = report::export_group(group, tree_stats).unwrap() {
+        println!("{}: results written to {}", group, path.display());
+    }
+}
+
+// Spelled out rather than `criterion_group!`, which would apply the command line last
+fn benches() {
+    let criterion = Criterion::default().configure_from_args();
+    // Rendering a benchmark's plots takes longer than the CI profile spends measuring it
+    let mut criterion = with_baseline(if ci_profile() { criterion.without_plots() } else { criterion });
+    jmt_insert_benchmark(&mut criterion);
+    jmt_get_benchmark(&mut criterion);
+    jmt_update_benchmark(&mut criterion);
+    jmt_delete_benchmark(&mut criterion);
+    jmt_verify_benchmark(&mut criterion);
+    jmt_nonmembership_benchmark(&mut criterion);
+    jmt_range_benchmark(&mut criterion);
+    jmt_cache_benchmark(&mut criterion);
+    jmt_concurrent_get_benchmark(&mut criterion);
+    jmt_proof_size_benchmark(&mut criterion);
+    jmt_mixed_benchmark(&mut criterion);
+    jmt_history_benchmark(&mut criterion);
+    jmt_restore_benchmark(&mut criterion);
+    jmt_prune_benchmark(&mut criterion);
+    jmt_typed_benchmark(&mut criterion);
+    jmt_batch_get_benchmark(&mut criterion);
+    jmt_deep_benchmark(&mut criterion);
+    jmt_zipf_benchmark(&mut criterion);
+    jmt_codec_benchmark(&mut criterion);
+    jmt_node_reads_benchmark(&mut criterion);
+    jmt_write_amp_benchmark(&mut criterion);
+    jmt_read_benchmark(&mut criterion);
+    jmt_versions_benchmark(&mut criterion);
+    jmt_shape_benchmark(&mut criterion);
+    jmt_dedup_benchmark(&mut criterion);
+    jmt_proof_json_benchmark(&mut criterion);
+    jmt_incremental_benchmark(&mut criterion);
+    jmt_overwrite_benchmark(&mut criterion);
+    jmt_latency_benchmark(&mut criterion);
+    jmt_proof_cache_benchmark(&mut criterion);
+    jmt_churn_benchmark(&mut criterion);
+    jmt_order_benchmark(&mut criterion);
+    jmt_buckets_benchmark(&mut criterion);
+    jmt_verify_batch_benchmark(&mut criterion);
+    jmt_node_batch_benchmark(&mut criterion);
+    jmt_dataset_benchmark(&mut criterion);
+    jmt_lazy_proof_benchmark(&mut criterion);
+    jmt_diff_benchmark(&mut criterion);
+    jmt_key_hash_benchmark(&mut criterion);
+    jmt_replay_benchmark(&mut criterion);
+    jmt_concurrent_commit_benchmark(&mut criterion);
+    jmt_first_insert_benchmark(&mut criterion);
+    jmt_bounded_commit_benchmark(&mut criterion);
+    jmt_hasher_proofs_benchmark(&mut criterion);
+    jmt_miss_benchmark(&mut criterion);
+    jmt_export_benchmark(&mut criterion);
+    jmt_get_tail_benchmark(&mut criterion);
+    jmt_hot_key_benchmark(&mut criterion);
+    jmt_root_only_benchmark(&mut criterion);
+    jmt_proof_refresh_benchmark(&mut criterion);
+    jmt_flush_benchmark(&mut criterion);
+    jmt_version_magnitude_benchmark(&mut criterion);
+    jmt_sharded_benchmark(&mut criterion);
+    jmt_sibling_emptiness_benchmark(&mut criterion);
+    jmt_preload_benchmark(&mut criterion);
+
+    // Every iteration reads the same nodes, and Criterion's density plots panic on a sample
+    // without spread, so this one runs without plots
+    let node_reads = NodeReads::default();
+    let mut counted = with_baseline(Criterion::default().configure_from_args().with_measurement(node_reads.clone()).without_plots());
+    jmt_get_node_reads_benchmark(&mut counted, &node_reads);
 }
 
-criterion_group!(benches, jmt_insert_benchmark, jmt_get_benchmark, jmt_update_benchmark);
 criterion_main!(benches);
@@ -0,0 +1,30 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use sha2::Sha256;
+use tree_bench::store::InMemoryTreeStore;
+use tree_bench::workload::{self, commit_streamed};
+
+const LARGE_SIZE: usize = 1_000_000;
+const VALUE_LEN: usize = 8;
+
+fn jmt_large_insert_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("jmt_large_insert");
+    // A million-key commit takes seconds, so ten samples already run for minutes
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(LARGE_SIZE as u64));
+
+    // The pairs are generated and hashed as jmt pulls them, so that work is timed with the commit
+    group.bench_function(BenchmarkId::new("insert_streamed", LARGE_SIZE), |b| {
+        b.iter_batched(
+            InMemoryTreeStore::new,
+            |store| {
+                commit_streamed::<_, Sha256>(&store, workload::value_set_iter(LARGE_SIZE, VALUE_LEN), 0).unwrap();
+                store
+            },
+            BatchSize::PerIteration,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, jmt_large_insert_benchmark);
+criterion_main!(benches);
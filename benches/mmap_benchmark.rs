@@ -0,0 +1,59 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use jmt::{JellyfishMerkleTree, KeyHash};
+use sha2::Sha256;
+use std::path::PathBuf;
+use tempfile::TempDir;
+use tree_bench::store::{InMemoryTreeStore, MmapTreeStore, WarmUp};
+use tree_bench::workload::{self, commit_streamed};
+
+const VALUE_LEN: usize = 8;
+
+// Builds a one-commit tree in a fresh store file and closes it
+fn build_on_disk(size: usize) -> (TempDir, PathBuf) {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("tree");
+    let store = MmapTreeStore::open(&path).unwrap();
+    commit_streamed::<_, Sha256>(&store, workload::value_set_iter(size, VALUE_LEN), 0).unwrap();
+    (dir, path)
+}
+
+fn jmt_mmap_read_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("jmt_mmap_read");
+
+    for size in [1000, 10000].iter() {
+        let (_dir, path) = build_on_disk(*size);
+        let key_hash = KeyHash::with::<Sha256>(workload::key(size / 2));
+
+        group.bench_with_input(BenchmarkId::new("cold/mmap", *size), size, |b, _| {
+            // Reopening rebuilds the offset index, which is setup; the proof then reads through a
+            // fresh map. The OS page cache may still hold the file
+            b.iter_batched(
+                || MmapTreeStore::open(&path).unwrap(),
+                |store| {
+                    let jmt: JellyfishMerkleTree<'_, MmapTreeStore, Sha256> = JellyfishMerkleTree::new(&store);
+                    jmt.get_with_proof(key_hash, 0).unwrap();
+                    store
+                },
+                BatchSize::PerIteration,
+            );
+        });
+
+        let store = MmapTreeStore::open(&path).unwrap();
+        store.warm_up().unwrap();
+        let jmt: JellyfishMerkleTree<'_, MmapTreeStore, Sha256> = JellyfishMerkleTree::new(&store);
+        group.bench_with_input(BenchmarkId::new("warm/mmap", *size), size, |b, _| {
+            b.iter(|| jmt.get_with_proof(key_hash, 0).unwrap())
+        });
+
+        let memory = InMemoryTreeStore::new();
+        commit_streamed::<_, Sha256>(&memory, workload::value_set_iter(*size, VALUE_LEN), 0).unwrap();
+        let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Sha256> = JellyfishMerkleTree::new(&memory);
+        group.bench_with_input(BenchmarkId::new("warm/memory", *size), size, |b, _| {
+            b.iter(|| jmt.get_with_proof(key_hash, 0).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, jmt_mmap_read_benchmark);
+criterion_main!(benches);
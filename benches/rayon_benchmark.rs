@@ -0,0 +1,78 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use jmt::{JellyfishMerkleTree, OwnedValue};
+use rayon::ThreadPool;
+use sha2::Sha256;
+use std::time::{Duration, Instant};
+use tree_bench::store::{hash_keys, par_hash_keys, InMemoryTreeStore};
+use tree_bench::workload;
+
+const COMMIT_SIZE: usize = 100_000;
+const VALUE_LEN: usize = 8;
+
+type Pairs = Vec<(Vec<u8>, Option<OwnedValue>)>;
+
+fn pool(threads: usize) -> ThreadPool {
+    rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap()
+}
+
+// The fastest of a few untimed runs, enough for the printed ratios
+fn best_of(runs: usize, f: impl Fn() -> Duration) -> Duration {
+    (0..runs).map(|_| f()).min().unwrap()
+}
+
+fn time_hashing(pairs: &Pairs, pool: Option<&ThreadPool>) -> Duration {
+    let pairs = pairs.clone();
+    let start = Instant::now();
+    match pool {
+        Some(pool) => pool.install(|| par_hash_keys::<Sha256>(pairs)).unwrap(),
+        None => hash_keys::<Sha256>(pairs).unwrap(),
+    };
+    start.elapsed()
+}
+
+// jmt builds a batch on one thread, so hashing the keys is the only part of a commit that
+// parallelizes; its share of the whole commit bounds any speedup
+fn jmt_parallel_hash_benchmark(c: &mut Criterion) {
+    let pairs: Pairs = workload::pairs(COMMIT_SIZE, VALUE_LEN).into_iter().map(|(key, value)| (key, Some(value))).collect();
+
+    let sequential = best_of(5, || time_hashing(&pairs, None));
+    let store = InMemoryTreeStore::new();
+    let tree: JellyfishMerkleTree<'_, InMemoryTreeStore, Sha256> = JellyfishMerkleTree::new(&store);
+    let value_set = hash_keys::<Sha256>(pairs.clone()).unwrap();
+    let start = Instant::now();
+    tree.put_value_set(value_set, 0).unwrap();
+    let building = start.elapsed();
+    println!(
+        "jmt_parallel_hash/{}: hashing is {:.0}% of a sequential commit",
+        COMMIT_SIZE,
+        100.0 * sequential.as_secs_f64() / (sequential + building).as_secs_f64()
+    );
+
+    let mut group = c.benchmark_group("jmt_parallel_hash");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(COMMIT_SIZE as u64));
+    group.bench_function(BenchmarkId::new("sequential", COMMIT_SIZE), |b| {
+        b.iter_batched(|| pairs.clone(), |pairs| hash_keys::<Sha256>(pairs).unwrap(), BatchSize::LargeInput)
+    });
+    for threads in [1, 2, 4, 8] {
+        let pool = pool(threads);
+        let parallel = best_of(5, || time_hashing(&pairs, Some(&pool)));
+        println!(
+            "jmt_parallel_hash/{}/{}: {:.2}x the sequential hashing speed",
+            threads,
+            COMMIT_SIZE,
+            sequential.as_secs_f64() / parallel.as_secs_f64()
+        );
+        group.bench_function(BenchmarkId::new(format!("parallel/{}", threads), COMMIT_SIZE), |b| {
+            b.iter_batched(
+                || pairs.clone(),
+                |pairs| pool.install(|| par_hash_keys::<Sha256>(pairs)).unwrap(),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, jmt_parallel_hash_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,75 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use jmt::{JellyfishMerkleTree, KeyHash, Version};
+use sha2::Sha256;
+use tempfile::TempDir;
+use tree_bench::store::{InMemoryTreeStore, RocksDbTreeStore, TreeReader, TreeWriter, WarmUp};
+use tree_bench::workload;
+
+const VALUE_LEN: usize = 8;
+
+// Commits every pair either one key per version or all at version 0, persisting each batch
+fn insert<S: TreeReader + TreeWriter>(store: &S, pairs: Vec<(Vec<u8>, Vec<u8>)>, batched: bool) {
+    let jmt: JellyfishMerkleTree<'_, S, Sha256> = JellyfishMerkleTree::new(store);
+    if batched {
+        let (_root, batch) = jmt.put_value_set(
+            pairs.into_iter().map(|(key, value)| (KeyHash::with::<Sha256>(&key), Some(value))),
+            0
+        ).unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+    } else {
+        for (version, (key, value)) in pairs.into_iter().enumerate() {
+            let (_root, batch) = jmt.put_value_set(
+                vec![(KeyHash::with::<Sha256>(&key), Some(value))],
+                version as Version
+            ).unwrap();
+            store.write_node_batch(&batch.node_batch).unwrap();
+        }
+    }
+}
+
+fn jmt_insert_rocksdb_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("jmt_insert_rocksdb");
+
+    for (strategy, batched) in [("sequential", false), ("batched", true)] {
+        for size in [10, 100, 1000].iter() {
+            group.bench_with_input(
+                BenchmarkId::new(format!("insert/memory/{}", strategy), *size),
+                size,
+                |b, &size| {
+                    b.iter_batched(
+                        || (InMemoryTreeStore::new(), workload::pairs(size, VALUE_LEN)),
+                        |(store, pairs)| {
+                            insert(&store, pairs, batched);
+                            store
+                        },
+                        BatchSize::SmallInput,
+                    );
+                },
+            );
+            group.bench_with_input(
+                BenchmarkId::new(format!("insert/rocksdb/{}", strategy), *size),
+                size,
+                |b, &size| {
+                    // Opening a fresh database is setup; returning it keeps the close out of the timing
+                    b.iter_batched(
+                        || {
+                            let dir = TempDir::new().unwrap();
+                            let store = RocksDbTreeStore::open(dir.path()).unwrap();
+                            store.warm_up().unwrap();
+                            (dir, store, workload::pairs(size, VALUE_LEN))
+                        },
+                        |(dir, store, pairs)| {
+                            insert(&store, pairs, batched);
+                            (store, dir)
+                        },
+                        BatchSize::PerIteration,
+                    );
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, jmt_insert_rocksdb_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,202 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use jmt::{JellyfishMerkleTree, KeyHash, RootHash, Version};
+use sha2::Sha256;
+use tempfile::TempDir;
+use tree_bench::store::{BufferedTreeStore, InMemoryTreeStore, SledTreeStore, TreeReader, TreeWriter, WarmUp};
+use tree_bench::workload;
+
+const VALUE_LEN: usize = 8;
+
+// Commits every pair either one key per version or all at version 0, persisting each batch
+fn insert<S: TreeReader + TreeWriter>(store: &S, pairs: Vec<(Vec<u8>, Vec<u8>)>, batched: bool) {
+    let jmt: JellyfishMerkleTree<'_, S, Sha256> = JellyfishMerkleTree::new(store);
+    if batched {
+        let (_root, batch) = jmt.put_value_set(
+            pairs.into_iter().map(|(key, value)| (KeyHash::with::<Sha256>(&key), Some(value))),
+            0
+        ).unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+    } else {
+        for (version, (key, value)) in pairs.into_iter().enumerate() {
+            let (_root, batch) = jmt.put_value_set(
+                vec![(KeyHash::with::<Sha256>(&key), Some(value))],
+                version as Version
+            ).unwrap();
+            store.write_node_batch(&batch.node_batch).unwrap();
+        }
+    }
+}
+
+fn jmt_insert_sled_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("jmt_insert_sled");
+
+    for (strategy, batched) in [("sequential", false), ("batched", true)] {
+        for size in [10, 100, 1000].iter() {
+            group.bench_with_input(
+                BenchmarkId::new(format!("insert/memory/{}", strategy), *size),
+                size,
+                |b, &size| {
+                    b.iter_batched(
+                        || (InMemoryTreeStore::new(), workload::pairs(size, VALUE_LEN)),
+                        |(store, pairs)| {
+                            insert(&store, pairs, batched);
+                            store
+                        },
+                        BatchSize::SmallInput,
+                    );
+                },
+            );
+            group.bench_with_input(
+                BenchmarkId::new(format!("insert/sled/{}", strategy), *size),
+                size,
+                |b, &size| {
+                    // Opening a fresh database is setup; returning it keeps the close out of the timing
+                    b.iter_batched(
+                        || {
+                            let dir = TempDir::new().unwrap();
+                            let store = SledTreeStore::open(dir.path()).unwrap();
+                            store.warm_up().unwrap();
+                            (dir, store, workload::pairs(size, VALUE_LEN))
+                        },
+                        |(dir, store, pairs)| {
+                            insert(&store, pairs, batched);
+                            (store, dir)
+                        },
+                        BatchSize::PerIteration,
+                    );
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+struct OnDiskTree {
+    dir: TempDir,
+    root_hash: RootHash,
+    pairs: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+// Builds a batched tree in a fresh database and closes it, leaving only the files behind
+fn build_on_disk(size: usize) -> OnDiskTree {
+    let dir = TempDir::new().unwrap();
+    let pairs = workload::pairs(size, VALUE_LEN);
+    let store = SledTreeStore::open(dir.path()).unwrap();
+    insert(&store, pairs.clone(), true);
+    let root_hash = JellyfishMerkleTree::<'_, SledTreeStore, Sha256>::new(&store).get_root_hash(0).unwrap();
+    OnDiskTree { dir, root_hash, pairs }
+}
+
+fn jmt_reopen_sled_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("jmt_reopen_sled");
+
+    for size in [10, 100, 1000].iter() {
+        group.bench_with_input(BenchmarkId::new("build", *size), size, |b, &size| {
+            b.iter_batched(
+                || {
+                    let dir = TempDir::new().unwrap();
+                    (dir, workload::pairs(size, VALUE_LEN))
+                },
+                |(dir, pairs)| {
+                    let store = SledTreeStore::open(dir.path()).unwrap();
+                    insert(&store, pairs, true);
+                    (store, dir)
+                },
+                BatchSize::PerIteration,
+            );
+        });
+        group.bench_with_input(BenchmarkId::new("reopen_verify", *size), size, |b, &size| {
+            // Reopening the files and checking the root and every value against what was built
+            b.iter_batched(
+                || build_on_disk(size),
+                |OnDiskTree { dir, root_hash, pairs }| {
+                    let store = SledTreeStore::open(dir.path()).unwrap();
+                    let jmt: JellyfishMerkleTree<'_, SledTreeStore, Sha256> = JellyfishMerkleTree::new(&store);
+                    assert_eq!(jmt.get_root_hash(0).unwrap(), root_hash);
+                    for (key, value) in &pairs {
+                        assert_eq!(jmt.get(KeyHash::with::<Sha256>(key), 0).unwrap().as_ref(), Some(value));
+                    }
+                    (store, dir)
+                },
+                BatchSize::PerIteration,
+            );
+        });
+    }
+    group.finish();
+}
+
+fn jmt_cold_sled_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("jmt_cold_sled");
+
+    for size in [100, 1000, 10000].iter() {
+        let tree = build_on_disk(*size);
+        let key_hash = KeyHash::with::<Sha256>(workload::key(size / 2));
+
+        group.bench_with_input(BenchmarkId::new("cold", *size), size, |b, _| {
+            // Every iteration reopens the files, so the proof faults each node in from disk.
+            // The OS page cache may still hold them; sled's own cache starts empty
+            b.iter_batched(
+                || SledTreeStore::open(tree.dir.path()).unwrap(),
+                |store| {
+                    let jmt: JellyfishMerkleTree<'_, SledTreeStore, Sha256> = JellyfishMerkleTree::new(&store);
+                    jmt.get_with_proof(key_hash, 0).unwrap();
+                    store
+                },
+                BatchSize::PerIteration,
+            );
+        });
+
+        let store = SledTreeStore::open(tree.dir.path()).unwrap();
+        store.warm_up().unwrap();
+        let jmt: JellyfishMerkleTree<'_, SledTreeStore, Sha256> = JellyfishMerkleTree::new(&store);
+        let (_value, proof) = jmt.get_with_proof(key_hash, 0).unwrap();
+        proof.verify(tree.root_hash, key_hash, Some(&tree.pairs[size / 2].1)).unwrap();
+        group.bench_with_input(BenchmarkId::new("warm", *size), size, |b, _| {
+            b.iter(|| jmt.get_with_proof(key_hash, 0).unwrap())
+        });
+    }
+    group.finish();
+}
+
+const FLUSH_BATCH_LEN: usize = 100;
+
+fn jmt_flush_sled_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("jmt_flush_sled");
+    // 100 batches on disk take most of a second, too long for the default 100 samples
+    group.sample_size(10);
+
+    for batch_count in [1, 10, 100].iter() {
+        // Every batch inserts fresh keys at its own version, on top of the ones before it
+        let batches: Vec<Vec<_>> = (0..*batch_count)
+            .map(|i| workload::value_set_iter((i + 1) * FLUSH_BATCH_LEN, VALUE_LEN).skip(i * FLUSH_BATCH_LEN).collect())
+            .collect();
+        for (strategy, per_batch) in [("per_batch", true), ("deferred", false)] {
+            group.bench_with_input(BenchmarkId::new(strategy, *batch_count), &batches, |b, batches| {
+                // A sled write flushes to disk, so the buffer turns N flushes into one
+                b.iter_batched(
+                    || {
+                        let dir = TempDir::new().unwrap();
+                        let store = BufferedTreeStore::new(SledTreeStore::open(dir.path()).unwrap());
+                        store.warm_up().unwrap();
+                        (dir, store, batches.clone())
+                    },
+                    |(dir, store, batches)| {
+                        for (version, value_set) in batches.into_iter().enumerate() {
+                            workload::commit_streamed::<_, Sha256>(&store, value_set, version as Version).unwrap();
+                            if per_batch {
+                                store.flush().unwrap();
+                            }
+                        }
+                        store.flush().unwrap();
+                        (store, dir)
+                    },
+                    BatchSize::PerIteration,
+                );
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, jmt_insert_sled_benchmark, jmt_reopen_sled_benchmark, jmt_cold_sled_benchmark, jmt_flush_sled_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,91 @@
+//! Commits 10,000 small versions onto one tree and records commit latency over time, to catch
+//! slowdowns that only show up once many versions have accumulated. This is a single long run
+//! rather than a Criterion benchmark: the point is how latency drifts from the first version
+//! to the last, which repeated samples of one commit would average away.
+
+use jmt::{JellyfishMerkleTree, KeyHash, Version};
+use sha2::Sha256;
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tree_bench::report::RESULTS_DIR_VAR;
+use tree_bench::store::{InMemoryTreeStore, TreeWriter};
+use tree_bench::workload::{self, commit_streamed};
+
+const BASE_SIZE: usize = 10_000;
+const VERSIONS: usize = 10_000;
+const KEYS_PER_VERSION: usize = 8;
+// One CSV row per window, so a slow commit shows up as that window's max
+const WINDOW: usize = 100;
+const VALUE_LEN: usize = 32;
+
+struct Window {
+    last_version: usize,
+    mean: Duration,
+    max: Duration,
+    stored_bytes: usize,
+}
+
+fn main() {
+    let store = InMemoryTreeStore::new();
+    commit_streamed::<_, Sha256>(&store, workload::value_set_iter(BASE_SIZE, VALUE_LEN), 0).unwrap();
+    let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&store);
+    let touched = workload::random_indices(workload::BENCH_SEED, VERSIONS * KEYS_PER_VERSION, BASE_SIZE);
+
+    let mut windows = Vec::with_capacity(VERSIONS / WINDOW);
+    let mut latencies = Vec::with_capacity(WINDOW);
+    for (i, keys) in touched.chunks(KEYS_PER_VERSION).enumerate() {
+        let version = i + 1;
+        let value_set: Vec<_> = keys
+            .iter()
+            .map(|&k| (KeyHash::with::<Sha256>(workload::key(k)), Some(workload::value(version + k, VALUE_LEN))))
+            .collect();
+
+        // A commit is computing the batch and writing it, as a node would do per block
+        let start = Instant::now();
+        let (_root, batch) = tree.put_value_set(value_set, version as Version).unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+        latencies.push(start.elapsed());
+
+        if version.is_multiple_of(WINDOW) {
+            let window = Window {
+                last_version: version,
+                mean: latencies.iter().sum::<Duration>() / latencies.len() as u32,
+                max: latencies.iter().copied().max().unwrap(),
+                stored_bytes: store.estimated_size_bytes().unwrap(),
+            };
+            println!(
+                "jmt_soak/{}: mean {:?}, max {:?}, ~{} bytes stored",
+                window.last_version, window.mean, window.max, window.stored_bytes
+            );
+            windows.push(window);
+            latencies.clear();
+        }
+    }
+
+    let path = write_csv(&windows);
+    println!("jmt_soak: latency over time written to {}", path.display());
+}
+
+// Written to the results directory when one is set, like the JSON exports, else to `target/`
+fn write_csv(windows: &[Window]) -> PathBuf {
+    let out_dir = env::var_os(RESULTS_DIR_VAR).map(PathBuf::from).unwrap_or_else(|| PathBuf::from("target"));
+    let mut csv = String::from("version,mean_us,max_us,stored_bytes\n");
+    for window in windows {
+        writeln!(
+            csv,
+            "{},{:.1},{:.1},{}",
+            window.last_version,
+            window.mean.as_secs_f64() * 1e6,
+            window.max.as_secs_f64() * 1e6,
+            window.stored_bytes
+        )
+        .unwrap();
+    }
+    fs::create_dir_all(&out_dir).unwrap();
+    let path = out_dir.join("jmt_soak.csv");
+    fs::write(&path, csv).unwrap();
+    path
+}
@@ -0,0 +1,66 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use jmt::{JellyfishMerkleTree, KeyHash};
+use sha2::Sha256;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use tree_bench::store::{get_with_proof_async, InMemoryTreeStore, TreeWriter};
+use tree_bench::workload;
+
+const VALUE_LEN: usize = 8;
+const TREE_SIZE: usize = 1000;
+const READS_PER_TASK: usize = 100;
+
+fn populate(store: &InMemoryTreeStore) -> Vec<KeyHash> {
+    let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Sha256> = JellyfishMerkleTree::new(store);
+    let pairs: Vec<(KeyHash, Option<Vec<u8>>)> = workload::pairs(TREE_SIZE, VALUE_LEN)
+        .into_iter()
+        .map(|(key, value)| (KeyHash::with::<Sha256>(&key), Some(value)))
+        .collect();
+    let key_hashes = pairs.iter().map(|(key_hash, _)| *key_hash).collect();
+    let (_root, batch) = jmt.put_value_set(pairs, 0).unwrap();
+    store.write_node_batch(&batch.node_batch).unwrap();
+    key_hashes
+}
+
+// Every task proves its own run of keys, awaiting one blocking-pool walk per key
+fn read_concurrently(runtime: &Runtime, store: &Arc<InMemoryTreeStore>, key_hashes: &Arc<Vec<KeyHash>>, tasks: usize) {
+    runtime.block_on(async {
+        let handles: Vec<_> = (0..tasks)
+            .map(|t| {
+                let store = store.clone();
+                let key_hashes = key_hashes.clone();
+                tokio::task::spawn(async move {
+                    for i in 0..READS_PER_TASK {
+                        let key_hash = key_hashes[(t * READS_PER_TASK + i) % key_hashes.len()];
+                        get_with_proof_async::<_, Sha256>(store.clone(), key_hash, 0).await.unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.await.unwrap();
+        }
+    });
+}
+
+fn jmt_tokio_read_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("jmt_tokio_read");
+
+    let store = InMemoryTreeStore::new();
+    let key_hashes = Arc::new(populate(&store));
+    let store = Arc::new(store);
+    let runtime = Runtime::new().unwrap();
+
+    for tasks in [1, 2, 4, 8].iter() {
+        group.throughput(Throughput::Elements((tasks * READS_PER_TASK) as u64));
+        group.bench_with_input(
+            BenchmarkId::new("get_with_proof_async", tasks),
+            tasks,
+            |b, &tasks| b.iter(|| read_concurrently(&runtime, &store, &key_hashes, tasks)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, jmt_tokio_read_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,86 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use jmt::{JellyfishMerkleTree, KeyHash, OwnedValue};
+use sha2::Sha256;
+use tree_bench::store::{CompressingTreeStore, InMemoryTreeStore, TreeReader, TreeWriter};
+use tree_bench::workload::{self, commit_streamed};
+
+const TREE_SIZE: usize = 1000;
+const VALUE_LEN: usize = 1024;
+const GETS: usize = 100;
+// zstd's own default, the usual speed/size trade
+const LEVEL: i32 = 3;
+
+// A record with the repetitive structure of real application state
+fn compressible_value(i: usize, len: usize) -> Vec<u8> {
+    let record = format!("{{\"owner\":\"key{:08}\",\"balance\":{},\"frozen\":false}},", i, i * 1000);
+    record.as_bytes().iter().cycle().take(len).copied().collect()
+}
+
+fn value_set(value: fn(usize, usize) -> Vec<u8>) -> Vec<(KeyHash, Option<OwnedValue>)> {
+    (0..TREE_SIZE)
+        .map(|i| (KeyHash::with::<Sha256>(workload::key(i)), Some(value(i, VALUE_LEN))))
+        .collect()
+}
+
+fn bench_store<S: TreeReader + TreeWriter>(
+    group: &mut criterion::BenchmarkGroup<'_, criterion::measurement::WallTime>,
+    name: &str,
+    values: &str,
+    value_set: &[(KeyHash, Option<OwnedValue>)],
+    new_store: impl Fn() -> S,
+) -> S {
+    group.throughput(Throughput::Elements(TREE_SIZE as u64));
+    group.bench_function(BenchmarkId::new(format!("insert/{}", name), values), |b| {
+        b.iter_batched(
+            || (new_store(), value_set.to_vec()),
+            |(store, value_set)| {
+                commit_streamed::<_, Sha256>(&store, value_set, 0).unwrap();
+                store
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    let store = new_store();
+    commit_streamed::<_, Sha256>(&store, value_set.to_vec(), 0).unwrap();
+    let jmt: JellyfishMerkleTree<'_, S, Sha256> = JellyfishMerkleTree::new(&store);
+    let lookups: Vec<KeyHash> = workload::random_indices(workload::BENCH_SEED, GETS, TREE_SIZE)
+        .into_iter()
+        .map(|i| value_set[i].0)
+        .collect();
+    group.throughput(Throughput::Elements(GETS as u64));
+    group.bench_function(BenchmarkId::new(format!("get/{}", name), values), |b| {
+        b.iter(|| {
+            for key_hash in &lookups {
+                jmt.get(*key_hash, 0).unwrap();
+            }
+        })
+    });
+    store
+}
+
+// Only values are compressed, so the gap in stored bytes is all value bytes; nodes are the
+// same in both stores
+fn jmt_zstd_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("jmt_zstd");
+    group.sample_size(20);
+    for (values, value) in [("compressible", compressible_value as fn(usize, usize) -> Vec<u8>), ("random", workload::value)] {
+        let value_set = value_set(value);
+        let plain = bench_store(&mut group, "plain", values, &value_set, InMemoryTreeStore::new);
+        let compressed = bench_store(&mut group, "zstd", values, &value_set, || CompressingTreeStore::new(InMemoryTreeStore::new(), LEVEL));
+        let plain_bytes = plain.estimated_size_bytes().unwrap();
+        let compressed_bytes = compressed.inner().estimated_size_bytes().unwrap();
+        println!(
+            "jmt_zstd/{}/{}: {} bytes plain, {} compressed, {:.1}% saved",
+            values,
+            TREE_SIZE,
+            plain_bytes,
+            compressed_bytes,
+            100.0 * (1.0 - compressed_bytes as f64 / plain_bytes as f64)
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, jmt_zstd_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,85 @@
+//! A global allocator wrapper that counts allocations, so benchmarks can report allocation
+//! pressure next to wall-clock time.
+//!
+//! Counting only starts once a binary installs it:
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static ALLOCATOR: tree_bench::alloc::CountingAllocator = tree_bench::alloc::CountingAllocator;
+//! ```
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Forwards to the system allocator, counting every allocation and the bytes it requests.
+/// Frees are not subtracted: the counters measure allocation work, not live memory.
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record(layout.size());
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+fn record(bytes: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    BYTES.fetch_add(bytes as u64, Ordering::Relaxed);
+}
+
+/// Allocation totals since the last [`reset`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    pub allocations: u64,
+    pub bytes: u64,
+}
+
+/// Zeroes the counters.
+pub fn reset() {
+    ALLOCATIONS.store(0, Ordering::Relaxed);
+    BYTES.store(0, Ordering::Relaxed);
+}
+
+/// Reads the counters. Allocations from every thread are included.
+pub fn snapshot() -> AllocStats {
+    AllocStats {
+        allocations: ALLOCATIONS.load(Ordering::Relaxed),
+        bytes: BYTES.load(Ordering::Relaxed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    #[test]
+    fn counts_allocations_since_reset() {
+        // Other tests may allocate concurrently, so only lower bounds hold
+        reset();
+        let buffer: Vec<u8> = Vec::with_capacity(4096);
+        let stats = snapshot();
+        assert!(stats.allocations >= 1);
+        assert!(stats.bytes >= 4096);
+        drop(buffer);
+    }
+}
@@ -0,0 +1,87 @@
+//! Graphviz rendering of a tree, for eyeballing the shape a benchmark built.
+
+use jmt::storage::{NibblePath, Node, NodeKey, TreeReader};
+use jmt::Version;
+use std::fmt::Write;
+
+/// Renders the tree at `version` as a Graphviz DOT digraph, walking down from the root.
+/// Internal nodes are labeled by their nibble path and leaves by their path and key hash;
+/// edges carry the child's nibble. Render it with e.g. `dot -Tsvg`.
+///
+/// Every reachable node is emitted, so this is meant for trees of a few hundred keys at most.
+pub fn to_dot<R: TreeReader>(store: &R, version: Version) -> Result<String, anyhow::Error> {
+    let mut dot = String::from("digraph jmt {\n    node [fontname=\"monospace\"];\n");
+    let mut pending = vec![NodeKey::new(version, std::iter::empty().collect())];
+    while let Some(node_key) = pending.pop() {
+        let id = node_id(node_key.nibble_path());
+        match store.get_node(&node_key)? {
+            Node::Internal(internal) => {
+                writeln!(dot, "    {} [shape=box, label=\"{}\"];", id, path_label(node_key.nibble_path()))?;
+                for (nibble, child) in internal.children_sorted() {
+                    let child_path: NibblePath = node_key.nibble_path().nibbles().chain(std::iter::once(nibble)).collect();
+                    writeln!(dot, "    {} -> {} [label=\"{:x}\"];", id, node_id(&child_path), nibble)?;
+                    pending.push(NodeKey::new(child.version, child_path));
+                }
+            }
+            Node::Leaf(leaf) => {
+                let key_hash: String = leaf.key_hash().0.iter().map(|byte| format!("{:02x}", byte)).collect();
+                writeln!(
+                    dot,
+                    "    {} [shape=ellipse, label=\"{}\\n{}\"];",
+                    id,
+                    path_label(node_key.nibble_path()),
+                    key_hash
+                )?;
+            }
+            Node::Null => writeln!(dot, "    {} [shape=point, label=\"\"];", id)?,
+        }
+    }
+    dot.push_str("}\n");
+    Ok(dot)
+}
+
+// Paths are unique within one version's tree, so they double as DOT node IDs
+fn node_id(nibble_path: &NibblePath) -> String {
+    format!("n{}", nibble_path.nibbles().map(|nibble| format!("{:x}", nibble)).collect::<String>())
+}
+
+fn path_label(nibble_path: &NibblePath) -> String {
+    if nibble_path.num_nibbles() == 0 {
+        "root".to_string()
+    } else {
+        nibble_path.nibbles().map(|nibble| format!("{:x}", nibble)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{InMemoryTreeStore, TreeWriter};
+    use jmt::{JellyfishMerkleTree, KeyHash};
+    use sha2::Sha256;
+
+    #[test]
+    fn two_leaf_tree_labels_both_leaves() {
+        let store = InMemoryTreeStore::new();
+        let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Sha256> = JellyfishMerkleTree::new(&store);
+        // Hashes that differ in the first nibble, so both leaves hang straight off the root
+        let mut left = [0u8; 32];
+        left[31] = 1;
+        let mut right = [0xf0u8; 32];
+        right[31] = 2;
+        let (_root, batch) = jmt
+            .put_value_set(
+                vec![(KeyHash(left), Some(b"left".to_vec())), (KeyHash(right), Some(b"right".to_vec()))],
+                0,
+            )
+            .unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+
+        let dot = to_dot(&store, 0).unwrap();
+        assert!(dot.starts_with("digraph jmt {"), "{}", dot);
+        assert!(dot.contains("n [shape=box, label=\"root\"]"), "{}", dot);
+        assert!(dot.contains(&format!("n0 [shape=ellipse, label=\"0\\n{}01\"]", "00".repeat(31))), "{}", dot);
+        assert!(dot.contains(&format!("nf [shape=ellipse, label=\"f\\n{}02\"]", "f0".repeat(31))), "{}", dot);
+        assert!(dot.contains("n -> nf [label=\"f\"]"), "{}", dot);
+    }
+}
@@ -0,0 +1,76 @@
+//! One digest per tree version, for telling whether two runs or two builds committed exactly
+//! the same tree.
+
+use jmt::storage::{NibblePath, Node, NodeKey};
+use jmt::{SimpleHasher, Version};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+
+use crate::store::{InMemoryTreeStore, StoreError};
+
+// What jmt hashes an empty tree's null root to
+const NULL_HASH: [u8; 32] = *b"SPARSE_MERKLE_PLACEHOLDER_HASH__";
+
+/// Digests every node reachable from the root at `version`, as `(NodeKey, node hash)` pairs
+/// sorted by the key's bincode encoding, into one Sha256 hash. Node hashes are computed with
+/// the tree's hasher `H`. Unlike the root hash, the fingerprint also covers the version each
+/// node was written at, so it changes when a store or workload change rewrites nodes that
+/// hash the same. A node the walk cannot find fails with [`StoreError::NotFound`].
+pub fn fingerprint<H: SimpleHasher>(store: &InMemoryTreeStore, version: Version) -> Result<[u8; 32], StoreError> {
+    let mut pairs = BTreeMap::new();
+    let mut pending = vec![NodeKey::new(version, std::iter::empty().collect())];
+    while let Some(node_key) = pending.pop() {
+        let node = store
+            .node(&node_key)?
+            .ok_or_else(|| StoreError::NotFound(format!("node at version {}", node_key.version())))?;
+        let hash = match &node {
+            Node::Internal(internal) => {
+                for (nibble, child) in internal.children_sorted() {
+                    let child_path: NibblePath = node_key.nibble_path().nibbles().chain(std::iter::once(nibble)).collect();
+                    pending.push(NodeKey::new(child.version, child_path));
+                }
+                internal.hash::<H>()
+            }
+            Node::Leaf(leaf) => leaf.hash::<H>(),
+            Node::Null => NULL_HASH,
+        };
+        pairs.insert(bincode::serialize(&node_key)?, hash);
+    }
+
+    let mut digest = Sha256::new();
+    for (node_key, hash) in pairs {
+        digest.update(&node_key);
+        digest.update(&hash);
+    }
+    Ok(digest.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workload;
+    use jmt::KeyHash;
+
+    fn build(value_set: Vec<(KeyHash, Option<Vec<u8>>)>) -> InMemoryTreeStore {
+        let store = InMemoryTreeStore::new();
+        workload::commit_streamed::<_, Sha256>(&store, workload::value_set_iter(100, 8), 0).unwrap();
+        workload::commit_streamed::<_, Sha256>(&store, value_set, 1).unwrap();
+        store
+    }
+
+    #[test]
+    fn identical_trees_share_a_fingerprint_and_a_change_breaks_it() {
+        let key_hash = KeyHash::with::<Sha256>(workload::key(3));
+        let first = build(vec![(key_hash, Some(b"updated".to_vec()))]);
+        let second = build(vec![(key_hash, Some(b"updated".to_vec()))]);
+        let modified = build(vec![(key_hash, Some(b"modified".to_vec()))]);
+
+        let expected = fingerprint::<Sha256>(&first, 1).unwrap();
+        assert_eq!(fingerprint::<Sha256>(&second, 1).unwrap(), expected);
+        assert_ne!(fingerprint::<Sha256>(&modified, 1).unwrap(), expected);
+        // Version 0 is the same in all three stores
+        assert_eq!(fingerprint::<Sha256>(&modified, 0).unwrap(), fingerprint::<Sha256>(&first, 0).unwrap());
+        assert_ne!(fingerprint::<Sha256>(&first, 0).unwrap(), expected);
+        assert!(matches!(fingerprint::<Sha256>(&first, 2), Err(StoreError::NotFound(_))));
+    }
+}
@@ -0,0 +1,63 @@
+//! Tree hashers beyond the `digest` implementations jmt accepts out of the box.
+
+use jmt::SimpleHasher;
+
+/// BLAKE3 as a JMT tree hasher, through blake3's stable API rather than its preview `digest`
+/// traits. It produces the same 32-byte digests as `blake3::hash`.
+#[derive(Clone, Default)]
+pub struct Blake3(blake3::Hasher);
+
+impl SimpleHasher for Blake3 {
+    fn new() -> Self {
+        Blake3(blake3::Hasher::new())
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> [u8; 32] {
+        self.0.finalize().into()
+    }
+}
+
+/// Keccak-256, the pre-standard SHA-3 that Ethereum keys its state with. It is a `digest`
+/// hasher, so it works both as the tree hasher and for key hashing:
+/// `KeyHash::with::<Keccak256>(key)`.
+#[cfg(feature = "keccak")]
+pub use sha3::Keccak256;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(digest: [u8; 32]) -> String {
+        digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[test]
+    fn blake3_matches_known_digests() {
+        assert_eq!(hex(Blake3::hash(b"")), "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262");
+        assert_eq!(hex(Blake3::hash(b"abc")), "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85");
+
+        let mut hasher = <Blake3 as SimpleHasher>::new();
+        hasher.update(b"a");
+        hasher.update(b"bc");
+        assert_eq!(hasher.finalize(), Blake3::hash(b"abc"));
+    }
+
+    #[cfg(feature = "keccak")]
+    #[test]
+    fn keccak256_matches_known_digests() {
+        // Keccak-256 pads differently from FIPS SHA3-256, so these differ from sha3_256 vectors
+        assert_eq!(
+            hex(<Keccak256 as SimpleHasher>::hash(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        assert_eq!(
+            hex(<Keccak256 as SimpleHasher>::hash(b"abc")),
+            "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+        );
+        assert_eq!(jmt::KeyHash::with::<Keccak256>(b"abc").0, <Keccak256 as SimpleHasher>::hash(b"abc"));
+    }
+}
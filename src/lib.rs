@@ -0,0 +1,15 @@
+//! Shared building blocks for the JMT benchmarks.
+
+#[cfg(feature = "count-alloc")]
+pub mod alloc;
+#[cfg(test)]
+mod differential;
+pub mod dot;
+pub mod fingerprint;
+pub mod hash;
+pub mod proof;
+pub mod report;
+#[cfg(test)]
+mod root_hashes;
+pub mod store;
+pub mod workload;
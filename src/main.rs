@@ -1,3 +1,147 @@
-fn main() {
-    println!("Hello, world!");
+//! Runs one benchmark scenario once with the given parameters and prints how long it took,
+//! for quick checks that don't warrant a full Criterion run.
+
+use blake2::Blake2s256;
+use clap::{Parser, ValueEnum};
+use jmt::SimpleHasher;
+use sha2::Sha256;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tree_bench::hash::Blake3;
+use tree_bench::store::{BTreeMapTreeStore, BoxedStore, InMemoryTreeStore, ShardedTreeStore, WarmUp};
+use tree_bench::workload::{CommitStrategy, Delete, Get, Insert, KeyDistribution, Update, Workload};
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Scenario {
+    Insert,
+    Get,
+    Update,
+    Delete,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Hasher {
+    Sha256,
+    Blake2s,
+    Blake3,
+    #[cfg(feature = "keccak")]
+    Keccak256,
+}
+
+impl Hasher {
+    fn name(self) -> &'static str {
+        match self {
+            Hasher::Sha256 => "sha256",
+            Hasher::Blake2s => "blake2s",
+            Hasher::Blake3 => "blake3",
+            #[cfg(feature = "keccak")]
+            Hasher::Keccak256 => "keccak256",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Backend {
+    Memory,
+    Btree,
+    Sharded,
+    #[cfg(feature = "sled")]
+    Sled,
+    #[cfg(feature = "rocksdb")]
+    Rocksdb,
+}
+
+impl Backend {
+    // On-disk stores get a fresh directory under `dir` for every run, so no run reads another's
+    // tree; it is left in place for inspection
+    #[cfg_attr(not(any(feature = "sled", feature = "rocksdb")), allow(unused_variables))]
+    fn open(self, dir: &std::path::Path) -> Result<BoxedStore, anyhow::Error> {
+        Ok(match self {
+            Backend::Memory => BoxedStore::new(InMemoryTreeStore::new()),
+            Backend::Btree => BoxedStore::new(BTreeMapTreeStore::new()),
+            Backend::Sharded => BoxedStore::new(ShardedTreeStore::new()),
+            #[cfg(feature = "sled")]
+            Backend::Sled => BoxedStore::new(tree_bench::store::SledTreeStore::open(fresh_dir(dir, "sled")?)?),
+            #[cfg(feature = "rocksdb")]
+            Backend::Rocksdb => BoxedStore::new(tree_bench::store::RocksDbTreeStore::open(fresh_dir(dir, "rocksdb")?)?),
+        })
+    }
+}
+
+#[cfg(any(feature = "sled", feature = "rocksdb"))]
+fn fresh_dir(dir: &std::path::Path, backend: &str) -> Result<PathBuf, anyhow::Error> {
+    let path = dir.join(format!("{}-{}", backend, std::process::id()));
+    if path.exists() {
+        std::fs::remove_dir_all(&path)?;
+    }
+    Ok(path)
+}
+
+#[derive(Debug, Parser)]
+#[command(name = "tree-bench", about = "Time a single pass of one JMT benchmark scenario")]
+struct Args {
+    #[arg(long, value_enum, default_value = "insert")]
+    scenario: Scenario,
+    /// Number of keys in the tree
+    #[arg(long, default_value_t = 1000)]
+    size: usize,
+    #[arg(long, value_enum, default_value = "sha256")]
+    hasher: Hasher,
+    /// Value length in bytes
+    #[arg(long, default_value_t = 8)]
+    value_len: usize,
+    /// `sequential` or `batched`; insert only
+    #[arg(long, default_value = "batched")]
+    strategy: CommitStrategy,
+    /// `sequential` or `random`; insert and get only
+    #[arg(long, default_value = "sequential")]
+    distribution: KeyDistribution,
+    /// Store to run against; `sled` and `rocksdb` need their features
+    #[arg(long, value_enum, default_value = "memory")]
+    backend: Backend,
+    /// Directory the on-disk backends create their store in
+    #[arg(long, default_value = "target/tree-bench-stores")]
+    dir: PathBuf,
+}
+
+// Sets up untimed, then times exactly one `run`, as a single Criterion iteration would
+fn time_once<W: Workload, H: SimpleHasher>(workload: &W, args: &Args) -> Result<Duration, anyhow::Error> {
+    let size = args.size;
+    let store = args.backend.open(&args.dir)?;
+    workload.setup::<_, H>(&store, size)?;
+    store.warm_up()?;
+    let batch = workload.prepare(size);
+
+    let start = Instant::now();
+    workload.run::<_, H>(&store, batch)?;
+    Ok(start.elapsed())
+}
+
+fn run<W: Workload>(workload: &W, args: &Args) -> Result<(), anyhow::Error> {
+    let elapsed = match args.hasher {
+        Hasher::Sha256 => time_once::<W, Sha256>(workload, args)?,
+        Hasher::Blake2s => time_once::<W, Blake2s256>(workload, args)?,
+        Hasher::Blake3 => time_once::<W, Blake3>(workload, args)?,
+        #[cfg(feature = "keccak")]
+        Hasher::Keccak256 => time_once::<W, tree_bench::hash::Keccak256>(workload, args)?,
+    };
+
+    println!("scenario:   {}/{}", workload.id(args.hasher.name()), args.size);
+    println!("backend:    {}", args.backend.to_possible_value().unwrap().get_name());
+    println!("total:      {:.3} ms", elapsed.as_secs_f64() * 1e3);
+    println!("throughput: {:.0} ops/sec", args.size as f64 / elapsed.as_secs_f64());
+    Ok(())
+}
+
+fn main() -> Result<(), anyhow::Error> {
+    let args = Args::parse();
+    match args.scenario {
+        Scenario::Insert => run(
+            &Insert { strategy: args.strategy, distribution: args.distribution, value_len: args.value_len },
+            &args,
+        ),
+        Scenario::Get => run(&Get { distribution: args.distribution, value_len: args.value_len }, &args),
+        Scenario::Update => run(&Update { value_len: args.value_len }, &args),
+        Scenario::Delete => run(&Delete { value_len: args.value_len }, &args),
+    }
 }
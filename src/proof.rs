@@ -0,0 +1,258 @@
+//! Helpers for measuring the proofs a tree hands to light clients.
+
+use jmt::proof::SparseMerkleProof;
+use jmt::{KeyHash, OwnedValue, RootHash, SimpleHasher};
+
+use crate::store::StoreError;
+
+/// The size of `proof` on the wire, in bytes, as bincode encodes it.
+pub fn proof_size<H: SimpleHasher>(proof: &SparseMerkleProof<H>) -> Result<usize, anyhow::Error> {
+    Ok(bincode::serialize(proof)?.len())
+}
+
+/// How many siblings `proof` carries: one per bit of the path from the root to the proven leaf,
+/// since jmt proves each 16-way node as a binary subtree. jmt keeps the list private, so it is
+/// counted through the proof's serde form.
+pub fn sibling_count<H: SimpleHasher>(proof: &SparseMerkleProof<H>) -> usize {
+    proof_to_json(proof)["siblings"].as_array().map_or(0, Vec::len)
+}
+
+/// Verifies every `(key_hash, value, proof)` against one `root`, as a light client checking a
+/// batch of reads would, and fails with [`StoreError::InvalidProof`] at the first proof that
+/// does not hold. `None` values are checked as non-membership. jmt's proofs share no
+/// verification work, so this is a loop over `verify`; it exists to price that baseline and any
+/// per-call overhead.
+#[allow(clippy::type_complexity)]
+pub fn verify_batch<H: SimpleHasher>(
+    root: RootHash,
+    items: &[(KeyHash, Option<OwnedValue>, SparseMerkleProof<H>)],
+) -> Result<(), StoreError> {
+    for (index, (key_hash, value, proof)) in items.iter().enumerate() {
+        proof
+            .verify(root, *key_hash, value.as_ref())
+            .map_err(|e| StoreError::InvalidProof { index, reason: e.to_string() })?;
+    }
+    Ok(())
+}
+
+/// The size of an ICS23 `proof` on the wire, in bytes, as protobuf encodes it.
+#[cfg(feature = "ics23")]
+pub fn ics23_proof_size(proof: &ics23::CommitmentProof) -> usize {
+    prost::Message::encoded_len(proof)
+}
+
+/// Renders `proof` as JSON for comparing it by eye against another implementation's. The
+/// layout is the proof's own serde form, with every 32-byte hash written as a lowercase hex
+/// string: the leaf's key and value hashes, and the child hashes of each sibling.
+pub fn proof_to_json<H: SimpleHasher>(proof: &SparseMerkleProof<H>) -> serde_json::Value {
+    hashes_to_hex(serde_json::to_value(proof).expect("proofs have no map keys, so always encode"))
+}
+
+/// Parses JSON written by [`proof_to_json`] back into a proof that can be verified.
+pub fn proof_from_json<H: SimpleHasher>(json: &serde_json::Value) -> Result<SparseMerkleProof<H>, anyhow::Error> {
+    Ok(serde_json::from_value(hex_to_hashes(json.clone())?)?)
+}
+
+// serde writes a [u8; 32] as an array of 32 numbers; nothing else in a proof has that shape
+fn hashes_to_hex(json: serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+    match json {
+        Value::Array(items) if items.len() == 32 && items.iter().all(|item| item.as_u64().is_some_and(|n| n <= 0xff)) => {
+            Value::String(items.iter().map(|item| format!("{:02x}", item.as_u64().unwrap())).collect())
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(hashes_to_hex).collect()),
+        Value::Object(fields) => Value::Object(fields.into_iter().map(|(name, field)| (name, hashes_to_hex(field))).collect()),
+        other => other,
+    }
+}
+
+// Hex strings only ever stand for hashes; the other strings are enum variant names
+fn hex_to_hashes(json: serde_json::Value) -> Result<serde_json::Value, anyhow::Error> {
+    use serde_json::Value;
+    Ok(match json {
+        Value::String(hex) if hex.len() == 64 && hex.bytes().all(|b| b.is_ascii_hexdigit()) => Value::Array(
+            (0..32)
+                .map(|i| u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).map(Value::from))
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(hex_to_hashes).collect::<Result<_, _>>()?),
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(name, field)| Ok((name, hex_to_hashes(field)?)))
+                .collect::<Result<_, anyhow::Error>>()?,
+        ),
+        other => other,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{InMemoryTreeStore, TreeWriter};
+    use crate::workload;
+    use jmt::{JellyfishMerkleTree, KeyHash};
+    use sha2::Sha256;
+
+    fn first_key_proof_size(size: usize) -> usize {
+        let store = InMemoryTreeStore::new();
+        let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Sha256> = JellyfishMerkleTree::new(&store);
+        let (_root, batch) = jmt
+            .put_value_set(
+                workload::pairs(size, 8)
+                    .into_iter()
+                    .map(|(key, value)| (KeyHash::with::<Sha256>(&key), Some(value))),
+                0,
+            )
+            .unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+
+        let (_value, proof) = jmt.get_with_proof(KeyHash::with::<Sha256>(workload::key(0)), 0).unwrap();
+        proof_size(&proof).unwrap()
+    }
+
+    #[cfg(feature = "ics23")]
+    #[test]
+    fn ics23_proof_verifies_against_the_root() {
+        let store = crate::store::PreimageStore::new(InMemoryTreeStore::new());
+        let jmt: JellyfishMerkleTree<'_, _, Sha256> = JellyfishMerkleTree::new(&store);
+        let mut value_set = Vec::new();
+        for (key, value) in workload::pairs(100, 8) {
+            let key_hash = KeyHash::with::<Sha256>(&key);
+            store.record_preimage(key_hash, key).unwrap();
+            value_set.push((key_hash, Some(value)));
+        }
+        let (root, batch) = jmt.put_value_set(value_set, 0).unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+
+        let (value, proof) = jmt.get_with_ics23_proof(workload::key(0), 0).unwrap();
+        assert_eq!(value, Some(workload::value(0, 8)));
+        assert!(ics23_proof_size(&proof) > 0);
+        assert!(ics23::verify_membership::<ics23::HostFunctionsManager>(
+            &proof,
+            &jmt::ics23_spec(),
+            &root.0.to_vec(),
+            &workload::key(0),
+            &value.unwrap(),
+        ));
+    }
+
+    #[test]
+    fn json_proof_shows_hex_hashes_and_verifies_after_parsing() {
+        let store = InMemoryTreeStore::new();
+        let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Sha256> = JellyfishMerkleTree::new(&store);
+        let (root, batch) = jmt.put_value_set(workload::value_set_iter(100, 8), 0).unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+        let key_hash = KeyHash::with::<Sha256>(workload::key(0));
+        let (value, proof) = jmt.get_with_proof(key_hash, 0).unwrap();
+
+        let json = proof_to_json(&proof);
+        let key_hex: String = key_hash.0.iter().map(|byte| format!("{:02x}", byte)).collect();
+        assert_eq!(json["leaf"]["key_hash"], serde_json::Value::String(key_hex));
+        assert!(!json["siblings"].as_array().unwrap().is_empty());
+
+        let text = serde_json::to_string(&json).unwrap();
+        let parsed: SparseMerkleProof<Sha256> = proof_from_json(&serde_json::from_str(&text).unwrap()).unwrap();
+        assert_eq!(parsed, proof);
+        parsed.verify(root, key_hash, value).unwrap();
+    }
+
+    #[test]
+    fn proofs_are_rejected_against_a_tampered_root() {
+        let store = InMemoryTreeStore::new();
+        let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Sha256> = JellyfishMerkleTree::new(&store);
+        let (root, batch) = jmt.put_value_set(workload::value_set_iter(100, 8), 0).unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+        let mut tampered = root;
+        tampered.0[31] ^= 1;
+
+        let present = KeyHash::with::<Sha256>(workload::key(0));
+        let (value, proof) = jmt.get_with_proof(present, 0).unwrap();
+        proof.verify(root, present, value.as_ref()).unwrap();
+        assert!(proof.verify(tampered, present, value.as_ref()).is_err());
+
+        let absent = KeyHash::with::<Sha256>("absent");
+        let (value, proof) = jmt.get_with_proof(absent, 0).unwrap();
+        assert!(value.is_none());
+        proof.verify_nonexistence(root, absent).unwrap();
+        assert!(proof.verify_nonexistence(tampered, absent).is_err());
+    }
+
+    #[test]
+    fn batch_verification_names_the_bad_proof() {
+        let store = InMemoryTreeStore::new();
+        let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Sha256> = JellyfishMerkleTree::new(&store);
+        let (root, batch) = jmt.put_value_set(workload::value_set_iter(100, 8), 0).unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+        let mut items: Vec<_> = [workload::key(0), workload::key(1), b"absent".to_vec()]
+            .iter()
+            .map(|key| {
+                let key_hash = KeyHash::with::<Sha256>(key);
+                let (value, proof) = jmt.get_with_proof(key_hash, 0).unwrap();
+                (key_hash, value, proof)
+            })
+            .collect();
+        verify_batch(root, &items).unwrap();
+
+        items[1].1 = Some(b"forged".to_vec());
+        match verify_batch(root, &items) {
+            Err(StoreError::InvalidProof { index, .. }) => assert_eq!(index, 1),
+            other => panic!("expected proof 1 to fail, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn sibling_count_is_the_depth_of_the_proven_leaf() {
+        let store = InMemoryTreeStore::new();
+        let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Sha256> = JellyfishMerkleTree::new(&store);
+        // The first two differ in their first nibble; the third shares the first one's
+        let (low, high, near) = (KeyHash([0x00; 32]), KeyHash([0xf0; 32]), KeyHash([0x01; 32]));
+        let value_set = |key_hashes: &[KeyHash]| key_hashes.iter().map(|key_hash| (*key_hash, Some(b"value".to_vec()))).collect::<Vec<_>>();
+        let (_root, batch) = jmt.put_value_set(value_set(&[low]), 0).unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+        let (_root, batch) = jmt.put_value_set(value_set(&[high]), 1).unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+        let (_root, batch) = jmt.put_value_set(value_set(&[near]), 2).unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+
+        let siblings = |key_hash, version| sibling_count(&jmt.get_with_proof(key_hash, version).unwrap().1);
+        assert_eq!(siblings(low, 0), 0);
+        assert_eq!(siblings(low, 1), 1);
+        assert_eq!(siblings(high, 2), 1);
+        assert!(siblings(low, 2) > 1);
+    }
+
+    // jmt has no way to patch a proof, so after any commit every proof is regenerated whole.
+    // An update to one key leaves each other key's proof stale in exactly one sibling, the
+    // subtree holding the changed key where the two paths part
+    #[test]
+    fn one_update_leaves_other_proofs_stale_in_exactly_one_sibling() {
+        let store = InMemoryTreeStore::new();
+        let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Sha256> = JellyfishMerkleTree::new(&store);
+        let old_root = workload::commit_streamed::<_, Sha256>(&store, workload::value_set_iter(100, 8), 0).unwrap();
+        let changed = KeyHash::with::<Sha256>(workload::key(0));
+        let new_root = workload::commit_streamed::<_, Sha256>(&store, vec![(changed, Some(b"changed".to_vec()))], 1).unwrap();
+
+        for i in 1..100 {
+            let key_hash = KeyHash::with::<Sha256>(workload::key(i));
+            let (value, old_proof) = jmt.get_with_proof(key_hash, 0).unwrap();
+            let (new_value, new_proof) = jmt.get_with_proof(key_hash, 1).unwrap();
+            assert_eq!(new_value, value);
+            old_proof.verify(old_root, key_hash, value.as_ref()).unwrap();
+            assert!(old_proof.verify(new_root, key_hash, value.as_ref()).is_err());
+            new_proof.verify(new_root, key_hash, value.as_ref()).unwrap();
+
+            let (old_json, new_json) = (proof_to_json(&old_proof), proof_to_json(&new_proof));
+            let (old_siblings, new_siblings) = (old_json["siblings"].as_array().unwrap(), new_json["siblings"].as_array().unwrap());
+            assert_eq!(old_siblings.len(), new_siblings.len());
+            assert_eq!(old_siblings.iter().zip(new_siblings).filter(|(old, new)| old != new).count(), 1, "key {}", i);
+        }
+    }
+
+    #[test]
+    fn proof_size_grows_with_tree_size() {
+        let sizes: Vec<usize> = [1, 10, 1000].into_iter().map(first_key_proof_size).collect();
+        assert!(sizes[0] < sizes[1], "{:?}", sizes);
+        assert!(sizes[1] < sizes[2], "{:?}", sizes);
+    }
+}
@@ -0,0 +1,246 @@
+//! Exports Criterion's results as one JSON file per benchmark group, so runs can be diffed
+//! in CI or committed to track performance over time.
+//!
+//! Export is opt-in: benches call [`export_group`] after each group, which does nothing unless
+//! [`RESULTS_DIR_VAR`] names an output directory.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::store::TreeStats;
+
+/// Environment variable naming the directory `<group>.json` files are written to.
+pub const RESULTS_DIR_VAR: &str = "TREE_BENCH_RESULTS";
+
+/// Environment variable naming a Criterion baseline to save this run's results under.
+pub const SAVE_BASELINE_VAR: &str = "TREE_BENCH_SAVE_BASELINE";
+
+/// Environment variable naming a saved Criterion baseline to compare this run against.
+pub const BASELINE_VAR: &str = "TREE_BENCH_BASELINE";
+
+/// Environment variable choosing how much of `jmt_benchmark` runs: `full`, the default, or
+/// `ci`, which takes fewer samples and runs Sha256 alone at sizes up to 1000.
+pub const PROFILE_VAR: &str = "TREE_BENCH_PROFILE";
+
+// Tree hashers as they appear in benchmark IDs
+const HASHERS: [&str; 4] = ["sha256", "blake2s", "blake3", "keccak256"];
+
+/// One benchmark's timings, read back from Criterion's output.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct BenchResult {
+    /// Criterion's full ID, e.g. `jmt_get/get/sha256/random/100`.
+    pub id: String,
+    pub size: Option<usize>,
+    pub hasher: Option<String>,
+    pub mean_ns: f64,
+    pub median_ns: f64,
+    /// Key operations per second at the mean time, for benchmarks that count elements.
+    pub elements_per_sec: Option<f64>,
+    /// Value bytes per second at the mean time, for benchmarks that count bytes.
+    pub bytes_per_sec: Option<f64>,
+    /// Shape of the tree the benchmark ran on, when the bench recorded one for this size.
+    pub stats: Option<TreeStats>,
+}
+
+// The parts of Criterion's `benchmark.json` and `estimates.json` that are read back
+#[derive(Deserialize)]
+struct CriterionBenchmark {
+    group_id: String,
+    function_id: Option<String>,
+    value_str: Option<String>,
+    throughput: Option<CriterionThroughput>,
+    full_id: String,
+}
+
+#[derive(Deserialize)]
+enum CriterionThroughput {
+    Bytes(u64),
+    BytesDecimal(u64),
+    Elements(u64),
+}
+
+#[derive(Deserialize)]
+struct CriterionEstimates {
+    mean: CriterionEstimate,
+    median: CriterionEstimate,
+}
+
+#[derive(Deserialize)]
+struct CriterionEstimate {
+    point_estimate: f64,
+}
+
+/// Where Criterion writes its results, resolved the way Criterion does short of running
+/// `cargo metadata`.
+pub fn criterion_home() -> PathBuf {
+    if let Some(home) = env::var_os("CRITERION_HOME") {
+        PathBuf::from(home)
+    } else if let Some(target) = env::var_os("CARGO_TARGET_DIR") {
+        PathBuf::from(target).join("criterion")
+    } else {
+        PathBuf::from("target/criterion")
+    }
+}
+
+/// Reads the latest result of every benchmark in `group` under `criterion_home`, sorted by
+/// ID, attaching `stats` by benchmark size.
+pub fn collect_group(
+    criterion_home: &Path,
+    group: &str,
+    stats: &BTreeMap<usize, TreeStats>,
+) -> Result<Vec<BenchResult>, anyhow::Error> {
+    let mut results = Vec::new();
+    let mut pending = vec![criterion_home.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        if !dir.is_dir() {
+            continue;
+        }
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if !path.is_dir() {
+                continue;
+            }
+            // Criterion keeps the latest run in `new/` next to `base/` and its reports
+            if path.file_name().is_some_and(|name| name == "new") {
+                if let Some(result) = read_result(&path, group, stats)? {
+                    results.push(result);
+                }
+            } else {
+                pending.push(path);
+            }
+        }
+    }
+
+    results.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(results)
+}
+
+fn read_result(
+    dir: &Path,
+    group: &str,
+    stats: &BTreeMap<usize, TreeStats>,
+) -> Result<Option<BenchResult>, anyhow::Error> {
+    let (benchmark, estimates) = match (
+        fs::read(dir.join("benchmark.json")),
+        fs::read(dir.join("estimates.json")),
+    ) {
+        (Ok(benchmark), Ok(estimates)) => (benchmark, estimates),
+        _ => return Ok(None),
+    };
+    let benchmark: CriterionBenchmark = serde_json::from_slice(&benchmark)?;
+    if benchmark.group_id != group {
+        return Ok(None);
+    }
+    let estimates: CriterionEstimates = serde_json::from_slice(&estimates)?;
+
+    let size = benchmark.value_str.as_deref().and_then(|value| value.parse().ok());
+    let hasher = benchmark.function_id.as_deref().and_then(|function| {
+        function
+            .split('/')
+            .find(|segment| HASHERS.contains(segment))
+            .map(str::to_string)
+    });
+    let mean_ns = estimates.mean.point_estimate;
+    let per_sec = |count: u64| count as f64 * 1e9 / mean_ns;
+    let (elements_per_sec, bytes_per_sec) = match benchmark.throughput {
+        Some(CriterionThroughput::Elements(elements)) => (Some(per_sec(elements)), None),
+        Some(CriterionThroughput::Bytes(bytes) | CriterionThroughput::BytesDecimal(bytes)) => (None, Some(per_sec(bytes))),
+        None => (None, None),
+    };
+    Ok(Some(BenchResult {
+        id: benchmark.full_id,
+        size,
+        hasher,
+        mean_ns,
+        median_ns: estimates.median.point_estimate,
+        elements_per_sec,
+        bytes_per_sec,
+        stats: size.and_then(|size| stats.get(&size).copied()),
+    }))
+}
+
+/// Writes `results` to `<out_dir>/<group>.json` and returns the path.
+pub fn write_group(out_dir: &Path, group: &str, results: &[BenchResult]) -> Result<PathBuf, anyhow::Error> {
+    fs::create_dir_all(out_dir)?;
+    let path = out_dir.join(format!("{}.json", group));
+    fs::write(&path, serde_json::to_vec_pretty(results)?)?;
+    Ok(path)
+}
+
+/// Collects and writes `group`'s results if [`RESULTS_DIR_VAR`] is set, returning the file
+/// written. Call it after the group's `finish()`.
+pub fn export_group(group: &str, stats: &BTreeMap<usize, TreeStats>) -> Result<Option<PathBuf>, anyhow::Error> {
+    let out_dir = match env::var_os(RESULTS_DIR_VAR) {
+        Some(dir) => PathBuf::from(dir),
+        None => return Ok(None),
+    };
+    let results = collect_group(&criterion_home(), group, stats)?;
+    Ok(Some(write_group(&out_dir, group, &results)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn fake_result(home: &Path, dir: &str, group: &str, function: &str, value: &str, mean: f64, throughput: &str) {
+        let new_dir = home.join(dir).join("new");
+        fs::create_dir_all(&new_dir).unwrap();
+        let full_id = format!("{}/{}/{}", group, function, value);
+        fs::write(
+            new_dir.join("benchmark.json"),
+            format!(
+                r#"{{"group_id":"{}","function_id":"{}","value_str":"{}","throughput":{},"full_id":"{}","directory_name":"{}","title":"{}"}}"#,
+                group, function, value, throughput, full_id, dir, full_id
+            ),
+        )
+        .unwrap();
+        let estimate = |point: f64| {
+            format!(
+                r#"{{"confidence_interval":{{"confidence_level":0.95,"lower_bound":{p},"upper_bound":{p}}},"point_estimate":{p},"standard_error":0.0}}"#,
+                p = point
+            )
+        };
+        fs::write(
+            new_dir.join("estimates.json"),
+            format!(
+                r#"{{"mean":{},"median":{},"median_abs_dev":{},"slope":null,"std_dev":{}}}"#,
+                estimate(mean),
+                estimate(mean / 2.0),
+                estimate(0.0),
+                estimate(0.0)
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn collects_only_the_requested_group() {
+        let home = TempDir::new().unwrap();
+        fake_result(home.path(), "jmt_get/get_blake3_random/100", "jmt_get", "get/blake3/random", "100", 900.0, "null");
+        fake_result(home.path(), "jmt_get/get_sha256_random/10", "jmt_get", "get/sha256/random", "10", 100.0, r#"{"Elements":10}"#);
+        fake_result(home.path(), "jmt_update/update_sha256/10", "jmt_update", "update/sha256", "10", 5.0, "null");
+
+        let stats = BTreeMap::from([(10, TreeStats { height: 2, internal_nodes: 4, leaf_nodes: 10 })]);
+        let results = collect_group(home.path(), "jmt_get", &stats).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].id, "jmt_get/get/blake3/random/100");
+        assert_eq!(results[0].hasher.as_deref(), Some("blake3"));
+        assert_eq!(results[0].stats, None);
+        assert_eq!(results[1].size, Some(10));
+        assert_eq!((results[1].mean_ns, results[1].median_ns), (100.0, 50.0));
+        assert_eq!((results[1].elements_per_sec, results[1].bytes_per_sec), (Some(1e8), None));
+        assert_eq!(results[0].elements_per_sec, None);
+        assert_eq!(results[1].stats, stats.get(&10).copied());
+
+        let out = TempDir::new().unwrap();
+        let path = write_group(out.path(), "jmt_get", &results).unwrap();
+        assert_eq!(path, out.path().join("jmt_get.json"));
+        let written: serde_json::Value = serde_json::from_slice(&fs::read(path).unwrap()).unwrap();
+        assert_eq!(written[1]["stats"]["leaf_nodes"], 10);
+    }
+}
@@ -0,0 +1,100 @@
+//! Pins the root hash of a fixed key set for every tree hasher. Keys, values and the tree
+//! shape are all deterministic, so a changed root means a store, codec or workload change
+//! altered what gets committed. Add a row here when a new hasher is wired in.
+
+use crate::store::{InMemoryTreeStore, TreeWriter};
+use crate::workload;
+use jmt::{JellyfishMerkleTree, KeyHash, SimpleHasher};
+use sha2::Sha256;
+
+const SIZES: [usize; 3] = [1, 10, 1000];
+const VALUE_LEN: usize = 32;
+
+// Commits `size` pairs in one version and reads the root back out of the store
+fn root_hex<H: SimpleHasher>(size: usize) -> String {
+    let store = InMemoryTreeStore::new();
+    let tree: JellyfishMerkleTree<_, H> = JellyfishMerkleTree::new(&store);
+    let value_set = workload::pairs(size, VALUE_LEN)
+        .into_iter()
+        .map(|(key, value)| (KeyHash::with::<Sha256>(key), Some(value)));
+    let (root, batch) = tree.put_value_set(value_set, 0).unwrap();
+    store.write_node_batch(&batch.node_batch).unwrap();
+    assert_eq!(tree.get_root_hash(0).unwrap(), root);
+    hex(&root.0)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn assert_roots<H: SimpleHasher>(expected: [&str; 3]) {
+    for (size, expected) in SIZES.into_iter().zip(expected) {
+        assert_eq!(root_hex::<H>(size), expected, "root of {} keys changed", size);
+    }
+}
+
+#[test]
+fn sha256_roots_are_stable() {
+    assert_roots::<Sha256>([
+        "ef06a3c6365453b83d4ec4311ca0421725ead34b089f5c2a03837d23d706ce27",
+        "4c5d727a03a3ec7120af0fbd18ac7fe60fa38edf6b0b56498e973939b78e86cc",
+        "d22ed5d5e07746747a9c5c927c816e861cedde3cab6f92f3f07969eaf0371f94",
+    ]);
+}
+
+#[test]
+fn blake2s_roots_are_stable() {
+    assert_roots::<blake2::Blake2s256>([
+        "b14aa26bedc0c5938e6afaefa7b230b7aed639d1beb18cef9468ad9c406db26b",
+        "0979b8e6f143eca2f1ac5f40a876b4b4639153283fff7f74df8b1372a6c4ec59",
+        "92a390485f02c3fde2a2f5570f1df848cbad0969509384435244552d8960cdf1",
+    ]);
+}
+
+#[test]
+fn blake3_roots_are_stable() {
+    assert_roots::<crate::hash::Blake3>([
+        "436339350be54b96ac3c477d6a0992bfc0ca415da91610d2e23073c1c1f51a8f",
+        "588cbef53f14f6df07de01b53def5969cd95481bf4cbe4fa8f29a60bd97b4e90",
+        "574626ab870acef2538ed29ea90e339f1b3c013b45a5dc74b9a10394497cda50",
+    ]);
+}
+
+#[cfg(feature = "keccak")]
+#[test]
+fn keccak256_roots_are_stable() {
+    assert_roots::<crate::hash::Keccak256>([
+        "d1cdd1aa5b3f151b02f92bf398b9e7f84a3fd4d87cd70900969abbc78fb95992",
+        "654315f5dd18a1607b7f499d30553807ff2f754800237a2c978ded019b515092",
+        "45751dd3975fde84ee37118447f728310820dc438cb32da13cd76b51884384de",
+    ]);
+}
+
+// jmt sorts each batch by key hash itself, so the order keys arrive in changes nothing
+#[test]
+fn commit_order_changes_neither_root_nor_nodes() {
+    let commit = |value_set: Vec<_>| {
+        let store = InMemoryTreeStore::new();
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&store);
+        tree.put_value_set(value_set, 0).unwrap()
+    };
+    let mut value_set: Vec<_> = workload::value_set_iter(1000, VALUE_LEN).collect();
+    let (root, batch) = commit(value_set.clone());
+    value_set.reverse();
+    let (reversed_root, reversed_batch) = commit(value_set);
+
+    assert_eq!(reversed_root, root);
+    assert_eq!(reversed_batch.node_batch.nodes(), batch.node_batch.nodes());
+}
+
+// Version 1 rewrites ten keys with the values they already had, so its root matches version
+// 0's; the fingerprint also covers the versions nodes were written at, and tells them apart
+#[test]
+fn sha256_fingerprint_is_stable() {
+    let store = InMemoryTreeStore::new();
+    workload::commit_streamed::<_, Sha256>(&store, workload::value_set_iter(1000, VALUE_LEN), 0).unwrap();
+    workload::commit_streamed::<_, Sha256>(&store, workload::value_set_iter(10, VALUE_LEN), 1).unwrap();
+    let fingerprint = crate::fingerprint::fingerprint::<Sha256>(&store, 1).unwrap();
+    assert_eq!(hex(&fingerprint), "5fbd1716b537e0c030402130b95b66074356c76d85e04d7df53105d85aa101be");
+    assert_ne!(crate::fingerprint::fingerprint::<Sha256>(&store, 0).unwrap(), fingerprint);
+}
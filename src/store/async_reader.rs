@@ -0,0 +1,134 @@
+use jmt::proof::SparseMerkleProof;
+use jmt::storage::{LeafNode, Node, NodeKey, TreeReader};
+use jmt::{JellyfishMerkleTree, KeyHash, OwnedValue, SimpleHasher, Version};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::runtime::Handle;
+
+use super::{InMemoryTreeStore, NodeCodec};
+
+/// `TreeReader` for stores whose reads are async, such as network or io_uring backends.
+pub trait AsyncTreeReader {
+    fn get_node_option(&self, node_key: &NodeKey) -> impl Future<Output = Result<Option<Node>, anyhow::Error>> + Send;
+
+    fn get_value_option(
+        &self,
+        max_version: Version,
+        key_hash: KeyHash,
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, anyhow::Error>> + Send;
+
+    fn get_rightmost_leaf(&self) -> impl Future<Output = Result<Option<(NodeKey, LeafNode)>, anyhow::Error>> + Send;
+}
+
+// Reads are served from memory, so each future is ready on its first poll
+impl<C: NodeCodec + Send + Sync> AsyncTreeReader for InMemoryTreeStore<C> {
+    async fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node>, anyhow::Error> {
+        TreeReader::get_node_option(self, node_key)
+    }
+
+    async fn get_value_option(
+        &self,
+        max_version: Version,
+        key_hash: KeyHash,
+    ) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        TreeReader::get_value_option(self, max_version, key_hash)
+    }
+
+    async fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode)>, anyhow::Error> {
+        TreeReader::get_rightmost_leaf(self)
+    }
+}
+
+/// Presents an [`AsyncTreeReader`] as a `TreeReader` by blocking on each read, for jmt's
+/// synchronous tree walks. It must be used from a blocking thread, such as the ones
+/// `tokio::task::spawn_blocking` runs on, never from inside an async task.
+pub struct BlockingReader<A> {
+    inner: Arc<A>,
+    handle: Handle,
+}
+
+impl<A: AsyncTreeReader> BlockingReader<A> {
+    /// Bridges `inner`, driving its reads on the runtime behind `handle`.
+    pub fn new(inner: Arc<A>, handle: Handle) -> Self {
+        Self { inner, handle }
+    }
+}
+
+impl<A: AsyncTreeReader> TreeReader for BlockingReader<A> {
+    fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node>, anyhow::Error> {
+        self.handle.block_on(self.inner.get_node_option(node_key))
+    }
+
+    fn get_value_option(
+        &self,
+        max_version: Version,
+        key_hash: KeyHash,
+    ) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        self.handle.block_on(self.inner.get_value_option(max_version, key_hash))
+    }
+
+    fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode)>, anyhow::Error> {
+        self.handle.block_on(self.inner.get_rightmost_leaf())
+    }
+}
+
+/// `get_with_proof` over an async store, callable from async code. The tree walk runs on
+/// Tokio's blocking pool through a [`BlockingReader`], so it never stalls the async workers.
+///
+/// Panics if called outside a Tokio runtime.
+#[allow(clippy::type_complexity)]
+pub async fn get_with_proof_async<A, H>(
+    reader: Arc<A>,
+    key_hash: KeyHash,
+    version: Version,
+) -> Result<(Option<OwnedValue>, SparseMerkleProof<H>), anyhow::Error>
+where
+    A: AsyncTreeReader + Send + Sync + 'static,
+    H: SimpleHasher + Send + 'static,
+{
+    let handle = Handle::current();
+    tokio::task::spawn_blocking(move || {
+        let reader = BlockingReader::new(reader, handle);
+        let tree: JellyfishMerkleTree<'_, _, H> = JellyfishMerkleTree::new(&reader);
+        tree.get_with_proof(key_hash, version)
+    })
+    .await?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::TreeWriter;
+    use sha2::Sha256;
+
+    #[test]
+    fn async_proofs_match_sync_proofs() {
+        let store = InMemoryTreeStore::new();
+        let key_hashes: Vec<KeyHash> = (0..50).map(|i| KeyHash::with::<Sha256>(i.to_string())).collect();
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&store);
+        let (root, batch) = tree
+            .put_value_set(key_hashes.iter().map(|key_hash| (*key_hash, Some(key_hash.0.to_vec()))), 0)
+            .unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+        let expected: Vec<_> = key_hashes.iter().map(|key_hash| tree.get_with_proof(*key_hash, 0).unwrap()).collect();
+
+        let store = Arc::new(store);
+        let runtime = tokio::runtime::Builder::new_multi_thread().worker_threads(2).build().unwrap();
+        let proofs = runtime.block_on(async {
+            let tasks: Vec<_> = key_hashes
+                .iter()
+                .map(|key_hash| tokio::spawn(get_with_proof_async::<_, Sha256>(store.clone(), *key_hash, 0)))
+                .collect();
+            let mut proofs = Vec::new();
+            for task in tasks {
+                proofs.push(task.await.unwrap().unwrap());
+            }
+            proofs
+        });
+
+        assert_eq!(proofs, expected);
+        for (key_hash, (value, proof)) in key_hashes.iter().zip(&proofs) {
+            proof.verify(root, *key_hash, value.as_ref()).unwrap();
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use jmt::proof::SparseMerkleProof;
+use jmt::storage::{LeafNode, Node, NodeKey, TreeReader};
+use jmt::{JellyfishMerkleTree, KeyHash, OwnedValue, SimpleHasher, Version};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// Proves every key in `key_hashes` at `version` in one pass, returning results in input
+/// order. jmt has no native multi-get, so the ancestors the keys share are read from `reader`
+/// once and then served from a node cache that lives only for this call.
+#[allow(clippy::type_complexity)]
+pub fn get_many_with_proof<R: TreeReader, H: SimpleHasher>(
+    reader: &R,
+    key_hashes: &[KeyHash],
+    version: Version,
+) -> Result<Vec<(Option<OwnedValue>, SparseMerkleProof<H>)>, anyhow::Error> {
+    let batch = BatchReader {
+        inner: reader,
+        nodes: RefCell::new(HashMap::new()),
+    };
+    let tree: JellyfishMerkleTree<'_, _, H> = JellyfishMerkleTree::new(&batch);
+    key_hashes
+        .iter()
+        .map(|key_hash| tree.get_with_proof(*key_hash, version))
+        .collect()
+}
+
+// Unbounded and single-threaded, since it is dropped as soon as the batch is proven
+struct BatchReader<'a, R> {
+    inner: &'a R,
+    nodes: RefCell<HashMap<NodeKey, Node>>,
+}
+
+impl<R: TreeReader> TreeReader for BatchReader<'_, R> {
+    fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node>, anyhow::Error> {
+        if let Some(node) = self.nodes.borrow().get(node_key) {
+            return Ok(Some(node.clone()));
+        }
+        let node = self.inner.get_node_option(node_key)?;
+        if let Some(node) = &node {
+            self.nodes.borrow_mut().insert(node_key.clone(), node.clone());
+        }
+        Ok(node)
+    }
+
+    fn get_value_option(
+        &self,
+        max_version: Version,
+        key_hash: KeyHash,
+    ) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        self.inner.get_value_option(max_version, key_hash)
+    }
+
+    fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode)>, anyhow::Error> {
+        self.inner.get_rightmost_leaf()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{CachingReader, InMemoryTreeStore, InstrumentedReader, TreeWriter};
+    use crate::workload;
+    use sha2::Sha256;
+    use std::num::NonZeroUsize;
+
+    #[test]
+    fn batch_matches_individual_proofs_with_fewer_node_reads() {
+        let store = InMemoryTreeStore::new();
+        let key_hashes: Vec<KeyHash> = (0..100).map(|i| KeyHash::with::<Sha256>(workload::key(i))).collect();
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&store);
+        let (root, batch) = tree
+            .put_value_set(key_hashes.iter().map(|key_hash| (*key_hash, Some(b"value".to_vec()))), 0)
+            .unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+        // Unknown keys are proven absent, like any other get_with_proof
+        let mut requested = key_hashes.clone();
+        requested.push(KeyHash::with::<Sha256>("missing"));
+
+        // A capacity-one cache never hits across keys, so its misses count the store reads
+        let counting = CachingReader::new(store, NonZeroUsize::new(1).unwrap());
+        let proofs = get_many_with_proof::<_, Sha256>(&counting, &requested, 0).unwrap();
+        let batch_reads = counting.misses();
+
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&counting);
+        assert_eq!(proofs.len(), requested.len());
+        for (key_hash, (value, proof)) in requested.iter().zip(&proofs) {
+            proof.verify(root, *key_hash, value.as_ref()).unwrap();
+            assert_eq!(tree.get_with_proof(*key_hash, 0).unwrap(), (value.clone(), proof.clone()));
+        }
+        assert_eq!(proofs.last().unwrap().0, None);
+        assert!(batch_reads < counting.misses() - batch_reads);
+    }
+
+    // Sibling hashes are recomputed from each internal node's children on every call, so a
+    // repeat proof costs as many reads as the first and a node cache is all a reader can add
+    #[test]
+    fn jmt_memoizes_nothing_across_proofs() {
+        let store = InstrumentedReader::new(InMemoryTreeStore::new());
+        workload::commit_streamed::<_, Sha256>(&store, workload::value_set_iter(100, 8), 0).unwrap();
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&store);
+        let key_hash = KeyHash::with::<Sha256>(workload::key(0));
+
+        store.reset();
+        let first = tree.get_with_proof(key_hash, 0).unwrap();
+        let first_reads = store.counts();
+        store.reset();
+        let second = tree.get_with_proof(key_hash, 0).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(store.counts(), first_reads);
+    }
+}
@@ -0,0 +1,191 @@
+use jmt::storage::{LeafNode, Node, NodeKey, StaleNodeIndex, TreeReader, TreeUpdateBatch};
+use jmt::{KeyHash, Version};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use super::{InMemoryTreeStore, StoreError, WarmUp};
+
+/// An [`InMemoryTreeStore`] that keeps only the newest `window` versions readable, the way a
+/// node that holds recent state in memory would. Each [`commit`](Self::commit) queues the
+/// nodes the batch made stale and the values it wrote, then prunes the nodes that no retained
+/// version still reaches and the values that a newer one at or below the window hides, so the
+/// store grows with the live state rather than the version count. Reading an evicted version's
+/// nodes or values from the store fails with [`StoreError::NotFound`]; jmt reports any failed
+/// root read as its own `MissingRootError`, so tree reads of an evicted version fail with that
+/// instead.
+pub struct BoundedTreeStore {
+    inner: InMemoryTreeStore,
+    window: Version,
+    state: Mutex<Window>,
+}
+
+struct Window {
+    // `None` until the first commit
+    newest: Option<Version>,
+    // Stale nodes waiting for every version that can reach them to be evicted, oldest first
+    pending: VecDeque<StaleNodeIndex>,
+    // Values written, oldest first, waiting for the window to move past their version
+    written: VecDeque<(Version, KeyHash)>,
+}
+
+impl Window {
+    fn oldest_retained(&self, window: Version) -> Version {
+        self.newest.map_or(0, |newest| (newest + 1).saturating_sub(window))
+    }
+}
+
+impl BoundedTreeStore {
+    /// An empty store retaining the newest `window` versions. A window of 0 is treated as 1,
+    /// since the next commit always reads the newest version.
+    pub fn new(window: Version) -> Self {
+        Self {
+            inner: InMemoryTreeStore::new(),
+            window: window.max(1),
+            state: Mutex::new(Window { newest: None, pending: VecDeque::new(), written: VecDeque::new() }),
+        }
+    }
+
+    /// The wrapped store, which holds only what eviction has left.
+    pub fn inner(&self) -> &InMemoryTreeStore {
+        &self.inner
+    }
+
+    /// The oldest version that can still be read.
+    pub fn oldest_retained(&self) -> Result<Version, StoreError> {
+        Ok(self.state.lock()?.oldest_retained(self.window))
+    }
+
+    /// Writes a commit's nodes and values, then evicts every node that only versions older than
+    /// the window can reach, and returns how many nodes were evicted. Each key written at or
+    /// below the oldest retained version keeps only the value visible there, and no value at
+    /// all if that is a deletion. Commits must arrive in version order, so the stale nodes and
+    /// values they report queue up oldest first.
+    pub fn commit(&self, batch: &TreeUpdateBatch) -> Result<usize, StoreError> {
+        self.inner.write(&batch.node_batch)?;
+
+        let mut state = self.state.lock()?;
+        if let Some(version) = batch.node_batch.nodes().keys().map(|node_key| node_key.version()).max() {
+            state.newest = Some(state.newest.map_or(version, |newest| newest.max(version)));
+        }
+        state.pending.extend(batch.stale_node_index_batch.iter().cloned());
+        state.written.extend(batch.node_batch.values().keys().copied());
+
+        // A node stale since `v` is reachable only from versions below `v`
+        let oldest = state.oldest_retained(self.window);
+        let evictable = state.pending.iter().take_while(|index| index.stale_since_version <= oldest).count();
+        let evicted: Vec<_> = state.pending.drain(..evictable).collect();
+        let evicted = self.inner.prune(&evicted)?;
+
+        // Once the window reaches a write, the next read of its key can only see it or
+        // something newer, so anything older goes
+        let settled = state.written.iter().take_while(|(version, _key_hash)| *version <= oldest).count();
+        for (_version, key_hash) in state.written.drain(..settled) {
+            self.inner.prune_values(key_hash, oldest)?;
+        }
+        Ok(evicted)
+    }
+
+    fn check_retained(&self, version: Version) -> Result<(), StoreError> {
+        let oldest = self.oldest_retained()?;
+        if version < oldest {
+            return Err(StoreError::NotFound(format!(
+                "version {} was evicted; versions from {} on are retained",
+                version, oldest
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl TreeReader for BoundedTreeStore {
+    /// Nodes from old versions that the newest tree still shares are served as usual; only a
+    /// missing node below the window counts as evicted.
+    fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node>, anyhow::Error> {
+        match self.inner.get_node_option(node_key)? {
+            Some(node) => Ok(Some(node)),
+            None => {
+                self.check_retained(node_key.version())?;
+                Ok(None)
+            }
+        }
+    }
+
+    fn get_value_option(
+        &self,
+        max_version: Version,
+        key_hash: KeyHash,
+    ) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        self.check_retained(max_version)?;
+        self.inner.get_value_option(max_version, key_hash)
+    }
+
+    fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode)>, anyhow::Error> {
+        self.inner.get_rightmost_leaf()
+    }
+}
+
+impl WarmUp for BoundedTreeStore {
+    fn warm_up(&self) -> Result<(), anyhow::Error> {
+        self.inner.warm_up()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workload;
+    use jmt::JellyfishMerkleTree;
+    use sha2::Sha256;
+
+    #[test]
+    fn evicted_versions_are_not_found_and_retained_ones_read() {
+        let store = BoundedTreeStore::new(2);
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&store);
+        let key_hash = KeyHash::with::<Sha256>(workload::key(0));
+        let mut evicted = 0;
+        for version in 0..4 {
+            let value_set = workload::value_set_iter(50, 8)
+                .map(|(key_hash, _value)| (key_hash, Some(workload::value(version, 8))));
+            let (_root, batch) = tree.put_value_set(value_set, version as Version).unwrap();
+            evicted += store.commit(&batch).unwrap();
+        }
+        assert!(evicted > 0);
+        assert_eq!(store.oldest_retained().unwrap(), 2);
+
+        for version in [2, 3] {
+            let (value, proof) = tree.get_with_proof(key_hash, version).unwrap();
+            assert_eq!(value, Some(workload::value(version as usize, 8)));
+            proof.verify(tree.get_root_hash(version).unwrap(), key_hash, value).unwrap();
+        }
+        for version in [0, 1] {
+            let not_found = |error: anyhow::Error| matches!(error.downcast_ref::<StoreError>(), Some(StoreError::NotFound(_)));
+            assert!(not_found(store.get_node_option(&NodeKey::new(version, std::iter::empty().collect())).unwrap_err()));
+            assert!(not_found(store.get_value_option(version, key_hash).unwrap_err()));
+            let error = tree.get_with_proof(key_hash, version).unwrap_err();
+            assert!(error.downcast_ref::<jmt::MissingRootError>().is_some(), "{}", error);
+        }
+    }
+
+    #[test]
+    fn size_stays_flat_once_past_the_window() {
+        let store = BoundedTreeStore::new(3);
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&store);
+        let mut sizes = Vec::new();
+        for version in 0..20 {
+            // Every version rewrites the same keys and deletes one of them
+            let value_set = workload::value_set_iter(50, 8)
+                .map(|(key_hash, _value)| (key_hash, Some(workload::value(version, 8))))
+                .chain(std::iter::once((KeyHash::with::<Sha256>(workload::key(1000 + version)), None)));
+            let (_root, batch) = tree.put_value_set(value_set, version as Version).unwrap();
+            store.commit(&batch).unwrap();
+            sizes.push(store.inner().estimated_size_bytes().unwrap());
+        }
+        // By the window's second turn, every commit adds as much as it evicts
+        assert!(sizes[6..].iter().all(|size| *size == sizes[6]), "{:?}", sizes);
+
+        let key_hash = KeyHash::with::<Sha256>(workload::key(0));
+        let (value, proof) = tree.get_with_proof(key_hash, 17).unwrap();
+        assert_eq!(value, Some(workload::value(17, 8)));
+        proof.verify(tree.get_root_hash(17).unwrap(), key_hash, value).unwrap();
+    }
+}
@@ -0,0 +1,89 @@
+use jmt::storage::{LeafNode, Node, NodeBatch, NodeKey, TreeReader, TreeWriter};
+use jmt::{KeyHash, Version};
+
+use super::WarmUp;
+
+/// Everything a benchmark needs from a store, in one object-safe trait, so a store chosen at
+/// runtime can sit behind a [`BoxedStore`]. Every store that implements the parts gets it.
+pub trait TreeStore: TreeReader + TreeWriter + WarmUp + Send + Sync {}
+
+impl<S: TreeReader + TreeWriter + WarmUp + Send + Sync> TreeStore for S {}
+
+/// Any [`TreeStore`] with its concrete type erased, for picking a backend at runtime. Every
+/// call costs one virtual dispatch on top of the store's own work.
+pub struct BoxedStore {
+    inner: Box<dyn TreeStore>,
+}
+
+impl BoxedStore {
+    pub fn new(store: impl TreeStore + 'static) -> Self {
+        Self { inner: Box::new(store) }
+    }
+}
+
+impl TreeReader for BoxedStore {
+    fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node>, anyhow::Error> {
+        self.inner.get_node_option(node_key)
+    }
+
+    fn get_value_option(
+        &self,
+        max_version: Version,
+        key_hash: KeyHash,
+    ) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        self.inner.get_value_option(max_version, key_hash)
+    }
+
+    fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode)>, anyhow::Error> {
+        self.inner.get_rightmost_leaf()
+    }
+}
+
+impl TreeWriter for BoxedStore {
+    fn write_node_batch(&self, node_batch: &NodeBatch) -> Result<(), anyhow::Error> {
+        self.inner.write_node_batch(node_batch)
+    }
+}
+
+impl WarmUp for BoxedStore {
+    fn warm_up(&self) -> Result<(), anyhow::Error> {
+        self.inner.warm_up()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{BTreeMapTreeStore, InMemoryTreeStore};
+    use crate::workload;
+    use jmt::JellyfishMerkleTree;
+    use sha2::Sha256;
+
+    // Commits through the boxed store and proves a key back out of it
+    fn round_trip(store: BoxedStore) {
+        let root = workload::commit_streamed::<_, Sha256>(&store, workload::value_set_iter(100, 8), 0).unwrap();
+        store.warm_up().unwrap();
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&store);
+        let key_hash = KeyHash::with::<Sha256>(workload::key(7));
+        let (value, proof) = tree.get_with_proof(key_hash, 0).unwrap();
+        assert_eq!(value, Some(workload::value(7, 8)));
+        proof.verify(root, key_hash, value).unwrap();
+        assert!(store.get_rightmost_leaf().unwrap().is_some());
+    }
+
+    #[test]
+    fn every_backend_works_through_the_box() {
+        round_trip(BoxedStore::new(InMemoryTreeStore::new()));
+        round_trip(BoxedStore::new(BTreeMapTreeStore::new()));
+        #[cfg(feature = "sled")]
+        {
+            let dir = tempfile::TempDir::new().unwrap();
+            round_trip(BoxedStore::new(crate::store::SledTreeStore::open(dir.path()).unwrap()));
+        }
+        #[cfg(feature = "rocksdb")]
+        {
+            let dir = tempfile::TempDir::new().unwrap();
+            round_trip(BoxedStore::new(crate::store::RocksDbTreeStore::open(dir.path()).unwrap()));
+        }
+    }
+}
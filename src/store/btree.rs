@@ -0,0 +1,188 @@
+use jmt::storage::{LeafNode, NibblePath, Node, NodeBatch, NodeKey, TreeReader, TreeWriter};
+use jmt::{KeyHash, Version};
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+use super::{is_further_right, ordered_value_storage_key, WarmUp, NODE_PREFIX, VALUE_PREFIX};
+
+// Nodes are keyed by nibble path, one byte per nibble offset by one, then a zero terminator and
+// the big-endian version. Byte order over these keys is nibble order over paths, with a path
+// sorting before every path it prefixes.
+fn ordered_node_storage_key(node_key: &NodeKey) -> Vec<u8> {
+    let nibble_path = node_key.nibble_path();
+    let mut key = Vec::with_capacity(1 + nibble_path.num_nibbles() + 1 + 8);
+    key.push(NODE_PREFIX);
+    key.extend(nibble_path.nibbles().map(|nibble| u8::from(nibble) + 1));
+    key.push(0);
+    key.extend_from_slice(&node_key.version().to_be_bytes());
+    key
+}
+
+fn decode_ordered_node_key(key: &[u8]) -> Result<NodeKey, anyhow::Error> {
+    let (nibbles, version) = match key {
+        [NODE_PREFIX, rest @ ..] if rest.len() >= 9 => rest.split_at(rest.len() - 8),
+        _ => anyhow::bail!("Malformed node key: {:?}", key),
+    };
+    let nibbles = match nibbles.split_last() {
+        Some((0, nibbles)) => nibbles,
+        _ => anyhow::bail!("Malformed node key: {:?}", key),
+    };
+    let nibble_path: NibblePath = nibbles.iter().map(|nibble| (nibble - 1).into()).collect();
+    let version = Version::from_be_bytes(version.try_into()?);
+    Ok(NodeKey::new(version, nibble_path))
+}
+
+/// A `BTreeMap`-backed JMT store. Keeping nodes in nibble order lets `get_rightmost_leaf`
+/// seek to the end of the node range in O(log n) rather than scanning every node.
+#[derive(Default)]
+pub struct BTreeMapTreeStore {
+    store: Arc<RwLock<BTreeMap<Vec<u8>, Vec<u8>>>>,
+}
+
+impl BTreeMapTreeStore {
+    pub fn new() -> Self {
+        Self {
+            store: Arc::new(RwLock::new(BTreeMap::new())),
+        }
+    }
+}
+
+impl TreeReader for BTreeMapTreeStore {
+    fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node>, anyhow::Error> {
+        let store = self
+            .store
+            .read()
+            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        match store.get(&ordered_node_storage_key(node_key)) {
+            Some(bytes) => Ok(Some(bincode::deserialize(bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn get_value_option(
+        &self,
+        max_version: Version,
+        key_hash: KeyHash,
+    ) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        let store = self
+            .store
+            .read()
+            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        let lowest = ordered_value_storage_key(0, key_hash);
+        let highest = ordered_value_storage_key(max_version, key_hash);
+        match store.range(lowest..=highest).next_back() {
+            Some((_key, bytes)) => Ok(bincode::deserialize(bytes)?),
+            None => Ok(None),
+        }
+    }
+
+    fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode)>, anyhow::Error> {
+        let store = self
+            .store
+            .read()
+            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+
+        // An internal node sorts before its children, so walking back from the end of the node
+        // range, the first leaf has the rightmost path. Leaves from other versions may share
+        // that path, so keep going until the path changes.
+        let mut rightmost: Option<(NodeKey, LeafNode)> = None;
+        for (key_bytes, node_bytes) in store.range(vec![NODE_PREFIX]..vec![VALUE_PREFIX]).rev() {
+            let node_key = decode_ordered_node_key(key_bytes)?;
+            if let Some((best_key, _)) = &rightmost {
+                if best_key.nibble_path() != node_key.nibble_path() {
+                    break;
+                }
+            }
+            let leaf = match bincode::deserialize(node_bytes)? {
+                Node::Leaf(leaf) => leaf,
+                _ => continue,
+            };
+            if is_further_right(&node_key, &leaf, rightmost.as_ref()) {
+                rightmost = Some((node_key, leaf));
+            }
+        }
+
+        Ok(rightmost)
+    }
+}
+
+impl WarmUp for BTreeMapTreeStore {}
+
+impl TreeWriter for BTreeMapTreeStore {
+    fn write_node_batch(&self, node_batch: &NodeBatch) -> Result<(), anyhow::Error> {
+        let mut store = self
+            .store
+            .write()
+            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+
+        for (node_key, node) in node_batch.nodes() {
+            store.insert(
+                ordered_node_storage_key(node_key),
+                bincode::serialize(node)?,
+            );
+        }
+        for ((version, key_hash), value_option) in node_batch.values() {
+            store.insert(
+                ordered_value_storage_key(*version, *key_hash),
+                bincode::serialize(value_option)?,
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::InMemoryTreeStore;
+    use jmt::JellyfishMerkleTree;
+    use sha2::Sha256;
+
+    #[test]
+    fn node_keys_round_trip_through_ordered_encoding() {
+        for (version, nibbles) in [(0, vec![]), (42, vec![3u8, 15, 0, 7, 1])] {
+            let nibble_path: NibblePath = nibbles.into_iter().map(|nibble| nibble.into()).collect();
+            let node_key = NodeKey::new(version, nibble_path);
+            let encoded = ordered_node_storage_key(&node_key);
+            assert_eq!(decode_ordered_node_key(&encoded).unwrap(), node_key);
+        }
+    }
+
+    #[test]
+    fn rightmost_leaf_matches_full_scan_across_versions() {
+        let btree = BTreeMapTreeStore::new();
+        let memory = InMemoryTreeStore::new();
+        assert!(btree.get_rightmost_leaf().unwrap().is_none());
+
+        for version in 0..5u64 {
+            let value_set: Vec<_> = (0..10 * (version + 1))
+                .map(|i| {
+                    (
+                        KeyHash::with::<Sha256>(format!("key{}", i)),
+                        Some(version.to_be_bytes().to_vec()),
+                    )
+                })
+                .collect();
+            let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&btree);
+            let (_root, batch) = tree.put_value_set(value_set, version).unwrap();
+            btree.write_node_batch(&batch.node_batch).unwrap();
+            memory.write_node_batch(&batch.node_batch).unwrap();
+
+            assert_eq!(
+                btree.get_rightmost_leaf().unwrap(),
+                memory.get_rightmost_leaf().unwrap()
+            );
+        }
+
+        let key_hash = KeyHash::with::<Sha256>("key0");
+        assert_eq!(
+            btree.get_value_option(2, key_hash).unwrap(),
+            Some(2u64.to_be_bytes().to_vec())
+        );
+        assert_eq!(
+            btree.get_value_option(9, key_hash).unwrap(),
+            Some(4u64.to_be_bytes().to_vec())
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use jmt::storage::{LeafNode, Node, NodeBatch, NodeKey, TreeReader, TreeWriter};
+use jmt::{KeyHash, Version};
+use std::sync::RwLock;
+
+use super::{is_further_right, WarmUp};
+
+/// Holds every written batch in memory until [`flush`](Self::flush) hands them to the inner
+/// store as one, the way a database separates a commit's write buffer from its durable flush.
+/// Reads see buffered writes before they are flushed. `write_node_batch` buffers too, so the
+/// tree and the workload helpers commit through it unchanged.
+pub struct BufferedTreeStore<R> {
+    inner: R,
+    pending: RwLock<NodeBatch>,
+}
+
+impl<R> BufferedTreeStore<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            pending: RwLock::new(NodeBatch::default()),
+        }
+    }
+
+    /// The wrapped store, which holds only what has been flushed.
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// Adds `node_batch` to the buffer without touching the inner store.
+    pub fn buffer_node_batch(&self, node_batch: &NodeBatch) -> Result<(), anyhow::Error> {
+        let mut pending = self.pending.write().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        pending.extend(node_batch.nodes().clone(), node_batch.values().clone());
+        Ok(())
+    }
+}
+
+impl<R: TreeWriter> BufferedTreeStore<R> {
+    /// Writes everything buffered since the last flush to the inner store in one batch. Reads
+    /// wait until it is done, and a failed write leaves the buffer as it was.
+    pub fn flush(&self) -> Result<(), anyhow::Error> {
+        let mut pending = self.pending.write().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        if pending.is_empty() {
+            return Ok(());
+        }
+        self.inner.write_node_batch(&pending)?;
+        pending.clear();
+        Ok(())
+    }
+}
+
+impl<R: TreeReader> TreeReader for BufferedTreeStore<R> {
+    fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node>, anyhow::Error> {
+        let pending = self.pending.read().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        match pending.get_node(node_key) {
+            Some(node) => Ok(Some(node.clone())),
+            None => self.inner.get_node_option(node_key),
+        }
+    }
+
+    /// A buffered value wins over a flushed one, which holds as long as versions are committed
+    /// in order. Finding it scans the buffered values at or below `max_version`, since the
+    /// batch sorts them by version first.
+    fn get_value_option(
+        &self,
+        max_version: Version,
+        key_hash: KeyHash,
+    ) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        let pending = self.pending.read().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        let buffered = pending
+            .values()
+            .range(..=(max_version, KeyHash([u8::MAX; 32])))
+            .rev()
+            .find(|((_version, buffered_key_hash), _value)| *buffered_key_hash == key_hash);
+        match buffered {
+            Some((_version_and_key, value_option)) => Ok(value_option.clone()),
+            None => self.inner.get_value_option(max_version, key_hash),
+        }
+    }
+
+    fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode)>, anyhow::Error> {
+        let pending = self.pending.read().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        let mut best = self.inner.get_rightmost_leaf()?;
+        for (node_key, node) in pending.nodes() {
+            if let Node::Leaf(leaf) = node {
+                if is_further_right(node_key, leaf, best.as_ref()) {
+                    best = Some((node_key.clone(), leaf.clone()));
+                }
+            }
+        }
+        Ok(best)
+    }
+}
+
+impl<R> TreeWriter for BufferedTreeStore<R> {
+    fn write_node_batch(&self, node_batch: &NodeBatch) -> Result<(), anyhow::Error> {
+        self.buffer_node_batch(node_batch)
+    }
+}
+
+impl<R: WarmUp> WarmUp for BufferedTreeStore<R> {
+    fn warm_up(&self) -> Result<(), anyhow::Error> {
+        self.inner.warm_up()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::InMemoryTreeStore;
+    use crate::workload;
+    use jmt::JellyfishMerkleTree;
+    use sha2::Sha256;
+
+    #[test]
+    fn reads_see_buffered_writes_before_the_flush() {
+        let store = BufferedTreeStore::new(InMemoryTreeStore::new());
+        let root = workload::commit_streamed::<_, Sha256>(&store, workload::value_set_iter(100, 8), 0).unwrap();
+        let key_hash = KeyHash::with::<Sha256>(workload::key(7));
+        workload::commit_streamed::<_, Sha256>(&store, vec![(key_hash, None)], 1).unwrap();
+        assert_eq!(store.inner().get_rightmost_leaf().unwrap(), None);
+
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&store);
+        let buffered_leaf = store.get_rightmost_leaf().unwrap();
+        let (value, proof) = tree.get_with_proof(key_hash, 0).unwrap();
+        assert_eq!(value, Some(workload::value(7, 8)));
+        proof.verify(root, key_hash, value).unwrap();
+        assert_eq!(tree.get(key_hash, 1).unwrap(), None);
+
+        store.flush().unwrap();
+        let flushed: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(store.inner());
+        assert_eq!(flushed.get_root_hash(0).unwrap(), root);
+        assert_eq!(flushed.get(key_hash, 0).unwrap(), Some(workload::value(7, 8)));
+        assert_eq!(flushed.get(key_hash, 1).unwrap(), None);
+        assert_eq!(store.inner().get_rightmost_leaf().unwrap(), buffered_leaf);
+        assert_eq!(store.get_rightmost_leaf().unwrap(), buffered_leaf);
+    }
+}
@@ -0,0 +1,209 @@
+use jmt::storage::{LeafNode, NibblePath, Node, NodeKey, TreeReader};
+use jmt::{KeyHash, Version};
+use lru::LruCache;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use super::{StoreError, WarmUp};
+
+/// A read-through cache of deserialized nodes in front of any `TreeReader`, the way production
+/// deployments put an LRU in front of disk. Nodes are immutable once written under a given
+/// `NodeKey`, so cached entries never go stale; values and rightmost-leaf lookups are passed
+/// straight through.
+pub struct CachingReader<R> {
+    inner: R,
+    cache: Mutex<LruCache<NodeKey, Node>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<R: TreeReader> CachingReader<R> {
+    /// Wraps `inner`, keeping up to `capacity` nodes.
+    pub fn new(inner: R, capacity: NonZeroUsize) -> Self {
+        Self {
+            inner,
+            cache: Mutex::new(LruCache::new(capacity)),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// The wrapped reader, for reads that should bypass the cache.
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// Node lookups answered from the cache so far.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Node lookups delegated to the inner reader so far.
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Warms the cache with the nodes on each of `key_hashes`' paths at `version`, the way a
+    /// read-ahead fetches a batch's pages before serving it. The tree is walked down from the
+    /// root by each key's nibbles, one level at a time across the whole batch, so a node several
+    /// keys pass through is read once. No values are read and no proofs built. A path ends
+    /// where its key's nibble has no child, so a later proof still reads the sibling leaves it
+    /// hashes, and for an absent key the leaf it proves absence with. A capacity below the
+    /// number of nodes read evicts the least recently used. These reads are counted in neither
+    /// [`hits`](Self::hits) nor [`misses`](Self::misses).
+    pub fn preload(&self, key_hashes: &[KeyHash], version: Version) -> Result<(), anyhow::Error> {
+        let mut key_hashes = key_hashes.to_vec();
+        key_hashes.sort_unstable();
+        key_hashes.dedup();
+        let mut level = vec![(NodeKey::new(version, std::iter::empty().collect()), &key_hashes[..])];
+        while !level.is_empty() {
+            let mut next = Vec::new();
+            for (node_key, key_hashes) in level {
+                let node = self
+                    .read_through(&node_key, false)?
+                    .ok_or_else(|| StoreError::NotFound(format!("node at version {}", node_key.version())))?;
+                if let Node::Internal(internal) = node {
+                    let depth = node_key.nibble_path().num_nibbles();
+                    // Sorted keys that share the next nibble sit next to each other
+                    for group in key_hashes.chunk_by(|a, b| nibble_at(a, depth) == nibble_at(b, depth)) {
+                        let wanted = nibble_at(&group[0], depth);
+                        if let Some((nibble, child)) = internal.children_sorted().find(|(nibble, _child)| u8::from(*nibble) == wanted) {
+                            let child_path: NibblePath = node_key.nibble_path().nibbles().chain(std::iter::once(nibble)).collect();
+                            next.push((NodeKey::new(child.version, child_path), group));
+                        }
+                    }
+                }
+            }
+            level = next;
+        }
+        Ok(())
+    }
+
+    // Serves `node_key` from the cache, or reads it from the inner reader and caches it;
+    // `count` says whether the lookup shows in the hit and miss counters
+    fn read_through(&self, node_key: &NodeKey, count: bool) -> Result<Option<Node>, anyhow::Error> {
+        {
+            let mut cache = self.cache.lock().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+            if let Some(node) = cache.get(node_key) {
+                if count {
+                    self.hits.fetch_add(1, Ordering::Relaxed);
+                }
+                return Ok(Some(node.clone()));
+            }
+        }
+
+        // The inner read happens without the lock held, so concurrent misses don't serialize
+        if count {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        let node = self.inner.get_node_option(node_key)?;
+        if let Some(node) = &node {
+            let mut cache = self.cache.lock().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+            cache.put(node_key.clone(), node.clone());
+        }
+        Ok(node)
+    }
+}
+
+// jmt keeps its `Nibble` type private, so nibbles are compared as bytes
+fn nibble_at(key_hash: &KeyHash, depth: usize) -> u8 {
+    let byte = key_hash.0[depth / 2];
+    if depth.is_multiple_of(2) {
+        byte >> 4
+    } else {
+        byte & 0x0f
+    }
+}
+
+impl<R: TreeReader> TreeReader for CachingReader<R> {
+    fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node>, anyhow::Error> {
+        self.read_through(node_key, true)
+    }
+
+    fn get_value_option(
+        &self,
+        max_version: Version,
+        key_hash: KeyHash,
+    ) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        self.inner.get_value_option(max_version, key_hash)
+    }
+
+    fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode)>, anyhow::Error> {
+        self.inner.get_rightmost_leaf()
+    }
+}
+
+impl<R: WarmUp> WarmUp for CachingReader<R> {
+    fn warm_up(&self) -> Result<(), anyhow::Error> {
+        self.inner.warm_up()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{InMemoryTreeStore, InstrumentedReader, TreeWriter};
+    use crate::workload;
+    use jmt::JellyfishMerkleTree;
+    use sha2::Sha256;
+
+    #[test]
+    fn repeated_proofs_are_served_from_cache() {
+        let store = InMemoryTreeStore::new();
+        let key_hash = KeyHash::with::<Sha256>("key");
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&store);
+        let (root, batch) = tree
+            .put_value_set(vec![(key_hash, Some(b"value".to_vec()))], 0)
+            .unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+
+        let cached = CachingReader::new(store, NonZeroUsize::new(16).unwrap());
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&cached);
+        let (value, proof) = tree.get_with_proof(key_hash, 0).unwrap();
+        proof.verify(root, key_hash, value.as_ref()).unwrap();
+        let misses = cached.misses();
+        assert!(misses > 0);
+        assert_eq!(cached.hits(), 0);
+
+        let (cached_value, cached_proof) = tree.get_with_proof(key_hash, 0).unwrap();
+        assert_eq!((cached_value, cached_proof), (value, proof));
+        assert_eq!(cached.misses(), misses);
+        assert_eq!(cached.hits(), misses);
+    }
+
+    #[test]
+    fn preload_reads_only_nodes_the_proofs_need() {
+        let store = InMemoryTreeStore::new();
+        let root = workload::commit_streamed::<_, Sha256>(&store, workload::value_set_iter(1000, 8), 0).unwrap();
+        let cached = CachingReader::new(InstrumentedReader::new(store), NonZeroUsize::new(1000).unwrap());
+        // Ten keys in the tree, with the first repeated, and one that is not
+        let mut key_hashes: Vec<KeyHash> = (0..1000).step_by(100).map(|i| KeyHash::with::<Sha256>(workload::key(i))).collect();
+        key_hashes.push(key_hashes[0]);
+        key_hashes.push(KeyHash::with::<Sha256>("missing"));
+
+        cached.preload(&key_hashes, 0).unwrap();
+        // Each node is read once, though every key passes through the root, and no value is read
+        let preloaded = cached.cache.lock().unwrap().len() as u64;
+        assert_eq!(cached.inner().counts().nodes, preloaded);
+        assert_eq!(cached.inner().counts().values, 0);
+        assert_eq!((cached.hits(), cached.misses()), (0, 0));
+
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&cached);
+        let direct: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(cached.inner().inner());
+        for key_hash in &key_hashes {
+            let (value, proof) = tree.get_with_proof(*key_hash, 0).unwrap();
+            proof.verify(root, *key_hash, value.as_ref()).unwrap();
+            assert_eq!((value, proof), direct.get_with_proof(*key_hash, 0).unwrap());
+        }
+
+        // Proving the same keys into an empty cache reads every preloaded node, plus the
+        // siblings the preloaded cache missed on
+        let cold = CachingReader::new(cached.inner().inner().clone_snapshot().unwrap(), NonZeroUsize::new(1000).unwrap());
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&cold);
+        for key_hash in &key_hashes {
+            tree.get_with_proof(*key_hash, 0).unwrap();
+        }
+        assert_eq!(cold.misses(), preloaded + cached.misses());
+    }
+}
@@ -0,0 +1,81 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::StoreError;
+
+/// How a store encodes the nodes and values it holds. Storage keys keep their fixed bincode
+/// layout whatever the codec, so only the stored bytes change.
+pub trait NodeCodec {
+    fn serialize<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, StoreError>;
+
+    fn deserialize<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, StoreError>;
+}
+
+/// bincode's default configuration: fixed-width little-endian integers.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Bincode;
+
+impl NodeCodec for Bincode {
+    fn serialize<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, StoreError> {
+        Ok(bincode::serialize(value)?)
+    }
+
+    fn deserialize<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, StoreError> {
+        Ok(bincode::deserialize(bytes)?)
+    }
+}
+
+/// postcard's varint encoding, which shrinks the lengths, versions and counts inside nodes.
+#[cfg(feature = "postcard")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Postcard;
+
+#[cfg(feature = "postcard")]
+impl NodeCodec for Postcard {
+    fn serialize<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, StoreError> {
+        Ok(postcard::to_allocvec(value)?)
+    }
+
+    fn deserialize<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, StoreError> {
+        Ok(postcard::from_bytes(bytes)?)
+    }
+}
+
+#[cfg(all(test, feature = "postcard"))]
+mod tests {
+    use super::*;
+    use crate::store::{InMemoryTreeStore, TreeReader, TreeWriter};
+    use crate::workload;
+    use jmt::storage::{Node, NodeKey};
+    use jmt::{JellyfishMerkleTree, KeyHash};
+    use sha2::Sha256;
+
+    #[test]
+    fn postcard_store_builds_the_same_tree_in_fewer_bytes() {
+        let bincode_store = InMemoryTreeStore::new();
+        let postcard_store = InMemoryTreeStore::<Postcard>::with_codec();
+        let value_set: Vec<_> = workload::pairs(100, 8)
+            .into_iter()
+            .map(|(key, value)| (KeyHash::with::<Sha256>(key), Some(value)))
+            .collect();
+
+        let bincode_jmt: JellyfishMerkleTree<'_, _, Sha256> = JellyfishMerkleTree::new(&bincode_store);
+        let postcard_jmt: JellyfishMerkleTree<'_, _, Sha256> = JellyfishMerkleTree::new(&postcard_store);
+        let (root, batch) = bincode_jmt.put_value_set(value_set.clone(), 0).unwrap();
+        bincode_store.write_node_batch(&batch.node_batch).unwrap();
+        let (postcard_root, batch) = postcard_jmt.put_value_set(value_set.clone(), 0).unwrap();
+        postcard_store.write_node_batch(&batch.node_batch).unwrap();
+        assert_eq!(postcard_root, root);
+
+        for (key_hash, value) in &value_set {
+            let (read, proof) = postcard_jmt.get_with_proof(*key_hash, 0).unwrap();
+            assert_eq!(&read, value);
+            proof.verify(root, *key_hash, read.as_ref()).unwrap();
+        }
+
+        let root_key = NodeKey::new(0, std::iter::empty().collect());
+        let root_node = postcard_store.get_node(&root_key).unwrap();
+        assert!(matches!(root_node, Node::Internal(_)));
+        assert!(Postcard::serialize(&root_node).unwrap().len() < Bincode::serialize(&root_node).unwrap().len());
+    }
+}
@@ -0,0 +1,99 @@
+use jmt::storage::{LeafNode, Node, NodeBatch, NodeKey, TreeReader, TreeWriter};
+use jmt::{KeyHash, Version};
+use std::collections::BTreeMap;
+
+use super::WarmUp;
+
+/// Compresses every value with zstd on its way into any store and decompresses it on the way
+/// out, leaving nodes untouched. jmt hashes values before they reach the store, so roots and
+/// proofs are the same as without compression. Nodes hold nothing but hashes, which do not
+/// compress.
+pub struct CompressingTreeStore<R> {
+    inner: R,
+    level: i32,
+}
+
+impl<R: TreeReader> CompressingTreeStore<R> {
+    /// Wraps `inner`, compressing at zstd `level`: 1 to 22, higher being smaller and slower, or
+    /// 0 for zstd's default of 3.
+    pub fn new(inner: R, level: i32) -> Self {
+        Self { inner, level }
+    }
+
+    /// The wrapped store, whose values read back compressed.
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+}
+
+impl<R: TreeReader> TreeReader for CompressingTreeStore<R> {
+    fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node>, anyhow::Error> {
+        self.inner.get_node_option(node_key)
+    }
+
+    fn get_value_option(
+        &self,
+        max_version: Version,
+        key_hash: KeyHash,
+    ) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        match self.inner.get_value_option(max_version, key_hash)? {
+            Some(compressed) => Ok(Some(zstd::decode_all(compressed.as_slice())?)),
+            None => Ok(None),
+        }
+    }
+
+    fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode)>, anyhow::Error> {
+        self.inner.get_rightmost_leaf()
+    }
+}
+
+impl<R: TreeWriter> TreeWriter for CompressingTreeStore<R> {
+    /// Rebuilds the batch with compressed values, which copies its nodes too: `NodeBatch` can
+    /// only be built whole. Deletions stay `None`.
+    fn write_node_batch(&self, node_batch: &NodeBatch) -> Result<(), anyhow::Error> {
+        let mut values = BTreeMap::new();
+        for (version_and_key, value_option) in node_batch.values() {
+            let compressed = match value_option {
+                Some(value) => Some(zstd::encode_all(value.as_slice(), self.level)?),
+                None => None,
+            };
+            values.insert(*version_and_key, compressed);
+        }
+        self.inner.write_node_batch(&NodeBatch::new(node_batch.nodes().clone(), values))
+    }
+}
+
+impl<R: WarmUp> WarmUp for CompressingTreeStore<R> {
+    fn warm_up(&self) -> Result<(), anyhow::Error> {
+        self.inner.warm_up()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::InMemoryTreeStore;
+    use crate::workload;
+    use jmt::JellyfishMerkleTree;
+    use sha2::Sha256;
+
+    #[test]
+    fn compressed_values_read_back_and_shrink_the_store() {
+        let value = b"balance=100;".repeat(100);
+        let value_set = |n| (0..n).map(|i| (KeyHash::with::<Sha256>(workload::key(i)), Some(value.clone()))).collect::<Vec<_>>();
+        let plain = InMemoryTreeStore::new();
+        let plain_root = workload::commit_streamed::<_, Sha256>(&plain, value_set(100), 0).unwrap();
+        let store = CompressingTreeStore::new(InMemoryTreeStore::new(), 0);
+        let root = workload::commit_streamed::<_, Sha256>(&store, value_set(100), 0).unwrap();
+        workload::commit_streamed::<_, Sha256>(&store, vec![(KeyHash::with::<Sha256>(workload::key(0)), None)], 1).unwrap();
+        assert_eq!(root, plain_root);
+
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&store);
+        let key_hash = KeyHash::with::<Sha256>(workload::key(0));
+        let (read, proof) = tree.get_with_proof(key_hash, 0).unwrap();
+        assert_eq!(read.as_ref(), Some(&value));
+        proof.verify(root, key_hash, read).unwrap();
+        assert_eq!(tree.get(key_hash, 1).unwrap(), None);
+        assert!(store.inner().estimated_size_bytes().unwrap() < plain.estimated_size_bytes().unwrap() / 2);
+    }
+}
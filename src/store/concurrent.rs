@@ -0,0 +1,170 @@
+use dashmap::DashMap;
+use jmt::storage::{LeafNode, Node, NodeBatch, NodeKey, TreeReader, TreeWriter};
+use jmt::{KeyHash, Version};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use super::{is_further_right, node_storage_key, value_storage_key, StoreError, WarmUp, NODE_PREFIX};
+
+/// A sharded counterpart to `InMemoryTreeStore`: the same encoding and lookup rules, but over
+/// `DashMap`s, so threads touching disjoint keys don't contend on one lock.
+#[derive(Default)]
+pub struct ConcurrentTreeStore {
+    store: Arc<DashMap<Vec<u8>, Vec<u8>>>,
+    // Every version at which a value (or tombstone) was written, per key hash
+    value_versions: Arc<DashMap<KeyHash, BTreeSet<Version>>>,
+}
+
+impl ConcurrentTreeStore {
+    pub fn new() -> Self {
+        Self {
+            store: Arc::new(DashMap::new()),
+            value_versions: Arc::new(DashMap::new()),
+        }
+    }
+}
+
+impl TreeReader for ConcurrentTreeStore {
+    fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node>, anyhow::Error> {
+        match self.store.get(&node_storage_key(node_key)?) {
+            Some(bytes) => Ok(Some(bincode::deserialize(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn get_value_option(
+        &self,
+        max_version: Version,
+        key_hash: KeyHash,
+    ) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        // The value visible at `max_version` is the one written at the greatest version <= it
+        let version = match self
+            .value_versions
+            .get(&key_hash)
+            .and_then(|versions| versions.range(..=max_version).next_back().copied())
+        {
+            Some(version) => version,
+            None => return Ok(None),
+        };
+
+        match self.store.get(&value_storage_key(version, key_hash)?) {
+            Some(bytes) => Ok(bincode::deserialize(&bytes)?),
+            None => Err(StoreError::NotFound(format!("value of {:?} at version {}", key_hash, version)).into()),
+        }
+    }
+
+    fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode)>, anyhow::Error> {
+        let mut rightmost: Option<(NodeKey, LeafNode)> = None;
+        for entry in self.store.iter() {
+            let node_key_bytes = match entry.key().split_first() {
+                Some((&NODE_PREFIX, rest)) => rest,
+                _ => continue,
+            };
+            let leaf = match bincode::deserialize(entry.value())? {
+                Node::Leaf(leaf) => leaf,
+                _ => continue,
+            };
+            let node_key: NodeKey = bincode::deserialize(node_key_bytes)?;
+            if is_further_right(&node_key, &leaf, rightmost.as_ref()) {
+                rightmost = Some((node_key, leaf));
+            }
+        }
+
+        Ok(rightmost)
+    }
+}
+
+impl WarmUp for ConcurrentTreeStore {}
+
+impl TreeWriter for ConcurrentTreeStore {
+    fn write_node_batch(&self, node_batch: &NodeBatch) -> Result<(), anyhow::Error> {
+        for (node_key, node) in node_batch.nodes() {
+            self.store.insert(node_storage_key(node_key)?, bincode::serialize(node)?);
+        }
+
+        // Each value slot is written before its version is indexed, so a concurrent reader
+        // never finds a version whose value is missing.
+        for ((version, key_hash), value_option) in node_batch.values() {
+            self.store.insert(
+                value_storage_key(*version, *key_hash)?,
+                bincode::serialize(value_option)?,
+            );
+            self.value_versions.entry(*key_hash).or_default().insert(*version);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::InMemoryTreeStore;
+    use jmt::JellyfishMerkleTree;
+    use sha2::Sha256;
+
+    #[test]
+    fn reads_match_in_memory_store() {
+        let concurrent = ConcurrentTreeStore::new();
+        let memory = InMemoryTreeStore::new();
+        let key_hashes: Vec<KeyHash> = (0..20)
+            .map(|i| KeyHash::with::<Sha256>(format!("key{}", i)))
+            .collect();
+
+        // Write every key, overwrite half of them, then delete a quarter
+        let value_sets: Vec<Vec<(KeyHash, Option<Vec<u8>>)>> = vec![
+            key_hashes.iter().map(|key_hash| (*key_hash, Some(b"v0".to_vec()))).collect(),
+            key_hashes.iter().step_by(2).map(|key_hash| (*key_hash, Some(b"v1".to_vec()))).collect(),
+            key_hashes.iter().step_by(4).map(|key_hash| (*key_hash, None)).collect(),
+        ];
+        for (version, value_set) in value_sets.into_iter().enumerate() {
+            let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&memory);
+            let (_root, batch) = tree.put_value_set(value_set, version as Version).unwrap();
+            concurrent.write_node_batch(&batch.node_batch).unwrap();
+            memory.write_node_batch(&batch.node_batch).unwrap();
+
+            for node_key in batch.node_batch.nodes().keys() {
+                assert_eq!(
+                    concurrent.get_node_option(node_key).unwrap(),
+                    memory.get_node_option(node_key).unwrap()
+                );
+            }
+        }
+
+        for key_hash in &key_hashes {
+            for version in 0..4 {
+                assert_eq!(
+                    concurrent.get_value_option(version, *key_hash).unwrap(),
+                    memory.get_value_option(version, *key_hash).unwrap()
+                );
+            }
+        }
+        assert_eq!(
+            concurrent.get_rightmost_leaf().unwrap(),
+            memory.get_rightmost_leaf().unwrap()
+        );
+    }
+
+    #[test]
+    fn missing_value_slot_fails_like_in_memory_store() {
+        let concurrent = ConcurrentTreeStore::new();
+        let memory = InMemoryTreeStore::new();
+        let key_hash = KeyHash::with::<Sha256>("key");
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&memory);
+        let (_root, batch) = tree.put_value_set(vec![(key_hash, Some(b"value".to_vec()))], 0).unwrap();
+        concurrent.write_node_batch(&batch.node_batch).unwrap();
+        memory.write_node_batch(&batch.node_batch).unwrap();
+
+        // The version stays indexed, but the slot it points at is gone
+        concurrent.store.remove(&value_storage_key(0, key_hash).unwrap());
+        memory.remove_value_slot(0, key_hash).unwrap();
+        let not_found = |error: anyhow::Error| match error.downcast::<StoreError>() {
+            Ok(StoreError::NotFound(what)) => what,
+            other => panic!("expected NotFound, got {:?}", other),
+        };
+        assert_eq!(
+            not_found(concurrent.get_value_option(0, key_hash).unwrap_err()),
+            not_found(memory.get_value_option(0, key_hash).unwrap_err())
+        );
+    }
+}
@@ -0,0 +1,44 @@
+use jmt::{KeyHash, OwnedValue};
+use std::collections::HashSet;
+
+/// Collapses repeated key hashes in one value set to their last write, keeping the surviving
+/// entries in the order they were written. jmt's `put_value_set` already resolves duplicates
+/// this way by collecting into a map, so deduping first changes no root; it lets callers see
+/// and rely on the rule instead.
+pub fn dedup_value_set(value_set: Vec<(KeyHash, Option<OwnedValue>)>) -> Vec<(KeyHash, Option<OwnedValue>)> {
+    let mut seen = HashSet::with_capacity(value_set.len());
+    let mut deduped: Vec<_> = value_set.into_iter().rev().filter(|(key_hash, _)| seen.insert(*key_hash)).collect();
+    deduped.reverse();
+    deduped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::InMemoryTreeStore;
+    use jmt::JellyfishMerkleTree;
+    use sha2::Sha256;
+
+    #[test]
+    fn last_write_wins_and_matches_jmt() {
+        let a = KeyHash::with::<Sha256>("a");
+        let b = KeyHash::with::<Sha256>("b");
+        let c = KeyHash::with::<Sha256>("c");
+        let value_set = vec![
+            (a, Some(b"a1".to_vec())),
+            (b, Some(b"b1".to_vec())),
+            (a, Some(b"a2".to_vec())),
+            (c, Some(b"c1".to_vec())),
+            (b, Some(b"b2".to_vec())),
+        ];
+
+        let deduped = dedup_value_set(value_set.clone());
+        assert_eq!(deduped, vec![(a, Some(b"a2".to_vec())), (c, Some(b"c1".to_vec())), (b, Some(b"b2".to_vec()))]);
+
+        let store = InMemoryTreeStore::new();
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&store);
+        let (raw_root, _) = tree.put_value_set(value_set, 0).unwrap();
+        let (deduped_root, _) = tree.put_value_set(deduped, 0).unwrap();
+        assert_eq!(raw_root, deduped_root);
+    }
+}
@@ -0,0 +1,42 @@
+use std::sync::PoisonError;
+
+/// Why a store operation failed. The JMT traits carry these inside `anyhow::Error`, so callers
+/// can still tell them apart with `downcast_ref::<StoreError>()`.
+#[derive(Debug, thiserror::Error)]
+pub enum StoreError {
+    /// A thread panicked while holding one of the store's locks.
+    #[error("lock poisoned")]
+    LockPoisoned,
+    /// A stored node, value or key failed to encode or decode.
+    #[error("serialization failed: {0}")]
+    Serialization(#[from] bincode::Error),
+    /// A stored node or value failed to encode or decode with the postcard codec.
+    #[cfg(feature = "postcard")]
+    #[error("postcard serialization failed: {0}")]
+    Postcard(#[from] postcard::Error),
+    /// The store's indexes point at an entry that is not there.
+    #[error("not found: {0}")]
+    NotFound(String),
+    /// A dataset file could not be read.
+    #[error("I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+    /// Line `line` of a dataset file is not a hex key or `key,value` pair.
+    #[error("line {line} is malformed: {reason}")]
+    MalformedRow { line: usize, reason: String },
+    /// Proof `index` of a batch failed verification.
+    #[error("proof {index} failed to verify: {reason}")]
+    InvalidProof { index: usize, reason: String },
+    /// Two different keys in one batch hashed to the same `KeyHash`.
+    #[error("keys {first:?} and {second:?} both hash to {key_hash:?}")]
+    KeyHashCollision {
+        key_hash: jmt::KeyHash,
+        first: Vec<u8>,
+        second: Vec<u8>,
+    },
+}
+
+impl<T> From<PoisonError<T>> for StoreError {
+    fn from(_: PoisonError<T>) -> Self {
+        StoreError::LockPoisoned
+    }
+}
@@ -0,0 +1,79 @@
+use jmt::storage::TreeReader;
+use jmt::{JellyfishMerkleIterator, KeyHash, OwnedValue, Version};
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+
+/// The leaves of one version in key-hash order, `chunk_len` at a time, as a periodic state
+/// snapshot would write them out. Built on jmt's `JellyfishMerkleIterator`, which walks the
+/// tree once, so a chunk costs only the nodes its leaves hang from.
+pub struct LeafChunks<R> {
+    leaves: JellyfishMerkleIterator<R>,
+    chunk_len: usize,
+    done: bool,
+}
+
+/// Starts exporting every leaf of the tree at `version`. Only the last chunk can be shorter
+/// than `chunk_len`, and an empty tree yields no chunks.
+pub fn export_leaves<R: TreeReader>(
+    reader: Arc<R>,
+    version: Version,
+    chunk_len: NonZeroUsize,
+) -> Result<LeafChunks<R>, anyhow::Error> {
+    Ok(LeafChunks {
+        leaves: JellyfishMerkleIterator::new(reader, version, KeyHash([0; 32]))?,
+        chunk_len: chunk_len.get(),
+        done: false,
+    })
+}
+
+impl<R: TreeReader> Iterator for LeafChunks<R> {
+    type Item = Result<Vec<(KeyHash, OwnedValue)>, anyhow::Error>;
+
+    /// A failed read ends the export, since the walk cannot resume past it.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let mut chunk = Vec::with_capacity(self.chunk_len);
+        for leaf in self.leaves.by_ref().take(self.chunk_len) {
+            match leaf {
+                Ok(leaf) => chunk.push(leaf),
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+        if chunk.len() < self.chunk_len {
+            self.done = true;
+        }
+        (!chunk.is_empty()).then_some(Ok(chunk))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::InMemoryTreeStore;
+    use crate::workload;
+    use sha2::Sha256;
+
+    #[test]
+    fn chunks_cover_every_leaf_in_key_order() {
+        let store = Arc::new(InMemoryTreeStore::new());
+        workload::commit_streamed::<_, Sha256>(&*store, workload::value_set_iter(1000, 8), 0).unwrap();
+        workload::commit_streamed::<_, Sha256>(&*store, workload::value_set_iter(1010, 8).skip(1000), 1).unwrap();
+
+        let chunks: Vec<_> = export_leaves(Arc::clone(&store), 0, NonZeroUsize::new(256).unwrap())
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(chunks.iter().map(Vec::len).collect::<Vec<_>>(), vec![256, 256, 256, 232]);
+        let mut expected: Vec<_> = workload::value_set_iter(1000, 8).map(|(key_hash, value)| (key_hash, value.unwrap())).collect();
+        expected.sort_by_key(|(key_hash, _value)| *key_hash);
+        assert_eq!(chunks.concat(), expected);
+
+        let leaves: usize = export_leaves(store, 1, NonZeroUsize::new(1010).unwrap()).unwrap().map(|chunk| chunk.unwrap().len()).sum();
+        assert_eq!(leaves, 1010);
+    }
+}
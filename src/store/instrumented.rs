@@ -0,0 +1,198 @@
+use jmt::storage::{LeafNode, Node, NodeBatch, NodeKey, TreeReader, TreeWriter};
+use jmt::{KeyHash, Version};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::WarmUp;
+
+/// How many reads an [`InstrumentedReader`] has passed through.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReadCounts {
+    /// Calls to `get_node_option`, including those made through `get_node`.
+    pub nodes: u64,
+    /// Calls to `get_value_option`.
+    pub values: u64,
+}
+
+/// What an [`InstrumentedReader`] has written through to a `TreeWriter`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WriteCounts {
+    /// Calls to `write_node_batch`, one per committed `put_value_set`.
+    pub batches: u64,
+    /// Nodes written across those batches.
+    pub nodes: u64,
+    /// Values written, i.e. the logical key-value changes the batches committed.
+    pub values: u64,
+}
+
+impl WriteCounts {
+    /// Nodes written per logical change: how much a commit rewrites beyond the values it was
+    /// asked to change. `None` before any value is written.
+    pub fn amplification(&self) -> Option<f64> {
+        (self.values > 0).then(|| self.nodes as f64 / self.values as f64)
+    }
+}
+
+/// Counts the node and value reads made against any `TreeReader`, so the fetches behind an
+/// operation can be read next to its latency. When the inner store is also a `TreeWriter`,
+/// writes are passed through and counted too. With the `metrics` feature it also tracks bytes
+/// written and commit latencies, and renders everything with
+/// [`metrics_text`](Self::metrics_text).
+pub struct InstrumentedReader<R> {
+    inner: R,
+    nodes: AtomicU64,
+    values: AtomicU64,
+    batches_written: AtomicU64,
+    nodes_written: AtomicU64,
+    values_written: AtomicU64,
+    #[cfg(feature = "metrics")]
+    metrics: super::metrics::CommitMetrics,
+}
+
+impl<R: TreeReader> InstrumentedReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            nodes: AtomicU64::new(0),
+            values: AtomicU64::new(0),
+            batches_written: AtomicU64::new(0),
+            nodes_written: AtomicU64::new(0),
+            values_written: AtomicU64::new(0),
+            #[cfg(feature = "metrics")]
+            metrics: super::metrics::CommitMetrics::new(),
+        }
+    }
+
+    /// The wrapped reader, for reads that should not be counted.
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// Reads counted since construction or the last [`reset`](Self::reset).
+    pub fn counts(&self) -> ReadCounts {
+        ReadCounts {
+            nodes: self.nodes.load(Ordering::Relaxed),
+            values: self.values.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Writes counted since construction or the last [`reset`](Self::reset).
+    pub fn write_counts(&self) -> WriteCounts {
+        WriteCounts {
+            batches: self.batches_written.load(Ordering::Relaxed),
+            nodes: self.nodes_written.load(Ordering::Relaxed),
+            values: self.values_written.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Zeroes every read and write counter.
+    pub fn reset(&self) {
+        for counter in [&self.nodes, &self.values, &self.batches_written, &self.nodes_written, &self.values_written] {
+            counter.store(0, Ordering::Relaxed);
+        }
+        #[cfg(feature = "metrics")]
+        self.metrics.reset();
+    }
+
+    /// Everything counted since construction or the last [`reset`](Self::reset), in the
+    /// Prometheus text exposition format: read and write counters, bytes written, and a
+    /// histogram of the time each `write_node_batch` took in the inner store.
+    #[cfg(feature = "metrics")]
+    pub fn metrics_text(&self) -> String {
+        self.metrics.render(self.counts(), self.write_counts())
+    }
+}
+
+impl<R: TreeReader> TreeReader for InstrumentedReader<R> {
+    fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node>, anyhow::Error> {
+        self.nodes.fetch_add(1, Ordering::Relaxed);
+        self.inner.get_node_option(node_key)
+    }
+
+    fn get_value_option(
+        &self,
+        max_version: Version,
+        key_hash: KeyHash,
+    ) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        self.values.fetch_add(1, Ordering::Relaxed);
+        self.inner.get_value_option(max_version, key_hash)
+    }
+
+    fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode)>, anyhow::Error> {
+        self.inner.get_rightmost_leaf()
+    }
+}
+
+impl<R: WarmUp> WarmUp for InstrumentedReader<R> {
+    fn warm_up(&self) -> Result<(), anyhow::Error> {
+        self.inner.warm_up()
+    }
+}
+
+impl<R: TreeWriter> TreeWriter for InstrumentedReader<R> {
+    fn write_node_batch(&self, node_batch: &NodeBatch) -> Result<(), anyhow::Error> {
+        #[cfg(feature = "metrics")]
+        let start = std::time::Instant::now();
+        self.inner.write_node_batch(node_batch)?;
+        #[cfg(feature = "metrics")]
+        self.metrics.record(node_batch, start.elapsed())?;
+        self.batches_written.fetch_add(1, Ordering::Relaxed);
+        self.nodes_written.fetch_add(node_batch.nodes().len() as u64, Ordering::Relaxed);
+        self.values_written.fetch_add(node_batch.values().len() as u64, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{InMemoryTreeStore, TreeWriter};
+    use jmt::JellyfishMerkleTree;
+    use sha2::Sha256;
+
+    #[test]
+    fn counts_the_reads_behind_each_proof() {
+        let store = InMemoryTreeStore::new();
+        let key_hash = KeyHash::with::<Sha256>("key");
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&store);
+        let (_root, batch) = tree
+            .put_value_set(
+                vec![(key_hash, Some(b"value".to_vec())), (KeyHash::with::<Sha256>("other"), Some(b"other".to_vec()))],
+                0,
+            )
+            .unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+
+        let instrumented = InstrumentedReader::new(store);
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&instrumented);
+        tree.get_with_proof(key_hash, 0).unwrap();
+        let counts = instrumented.counts();
+        assert!(counts.nodes >= 2, "{:?}", counts);
+        assert_eq!(counts.values, 1);
+
+        instrumented.reset();
+        assert_eq!(instrumented.counts(), ReadCounts::default());
+        tree.get_with_proof(key_hash, 0).unwrap();
+        assert_eq!(instrumented.counts(), counts);
+    }
+
+    #[test]
+    fn counts_the_nodes_each_commit_rewrites() {
+        let instrumented = InstrumentedReader::new(InMemoryTreeStore::new());
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&instrumented);
+        assert_eq!(instrumented.write_counts().amplification(), None);
+
+        let (_root, batch) = tree
+            .put_value_set((0..100).map(|i| (KeyHash::with::<Sha256>(i.to_string()), Some(vec![i as u8]))), 0)
+            .unwrap();
+        instrumented.write_node_batch(&batch.node_batch).unwrap();
+        instrumented.reset();
+
+        // One changed leaf rewrites itself and every internal node above it
+        let (_root, batch) = tree.put_value_set(vec![(KeyHash::with::<Sha256>("7"), Some(vec![0]))], 1).unwrap();
+        instrumented.write_node_batch(&batch.node_batch).unwrap();
+        let counts = instrumented.write_counts();
+        assert_eq!((counts.batches, counts.values), (1, 1));
+        assert!(counts.nodes >= 2, "{:?}", counts);
+        assert_eq!(counts.amplification(), Some(counts.nodes as f64));
+    }
+}
@@ -0,0 +1,111 @@
+use jmt::{KeyHash, OwnedValue, SimpleHasher};
+use std::collections::HashMap;
+
+use super::StoreError;
+
+/// Hashes every key of a batch with `H`, ready for `put_value_set`. The tree only ever sees
+/// the hashes, so two keys that collide would silently overwrite each other; debug builds check
+/// for that and fail with [`StoreError::KeyHashCollision`] instead. Release builds skip the
+/// check, so benchmarks pay nothing for it.
+///
+/// The same key given twice is not a collision: it hashes to one entry, and the last write
+/// wins as usual.
+pub fn hash_keys<H: SimpleHasher>(
+    pairs: impl IntoIterator<Item = (Vec<u8>, Option<OwnedValue>)>,
+) -> Result<Vec<(KeyHash, Option<OwnedValue>)>, StoreError> {
+    let mut preimages = HashMap::new();
+    pairs
+        .into_iter()
+        .map(|(key, value)| {
+            let key_hash = KeyHash::with::<H>(&key);
+            if cfg!(debug_assertions) {
+                if let Some(first) = preimages.insert(key_hash, key.clone()) {
+                    if first != key {
+                        return Err(StoreError::KeyHashCollision { key_hash, first, second: key });
+                    }
+                }
+            }
+            Ok((key_hash, value))
+        })
+        .collect()
+}
+
+/// [`hash_keys`] with the hashing spread over rayon's current thread pool, for the one part of
+/// a commit that parallelizes: jmt builds each batch on a single thread. The output and the
+/// debug-build collision check are the same, but the check runs after all keys are hashed,
+/// because it needs one map of every key seen.
+#[cfg(feature = "rayon")]
+pub fn par_hash_keys<H: SimpleHasher>(
+    pairs: Vec<(Vec<u8>, Option<OwnedValue>)>,
+) -> Result<Vec<(KeyHash, Option<OwnedValue>)>, StoreError> {
+    use rayon::prelude::*;
+
+    let key_hashes: Vec<KeyHash> = pairs.par_iter().map(|(key, _value)| KeyHash::with::<H>(key)).collect();
+    if cfg!(debug_assertions) {
+        let mut preimages: HashMap<KeyHash, &[u8]> = HashMap::with_capacity(pairs.len());
+        for ((key, _value), key_hash) in pairs.iter().zip(&key_hashes) {
+            if let Some(first) = preimages.insert(*key_hash, key) {
+                if first != key.as_slice() {
+                    return Err(StoreError::KeyHashCollision { key_hash: *key_hash, first: first.to_vec(), second: key.clone() });
+                }
+            }
+        }
+    }
+    Ok(key_hashes.into_iter().zip(pairs).map(|(key_hash, (_key, value))| (key_hash, value)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha2::Sha256;
+
+    // Keeps only the first byte of its input, so any two keys starting alike collide
+    struct FirstByte(u8);
+
+    impl SimpleHasher for FirstByte {
+        fn new() -> Self {
+            FirstByte(0)
+        }
+
+        fn update(&mut self, data: &[u8]) {
+            if let Some(byte) = data.first() {
+                self.0 = *byte;
+            }
+        }
+
+        fn finalize(self) -> [u8; 32] {
+            [self.0; 32]
+        }
+    }
+
+    #[test]
+    fn repeated_keys_are_not_collisions() {
+        let pairs = vec![(b"a".to_vec(), Some(b"1".to_vec())), (b"a".to_vec(), Some(b"2".to_vec()))];
+        let value_set = hash_keys::<Sha256>(pairs).unwrap();
+        assert_eq!(value_set[0].0, value_set[1].0);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn colliding_keys_are_rejected() {
+        let pairs = vec![(b"apple".to_vec(), Some(b"1".to_vec())), (b"avocado".to_vec(), Some(b"2".to_vec()))];
+        match hash_keys::<FirstByte>(pairs) {
+            Err(StoreError::KeyHashCollision { first, second, .. }) => {
+                assert_eq!((first, second), (b"apple".to_vec(), b"avocado".to_vec()))
+            }
+            other => panic!("expected a collision, got {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_hashing_matches_sequential() {
+        let pairs: Vec<_> = crate::workload::pairs(1000, 8).into_iter().map(|(key, value)| (key, Some(value))).collect();
+        assert_eq!(par_hash_keys::<Sha256>(pairs.clone()).unwrap(), hash_keys::<Sha256>(pairs).unwrap());
+
+        if cfg!(debug_assertions) {
+            let colliding = vec![(b"apple".to_vec(), None), (b"avocado".to_vec(), None)];
+            assert!(matches!(par_hash_keys::<FirstByte>(colliding), Err(StoreError::KeyHashCollision { .. })));
+        }
+    }
+}
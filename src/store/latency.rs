@@ -0,0 +1,89 @@
+use jmt::storage::{LeafNode, Node, NodeBatch, NodeKey, TreeReader, TreeWriter};
+use jmt::{KeyHash, Version};
+use std::time::{Duration, Instant};
+
+use super::WarmUp;
+
+/// Adds a fixed delay to every node read of any `TreeReader`, to model how a tree's node read
+/// count turns into latency on a store where each read costs `read_latency`. Values and
+/// writes pass straight through.
+///
+/// The delay is a busy-wait rather than `thread::sleep`, whose wakeups are too coarse for the
+/// tens of microseconds an SSD read takes.
+pub struct LatencyTreeStore<R> {
+    inner: R,
+    read_latency: Duration,
+}
+
+impl<R: TreeReader> LatencyTreeStore<R> {
+    pub fn new(inner: R, read_latency: Duration) -> Self {
+        Self { inner, read_latency }
+    }
+
+    /// The wrapped store, for reads that should not be delayed.
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+}
+
+fn spin_for(duration: Duration) {
+    let start = Instant::now();
+    while start.elapsed() < duration {
+        std::hint::spin_loop();
+    }
+}
+
+impl<R: TreeReader> TreeReader for LatencyTreeStore<R> {
+    fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node>, anyhow::Error> {
+        spin_for(self.read_latency);
+        self.inner.get_node_option(node_key)
+    }
+
+    fn get_value_option(
+        &self,
+        max_version: Version,
+        key_hash: KeyHash,
+    ) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        self.inner.get_value_option(max_version, key_hash)
+    }
+
+    fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode)>, anyhow::Error> {
+        self.inner.get_rightmost_leaf()
+    }
+}
+
+impl<R: TreeWriter> TreeWriter for LatencyTreeStore<R> {
+    fn write_node_batch(&self, node_batch: &NodeBatch) -> Result<(), anyhow::Error> {
+        self.inner.write_node_batch(node_batch)
+    }
+}
+
+impl<R: WarmUp> WarmUp for LatencyTreeStore<R> {
+    fn warm_up(&self) -> Result<(), anyhow::Error> {
+        self.inner.warm_up()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{InMemoryTreeStore, InstrumentedReader};
+    use crate::workload;
+    use jmt::JellyfishMerkleTree;
+    use sha2::Sha256;
+
+    #[test]
+    fn every_node_read_waits_at_least_the_latency() {
+        let store = InMemoryTreeStore::new();
+        workload::commit_streamed::<_, Sha256>(&store, workload::value_set_iter(100, 8), 0).unwrap();
+        let latency = Duration::from_micros(200);
+        let counted = InstrumentedReader::new(LatencyTreeStore::new(store, latency));
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&counted);
+
+        let start = Instant::now();
+        let (value, _proof) = tree.get_with_proof(KeyHash::with::<Sha256>(workload::key(0)), 0).unwrap();
+        let elapsed = start.elapsed();
+        assert_eq!(value, Some(workload::value(0, 8)));
+        assert!(elapsed >= latency * counted.counts().nodes as u32, "{:?}", elapsed);
+    }
+}
@@ -0,0 +1,618 @@
+use jmt::storage::{LeafNode, NibblePath, Node, NodeBatch, NodeKey, StaleNodeIndex, TreeReader, TreeWriter};
+use jmt::{KeyHash, OwnedValue, Version};
+use std::collections::{BTreeSet, HashMap};
+use std::marker::PhantomData;
+use std::sync::{Arc, RwLock};
+
+use super::{is_further_right, node_storage_key, value_storage_key, Bincode, NodeCodec, StoreError, TreeStats, WarmUp, NODE_PREFIX};
+
+// Stack size of a `HashMap<Vec<u8>, Vec<u8>>` entry, two `Vec` headers, on 64-bit targets
+const ENTRY_OVERHEAD: usize = 2 * std::mem::size_of::<Vec<u8>>();
+
+/// A `HashMap`-backed JMT store that serializes nodes and values the way a key-value
+/// database would, so benchmarks pay realistic encoding costs. The encoding is the codec `C`,
+/// bincode unless chosen otherwise.
+pub struct InMemoryTreeStore<C = Bincode> {
+    store: Arc<RwLock<HashMap<Vec<u8>, Vec<u8>>>>,
+    // Every version at which a value (or tombstone) was written, per key hash
+    value_versions: Arc<RwLock<HashMap<KeyHash, BTreeSet<Version>>>>,
+    codec: PhantomData<C>,
+}
+
+impl InMemoryTreeStore {
+    pub fn new() -> Self {
+        Self::with_codec()
+    }
+}
+
+impl<C: NodeCodec> Default for InMemoryTreeStore<C> {
+    fn default() -> Self {
+        Self::with_codec()
+    }
+}
+
+impl<C: NodeCodec> InMemoryTreeStore<C> {
+    /// An empty store encoding with `C`, e.g. `InMemoryTreeStore::<Postcard>::with_codec()`.
+    pub fn with_codec() -> Self {
+        Self {
+            store: Arc::new(RwLock::new(HashMap::new())),
+            value_versions: Arc::new(RwLock::new(HashMap::new())),
+            codec: PhantomData,
+        }
+    }
+
+    /// Copies the store into an independent one, so a populated tree can be built once and
+    /// handed to every benchmark iteration that writes to it. Only the bytes are copied; nothing
+    /// is deserialized.
+    pub fn clone_snapshot(&self) -> Result<Self, StoreError> {
+        Ok(Self {
+            store: Arc::new(RwLock::new(self.store.read()?.clone())),
+            value_versions: Arc::new(RwLock::new(self.value_versions.read()?.clone())),
+            codec: PhantomData,
+        })
+    }
+
+    /// Walks every stored node and tallies the tree's height and node counts.
+    pub fn tree_stats(&self) -> Result<TreeStats, StoreError> {
+        let mut stats = TreeStats::default();
+        self.for_each_node(|node_key, node| stats.record(&node_key, &node))?;
+        Ok(stats)
+    }
+
+    /// Estimates the bytes the store holds: every stored key and value, plus a fixed
+    /// per-entry overhead for their headers, plus the version index kept per key hash. Allocator
+    /// slack and the maps' spare capacity are not counted.
+    pub fn estimated_size_bytes(&self) -> Result<usize, StoreError> {
+        let store = self.store.read()?;
+        let value_versions = self.value_versions.read()?;
+        let entries: usize = store.iter().map(|(key, value)| key.len() + value.len() + ENTRY_OVERHEAD).sum();
+        let index: usize = value_versions
+            .values()
+            .map(|versions| {
+                std::mem::size_of::<KeyHash>()
+                    + std::mem::size_of::<BTreeSet<Version>>()
+                    + versions.len() * std::mem::size_of::<Version>()
+            })
+            .sum();
+        Ok(entries + index)
+    }
+
+    /// Counts the internal nodes with each number of children: entry `n` of the histogram is
+    /// how many internal nodes have `n` children, across all stored versions.
+    pub fn fanout_histogram(&self) -> Result<[u64; 17], StoreError> {
+        let mut histogram = [0; 17];
+        self.for_each_node(|_node_key, node| {
+            if let Node::Internal(internal) = node {
+                histogram[internal.children_sorted().count()] += 1;
+            }
+        })?;
+        Ok(histogram)
+    }
+
+    /// Deletes the nodes named by `stale_node_indices`, as reported in each commit's
+    /// `TreeUpdateBatch`, and returns how many were present. Versions older than the newest
+    /// `stale_since_version` can no longer be read afterwards; values are kept, for
+    /// [`prune_values`](Self::prune_values) to delete.
+    pub fn prune<'a>(
+        &self,
+        stale_node_indices: impl IntoIterator<Item = &'a StaleNodeIndex>,
+    ) -> Result<usize, StoreError> {
+        let mut store = self.store.write()?;
+
+        let mut pruned = 0;
+        for index in stale_node_indices {
+            if store.remove(&node_storage_key(&index.node_key)?).is_some() {
+                pruned += 1;
+            }
+        }
+
+        Ok(pruned)
+    }
+
+    /// Deletes the values of `key_hash` that no read at `oldest` or later can see: every one
+    /// older than the newest written at or below `oldest`, and that one too if it is a
+    /// deletion, since a deleted key reads the same as one never written. Returns how many
+    /// were deleted. Reads below `oldest` may find nothing afterwards, so callers must refuse
+    /// them, as `BoundedTreeStore` does.
+    pub fn prune_values(&self, key_hash: KeyHash, oldest: Version) -> Result<usize, StoreError> {
+        let mut store = self.store.write()?;
+        let mut value_versions = self.value_versions.write()?;
+        let versions = match value_versions.get_mut(&key_hash) {
+            Some(versions) => versions,
+            None => return Ok(0),
+        };
+        let visible = match versions.range(..=oldest).next_back() {
+            Some(version) => *version,
+            None => return Ok(0),
+        };
+
+        let mut pruned: Vec<Version> = versions.range(..visible).copied().collect();
+        if let Some(bytes) = store.get(&value_storage_key(visible, key_hash)?) {
+            if C::deserialize::<Option<Vec<u8>>>(bytes)?.is_none() {
+                pruned.push(visible);
+            }
+        }
+        for version in &pruned {
+            store.remove(&value_storage_key(*version, key_hash)?);
+            versions.remove(version);
+        }
+        if versions.is_empty() {
+            value_versions.remove(&key_hash);
+        }
+        Ok(pruned.len())
+    }
+
+    /// Walks the tree at `version` from its root and checks that every child an internal node
+    /// points at is stored, and that every leaf's value is readable at the leaf's version. The
+    /// first gap found is returned as [`StoreError::NotFound`], naming the missing piece and
+    /// where it hangs in the tree.
+    pub fn verify_integrity(&self, version: Version) -> Result<(), StoreError> {
+        let root_key = NodeKey::new(version, std::iter::empty().collect());
+        let mut pending = vec![(root_key, "the version's root".to_string())];
+        while let Some((node_key, referenced_by)) = pending.pop() {
+            let node = self.node(&node_key)?.ok_or_else(|| {
+                StoreError::NotFound(format!(
+                    "node {} at version {}, referenced by {}",
+                    path_hex(node_key.nibble_path()),
+                    node_key.version(),
+                    referenced_by
+                ))
+            })?;
+            match node {
+                Node::Internal(internal) => {
+                    for (nibble, child) in internal.children_sorted() {
+                        let child_path: NibblePath = node_key.nibble_path().nibbles().chain(std::iter::once(nibble)).collect();
+                        let referenced_by = format!("child {:x} of node {}", nibble, path_hex(node_key.nibble_path()));
+                        pending.push((NodeKey::new(child.version, child_path), referenced_by));
+                    }
+                }
+                Node::Leaf(leaf) => {
+                    if self.value(node_key.version(), leaf.key_hash())?.is_none() {
+                        return Err(StoreError::NotFound(format!(
+                            "value of {:?} at version {}, referenced by leaf {}",
+                            leaf.key_hash(),
+                            node_key.version(),
+                            path_hex(node_key.nibble_path())
+                        )));
+                    }
+                }
+                Node::Null => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Lists every key whose value at version `to` differs from its value at `from`, with the
+    /// value at `to` (`None` once deleted), sorted by key hash. jmt has no diff of its own, so
+    /// this compares the two versions' values. Only keys written after the older of the two
+    /// versions and no later than the newer can differ, so the version index narrows the
+    /// comparison to those. Rewriting a key with the value it already had is not a change.
+    pub fn diff(&self, from: Version, to: Version) -> Result<Vec<(KeyHash, Option<OwnedValue>)>, StoreError> {
+        if from == to {
+            return Ok(Vec::new());
+        }
+        let (older, newer) = (from.min(to), from.max(to));
+        let mut written: Vec<KeyHash> = self
+            .value_versions
+            .read()?
+            .iter()
+            .filter(|(_key_hash, versions)| versions.range(older + 1..=newer).next().is_some())
+            .map(|(key_hash, _versions)| *key_hash)
+            .collect();
+        written.sort_unstable();
+
+        let mut changed = Vec::new();
+        for key_hash in written {
+            let value = self.value(to, key_hash)?;
+            if self.value(from, key_hash)? != value {
+                changed.push((key_hash, value));
+            }
+        }
+        Ok(changed)
+    }
+
+    fn for_each_node(&self, mut f: impl FnMut(NodeKey, Node)) -> Result<(), StoreError> {
+        let store = self.store.read()?;
+        for (key_bytes, node_bytes) in store.iter() {
+            let node_key_bytes = match key_bytes.split_first() {
+                Some((&NODE_PREFIX, rest)) => rest,
+                _ => continue,
+            };
+            f(bincode::deserialize(node_key_bytes)?, C::deserialize(node_bytes)?);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn node(
+        &self,
+        node_key: &NodeKey,
+    ) -> Result<Option<Node>, StoreError> {
+        let store = self.store.read()?;
+        match store.get(&node_storage_key(node_key)?) {
+            Some(bytes) => Ok(Some(C::deserialize(bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn value(
+        &self,
+        max_version: Version,
+        key_hash: KeyHash,
+    ) -> Result<Option<Vec<u8>>, StoreError> {
+        // The value visible at `max_version` is the one written at the greatest version <= it.
+        // The index lock is released before the store's is taken, since `write` takes them in
+        // the other order
+        let version = match self
+            .value_versions
+            .read()?
+            .get(&key_hash)
+            .and_then(|versions| versions.range(..=max_version).next_back())
+        {
+            Some(version) => *version,
+            None => return Ok(None),
+        };
+
+        let store = self.store.read()?;
+        match store.get(&value_storage_key(version, key_hash)?) {
+            Some(bytes) => C::deserialize(bytes),
+            None => Err(StoreError::NotFound(format!("value of {:?} at version {}", key_hash, version))),
+        }
+    }
+
+    fn rightmost_leaf(
+        &self,
+    ) -> Result<Option<(NodeKey, LeafNode)>, StoreError> {
+        let store = self.store.read()?;
+
+        let mut rightmost: Option<(NodeKey, LeafNode)> = None;
+        for (key_bytes, node_bytes) in store.iter() {
+            let node_key_bytes = match key_bytes.split_first() {
+                Some((&NODE_PREFIX, rest)) => rest,
+                _ => continue,
+            };
+            let leaf = match C::deserialize(node_bytes)? {
+                Node::Leaf(leaf) => leaf,
+                _ => continue,
+            };
+            let node_key: NodeKey = bincode::deserialize(node_key_bytes)?;
+            if is_further_right(&node_key, &leaf, rightmost.as_ref()) {
+                rightmost = Some((node_key, leaf));
+            }
+        }
+
+        Ok(rightmost)
+    }
+
+    pub(super) fn write(&self, node_batch: &NodeBatch) -> Result<(), StoreError> {
+        let mut store = self.store.write()?;
+        let mut value_versions = self.value_versions.write()?;
+        
+        for (node_key, node) in node_batch.nodes() {
+            let node_bytes = C::serialize(node)?;
+            store.insert(node_storage_key(node_key)?, node_bytes);
+        }
+        
+        // Values are never overwritten in place: each version gets its own slot, and a
+        // deletion is recorded as a `None` tombstone so older versions stay readable.
+        for ((version, key_hash), value_option) in node_batch.values() {
+            store.insert(value_storage_key(*version, *key_hash)?, C::serialize(value_option)?);
+            value_versions.entry(*key_hash).or_default().insert(*version);
+        }
+        
+        Ok(())
+    }
+
+    // Drops one value slot and leaves its version indexed, for tests of a store whose index and
+    // slots disagree
+    #[cfg(all(test, feature = "dashmap"))]
+    pub(crate) fn remove_value_slot(&self, version: Version, key_hash: KeyHash) -> Result<(), StoreError> {
+        self.store.write()?.remove(&value_storage_key(version, key_hash)?);
+        Ok(())
+    }
+}
+
+fn path_hex(nibble_path: &NibblePath) -> String {
+    if nibble_path.num_nibbles() == 0 {
+        "root".to_string()
+    } else {
+        nibble_path.nibbles().map(|nibble| format!("{:x}", nibble)).collect()
+    }
+}
+
+// The JMT traits want `anyhow::Error`; the `StoreError` stays inside it for callers to downcast
+impl<C: NodeCodec> TreeReader for InMemoryTreeStore<C> {
+    fn get_node_option(
+        &self,
+        node_key: &NodeKey,
+    ) -> Result<Option<Node>, anyhow::Error> {
+        Ok(self.node(node_key)?)
+    }
+
+    fn get_value_option(
+        &self,
+        max_version: Version,
+        key_hash: KeyHash,
+    ) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        Ok(self.value(max_version, key_hash)?)
+    }
+
+    fn get_rightmost_leaf(
+        &self,
+    ) -> Result<Option<(NodeKey, LeafNode)>, anyhow::Error> {
+        Ok(self.rightmost_leaf()?)
+    }
+}
+
+impl<C: NodeCodec> WarmUp for InMemoryTreeStore<C> {}
+
+impl<C: NodeCodec> TreeWriter for InMemoryTreeStore<C> {
+    fn write_node_batch(&self, node_batch: &NodeBatch) -> Result<(), anyhow::Error> {
+        Ok(self.write(node_batch)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blake2::Blake2s256;
+    use jmt::JellyfishMerkleTree;
+    use sha2::{Digest, Sha256};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn overwritten_value_is_still_readable_at_old_version() {
+        let store = InMemoryTreeStore::new();
+        let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Blake2s256> = JellyfishMerkleTree::new(&store);
+        let key_hash = KeyHash(Sha256::digest(b"key").into());
+
+        let (_root, batch) = jmt.put_value_set(vec![(key_hash, Some(b"v0".to_vec()))], 0).unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+        let (_root, batch) = jmt.put_value_set(vec![(key_hash, Some(b"v1".to_vec()))], 1).unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+
+        assert_eq!(store.get_value_option(0, key_hash).unwrap(), Some(b"v0".to_vec()));
+        assert_eq!(store.get_value_option(1, key_hash).unwrap(), Some(b"v1".to_vec()));
+        assert_eq!(store.get_value_option(5, key_hash).unwrap(), Some(b"v1".to_vec()));
+        assert_eq!(jmt.get(key_hash, 0).unwrap(), Some(b"v0".to_vec()));
+    }
+
+    #[test]
+    fn deleted_value_is_hidden_from_later_versions_only() {
+        let store = InMemoryTreeStore::new();
+        let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Blake2s256> = JellyfishMerkleTree::new(&store);
+        let key_hash = KeyHash(Sha256::digest(b"key").into());
+
+        let (_root, batch) = jmt.put_value_set(vec![(key_hash, Some(b"v0".to_vec()))], 0).unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+        let (_root, batch) = jmt.put_value_set(vec![(key_hash, None)], 1).unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+
+        assert_eq!(store.get_value_option(0, key_hash).unwrap(), Some(b"v0".to_vec()));
+        assert_eq!(store.get_value_option(1, key_hash).unwrap(), None);
+        assert_eq!(jmt.get(key_hash, 1).unwrap(), None);
+    }
+
+    #[test]
+    fn snapshot_is_independent_of_the_original() {
+        let store = InMemoryTreeStore::new();
+        let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Sha256> = JellyfishMerkleTree::new(&store);
+        let key_hash = KeyHash(Sha256::digest(b"key").into());
+        let (root, batch) = jmt.put_value_set(vec![(key_hash, Some(b"v0".to_vec()))], 0).unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+
+        let snapshot = store.clone_snapshot().unwrap();
+        let snapshot_jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Sha256> = JellyfishMerkleTree::new(&snapshot);
+        assert_eq!(snapshot_jmt.get_root_hash(0).unwrap(), root);
+        let (_root, batch) = snapshot_jmt.put_value_set(vec![(key_hash, Some(b"v1".to_vec()))], 1).unwrap();
+        snapshot.write_node_batch(&batch.node_batch).unwrap();
+        snapshot.prune(&batch.stale_node_index_batch).unwrap();
+
+        assert_eq!(snapshot_jmt.get(key_hash, 1).unwrap(), Some(b"v1".to_vec()));
+        assert_eq!(jmt.get_root_hash_option(1).unwrap(), None);
+        assert_eq!(store.get_value_option(1, key_hash).unwrap(), Some(b"v0".to_vec()));
+        assert_eq!(jmt.get(key_hash, 0).unwrap(), Some(b"v0".to_vec()));
+        assert_eq!(jmt.get_root_hash(0).unwrap(), root);
+    }
+
+    #[test]
+    fn rightmost_leaf_is_last_key_in_nibble_order() {
+        let store = InMemoryTreeStore::new();
+        let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Blake2s256> = JellyfishMerkleTree::new(&store);
+        assert!(store.get_rightmost_leaf().unwrap().is_none());
+
+        let key_hashes: Vec<KeyHash> = (0..20)
+            .map(|i| KeyHash(Sha256::digest(format!("key{}", i)).into()))
+            .collect();
+        let (_root, batch) = jmt
+            .put_value_set(key_hashes.iter().map(|key_hash| (*key_hash, Some(b"value".to_vec()))), 0)
+            .unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+
+        // Nibble order over a key hash is the same as byte order
+        let expected = *key_hashes.iter().max().unwrap();
+        let (node_key, leaf) = store.get_rightmost_leaf().unwrap().unwrap();
+        assert_eq!(leaf.key_hash(), expected);
+        assert_eq!(store.get_node(&node_key).unwrap(), Node::Leaf(leaf));
+    }
+
+    #[test]
+    fn keys_without_a_shared_prefix_sit_one_nibble_below_the_root() {
+        let store = InMemoryTreeStore::new();
+        let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Blake2s256> = JellyfishMerkleTree::new(&store);
+        let mut low = [0x00; 32];
+        low[31] = 1;
+        let high = [0xf0; 32];
+
+        let (_root, batch) = jmt
+            .put_value_set(vec![(KeyHash(low), Some(b"low".to_vec())), (KeyHash(high), Some(b"high".to_vec()))], 0)
+            .unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+
+        let stats = store.tree_stats().unwrap();
+        assert_eq!(stats, TreeStats { height: 1, internal_nodes: 1, leaf_nodes: 2 });
+    }
+
+    #[test]
+    fn fanout_histogram_counts_children_per_internal_node() {
+        let store = InMemoryTreeStore::new();
+        let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Blake2s256> = JellyfishMerkleTree::new(&store);
+        // Two keys under nibble 0 that split on the second nibble, and one each under 1 and 2:
+        // the root has three children, one of them an internal node with two leaves
+        let key_hashes = [[0x00; 32], [0x01; 32], [0x10; 32], [0x20; 32]];
+
+        let (_root, batch) = jmt
+            .put_value_set(key_hashes.iter().map(|hash| (KeyHash(*hash), Some(b"value".to_vec()))), 0)
+            .unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+
+        let mut expected = [0; 17];
+        expected[2] = 1;
+        expected[3] = 1;
+        assert_eq!(store.fanout_histogram().unwrap(), expected);
+    }
+
+    #[test]
+    fn estimated_size_grows_with_every_commit() {
+        let store = InMemoryTreeStore::new();
+        let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Sha256> = JellyfishMerkleTree::new(&store);
+        let mut size = store.estimated_size_bytes().unwrap();
+        assert_eq!(size, 0);
+
+        for version in 0..20u64 {
+            let key_hash = KeyHash(Sha256::digest(version.to_le_bytes()).into());
+            let (_root, batch) = jmt.put_value_set(vec![(key_hash, Some(vec![0; 8]))], version).unwrap();
+            store.write_node_batch(&batch.node_batch).unwrap();
+
+            let grown = store.estimated_size_bytes().unwrap();
+            assert!(grown > size, "{} bytes after version {}, {} before", grown, version, size);
+            size = grown;
+        }
+    }
+
+    #[test]
+    fn version_magnitude_does_not_change_the_stored_size() {
+        let sized_from = |first: Version| {
+            let store = InMemoryTreeStore::new();
+            for i in 0..10 {
+                crate::workload::commit_streamed::<_, Sha256>(&store, crate::workload::value_set_iter(100, 8 + i), first + i as Version).unwrap();
+            }
+            store.estimated_size_bytes().unwrap()
+        };
+        assert_eq!(sized_from(Version::MAX - 10), sized_from(0));
+    }
+
+    #[test]
+    fn diff_lists_changed_keys_with_their_new_values() {
+        let store = InMemoryTreeStore::new();
+        crate::workload::commit_streamed::<_, Sha256>(&store, crate::workload::value_set_iter(100, 8), 0).unwrap();
+        let key_hash = |i| KeyHash::with::<Sha256>(crate::workload::key(i));
+        let updates = vec![
+            (key_hash(1), Some(b"updated".to_vec())),
+            (key_hash(2), Some(crate::workload::value(2, 8))),
+            (key_hash(3), None),
+            (key_hash(100), Some(b"inserted".to_vec())),
+        ];
+        crate::workload::commit_streamed::<_, Sha256>(&store, updates, 1).unwrap();
+
+        let mut expected =
+            vec![(key_hash(1), Some(b"updated".to_vec())), (key_hash(3), None), (key_hash(100), Some(b"inserted".to_vec()))];
+        expected.sort_by_key(|(key_hash, _value)| *key_hash);
+        assert_eq!(store.diff(0, 1).unwrap(), expected);
+        assert_eq!(store.diff(1, 1).unwrap(), vec![]);
+
+        let reverted: Vec<_> = store.diff(1, 0).unwrap().into_iter().map(|(key_hash, value)| (key_hash, value.is_some())).collect();
+        assert!(reverted.contains(&(key_hash(3), true)) && reverted.contains(&(key_hash(100), false)));
+    }
+
+    #[test]
+    fn integrity_check_names_a_deleted_child() {
+        let store = InMemoryTreeStore::new();
+        crate::workload::commit_streamed::<_, Sha256>(&store, crate::workload::value_set_iter(100, 8), 0).unwrap();
+        store.verify_integrity(0).unwrap();
+
+        let mut leaf_key = None;
+        store
+            .for_each_node(|node_key, node| {
+                if matches!(node, Node::Leaf(_)) {
+                    leaf_key = Some(node_key);
+                }
+            })
+            .unwrap();
+        let leaf_key = leaf_key.unwrap();
+        store.store.write().unwrap().remove(&node_storage_key(&leaf_key).unwrap());
+
+        let message = store.verify_integrity(0).unwrap_err().to_string();
+        assert!(message.contains("referenced by child"), "{}", message);
+        assert!(message.contains(&path_hex(leaf_key.nibble_path())), "{}", message);
+    }
+
+    #[test]
+    fn pruning_stale_nodes_keeps_the_latest_version_readable() {
+        let store = InMemoryTreeStore::new();
+        let jmt: JellyfishMerkleTree<'_, InMemoryTreeStore, Blake2s256> = JellyfishMerkleTree::new(&store);
+        let key_hashes: Vec<KeyHash> = (0..20)
+            .map(|i| KeyHash(Sha256::digest(format!("key{}", i)).into()))
+            .collect();
+
+        let mut stale = Vec::new();
+        for version in 0..3u8 {
+            let (_root, batch) = jmt
+                .put_value_set(key_hashes.iter().map(|key_hash| (*key_hash, Some(vec![version]))), version as Version)
+                .unwrap();
+            store.write_node_batch(&batch.node_batch).unwrap();
+            stale.extend(batch.stale_node_index_batch);
+        }
+        let before = store.tree_stats().unwrap();
+
+        assert_eq!(store.prune(&stale).unwrap(), stale.len());
+        let after = store.tree_stats().unwrap();
+        assert_eq!(
+            before.internal_nodes + before.leaf_nodes - stale.len(),
+            after.internal_nodes + after.leaf_nodes
+        );
+        for key_hash in &key_hashes {
+            assert_eq!(jmt.get_with_proof(*key_hash, 2).unwrap().0, Some(vec![2]));
+        }
+        assert!(jmt.get_with_proof(key_hashes[0], 0).is_err());
+    }
+
+    #[test]
+    fn corrupt_node_surfaces_as_a_serialization_error() {
+        let store = InMemoryTreeStore::new();
+        let node_key: NodeKey = bincode::deserialize(&[0u8; 24]).unwrap();
+        store
+            .store
+            .write()
+            .unwrap()
+            .insert(node_storage_key(&node_key).unwrap(), vec![0xff; 3]);
+
+        let error = store.get_node_option(&node_key).unwrap_err();
+        assert!(matches!(error.downcast_ref::<StoreError>(), Some(StoreError::Serialization(_))));
+    }
+
+    #[test]
+    fn node_keys_do_not_alias_value_slots() {
+        // Pick a key hash whose bytes decode as a NodeKey nibble path (32 nibbles in 16 bytes), so
+        // without prefixes the node and the value below would be stored under identical keys.
+        let version: Version = 7;
+        let mut key_hash_bytes = [0xab; 32];
+        key_hash_bytes[..16].copy_from_slice(&[32, 0, 0, 0, 0, 0, 0, 0, 16, 0, 0, 0, 0, 0, 0, 0]);
+        let key_hash = KeyHash(key_hash_bytes);
+        let unprefixed_value_key = bincode::serialize(&(version, key_hash)).unwrap();
+        let node_key: NodeKey = bincode::deserialize(&unprefixed_value_key).unwrap();
+        assert_eq!(bincode::serialize(&node_key).unwrap(), unprefixed_value_key);
+
+        let node = Node::Leaf(LeafNode::new(key_hash, jmt::ValueHash([0xcd; 32])));
+        let mut nodes = BTreeMap::new();
+        nodes.insert(node_key.clone(), node.clone());
+        let mut values = BTreeMap::new();
+        values.insert((version, key_hash), Some(b"value".to_vec()));
+
+        let store = InMemoryTreeStore::new();
+        store.write_node_batch(&NodeBatch::new(nodes, values)).unwrap();
+
+        assert_eq!(store.get_node_option(&node_key).unwrap(), Some(node));
+        assert_eq!(store.get_value_option(version, key_hash).unwrap(), Some(b"value".to_vec()));
+        assert_eq!(store.store.read().unwrap().len(), 2);
+    }
+}
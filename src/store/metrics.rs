@@ -0,0 +1,142 @@
+use jmt::storage::NodeBatch;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use super::{ReadCounts, WriteCounts};
+
+// Upper bounds of the commit latency histogram's buckets, in seconds, before `+Inf`
+const LATENCY_BUCKETS: [f64; 6] = [0.000_01, 0.000_1, 0.001, 0.01, 0.1, 1.0];
+
+/// What an `InstrumentedReader` tracks beyond its counters when the `metrics` feature is on:
+/// bytes written and a histogram of `write_node_batch` latencies.
+pub(super) struct CommitMetrics {
+    bytes_written: AtomicU64,
+    // Per bucket, not cumulative; the last one is `+Inf`
+    latency_buckets: [AtomicU64; LATENCY_BUCKETS.len() + 1],
+    latency_nanos: AtomicU64,
+}
+
+impl CommitMetrics {
+    pub(super) fn new() -> Self {
+        Self {
+            bytes_written: AtomicU64::new(0),
+            latency_buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+            latency_nanos: AtomicU64::new(0),
+        }
+    }
+
+    // Bytes are counted as bincode encodes the batch's nodes and values, whatever the store's codec
+    pub(super) fn record(&self, node_batch: &NodeBatch, latency: Duration) -> Result<(), bincode::Error> {
+        let mut bytes = 0;
+        for node in node_batch.nodes().values() {
+            bytes += bincode::serialized_size(node)?;
+        }
+        for value in node_batch.values().values() {
+            bytes += bincode::serialized_size(value)?;
+        }
+        self.bytes_written.fetch_add(bytes, Ordering::Relaxed);
+
+        let seconds = latency.as_secs_f64();
+        let bucket = LATENCY_BUCKETS.iter().position(|bound| seconds <= *bound).unwrap_or(LATENCY_BUCKETS.len());
+        self.latency_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.latency_nanos.fetch_add(latency.as_nanos() as u64, Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub(super) fn reset(&self) {
+        self.bytes_written.store(0, Ordering::Relaxed);
+        self.latency_nanos.store(0, Ordering::Relaxed);
+        for bucket in &self.latency_buckets {
+            bucket.store(0, Ordering::Relaxed);
+        }
+    }
+
+    pub(super) fn render(&self, reads: ReadCounts, writes: WriteCounts) -> String {
+        let mut text = String::new();
+        let counters = [
+            ("tree_bench_node_reads_total", "Calls to get_node_option.", reads.nodes),
+            ("tree_bench_value_reads_total", "Calls to get_value_option.", reads.values),
+            ("tree_bench_batches_written_total", "Node batches written.", writes.batches),
+            ("tree_bench_nodes_written_total", "Nodes written across all batches.", writes.nodes),
+            ("tree_bench_values_written_total", "Values written across all batches.", writes.values),
+            (
+                "tree_bench_bytes_written_total",
+                "Bincode-encoded bytes of the nodes and values written.",
+                self.bytes_written.load(Ordering::Relaxed),
+            ),
+        ];
+        for (name, help, value) in counters {
+            writeln!(text, "# HELP {} {}\n# TYPE {} counter\n{} {}", name, help, name, name, value).unwrap();
+        }
+
+        let name = "tree_bench_commit_latency_seconds";
+        writeln!(text, "# HELP {} Time spent in write_node_batch.\n# TYPE {} histogram", name, name).unwrap();
+        let mut cumulative = 0;
+        for (i, bucket) in self.latency_buckets.iter().enumerate() {
+            cumulative += bucket.load(Ordering::Relaxed);
+            match LATENCY_BUCKETS.get(i) {
+                Some(bound) => writeln!(text, "{}_bucket{{le=\"{}\"}} {}", name, bound, cumulative).unwrap(),
+                None => writeln!(text, "{}_bucket{{le=\"+Inf\"}} {}", name, cumulative).unwrap(),
+            }
+        }
+        let seconds = self.latency_nanos.load(Ordering::Relaxed) as f64 / 1e9;
+        writeln!(text, "{}_sum {}\n{}_count {}", name, seconds, name, cumulative).unwrap();
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::store::{InMemoryTreeStore, InstrumentedReader};
+    use crate::workload;
+    use jmt::{JellyfishMerkleTree, KeyHash};
+    use sha2::Sha256;
+    use std::collections::HashMap;
+
+    // Parses the text format's sample lines into `name{labels}` -> value, checking the comments
+    fn parse(text: &str) -> HashMap<String, f64> {
+        let mut samples = HashMap::new();
+        for line in text.lines() {
+            if let Some(comment) = line.strip_prefix("# ") {
+                let mut words = comment.split(' ');
+                let kind = words.next().unwrap();
+                assert!(kind == "HELP" || kind == "TYPE", "{}", line);
+                if kind == "TYPE" {
+                    assert!(matches!(words.nth(1), Some("counter" | "histogram")), "{}", line);
+                }
+                continue;
+            }
+            let (name, value) = line.rsplit_once(' ').unwrap_or_else(|| panic!("no value in `{}`", line));
+            assert!(name.starts_with("tree_bench_"), "{}", line);
+            samples.insert(name.to_string(), value.parse().unwrap_or_else(|_| panic!("bad value in `{}`", line)));
+        }
+        samples
+    }
+
+    #[test]
+    fn metrics_text_is_parseable_and_names_every_metric() {
+        let instrumented = InstrumentedReader::new(InMemoryTreeStore::new());
+        workload::commit_streamed::<_, Sha256>(&instrumented, workload::value_set_iter(100, 8), 0).unwrap();
+        workload::commit_streamed::<_, Sha256>(&instrumented, workload::value_set_iter(10, 8), 1).unwrap();
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&instrumented);
+        tree.get_with_proof(KeyHash::with::<Sha256>(workload::key(3)), 1).unwrap();
+
+        let samples = parse(&instrumented.metrics_text());
+        let counts = instrumented.counts();
+        let writes = instrumented.write_counts();
+        assert_eq!(samples["tree_bench_node_reads_total"], counts.nodes as f64);
+        assert_eq!(samples["tree_bench_value_reads_total"], counts.values as f64);
+        assert_eq!(samples["tree_bench_batches_written_total"], 2.0);
+        assert_eq!(samples["tree_bench_nodes_written_total"], writes.nodes as f64);
+        assert_eq!(samples["tree_bench_values_written_total"], 110.0);
+        assert!(samples["tree_bench_bytes_written_total"] > 110.0 * 8.0);
+        assert_eq!(samples["tree_bench_commit_latency_seconds_count"], 2.0);
+        assert_eq!(samples["tree_bench_commit_latency_seconds_bucket{le=\"+Inf\"}"], 2.0);
+        assert!(samples["tree_bench_commit_latency_seconds_sum"] > 0.0);
+
+        instrumented.reset();
+        let samples = parse(&instrumented.metrics_text());
+        assert!(samples.values().all(|value| *value == 0.0), "{:?}", samples);
+    }
+}
@@ -0,0 +1,228 @@
+use jmt::storage::{LeafNode, Node, NodeBatch, NodeKey, TreeReader, TreeWriter};
+use jmt::{KeyHash, Version};
+use memmap2::Mmap;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::RwLock;
+
+use super::{is_further_right, node_storage_key, ordered_value_storage_key, WarmUp, NODE_PREFIX};
+
+// Every record is `key_len: u32`, `value_len: u32` (little-endian), then the key and value bytes
+const HEADER_LEN: usize = 8;
+
+// Where a record's value bytes sit in the file
+#[derive(Clone, Copy)]
+struct Span {
+    offset: usize,
+    len: usize,
+}
+
+/// A persistent JMT store over one append-only file, read through a memory map. Reads come
+/// straight from the mapped pages; only an index of record offsets lives on the heap, and it
+/// is rebuilt by scanning the file on [`open`](Self::open). Nothing is fsynced, since the
+/// store is for measuring reads, not for durability.
+pub struct MmapTreeStore {
+    inner: RwLock<Mapped>,
+}
+
+struct Mapped {
+    file: File,
+    // `None` while the file is empty, which cannot be mapped
+    map: Option<Mmap>,
+    len: usize,
+    nodes: HashMap<Vec<u8>, Span>,
+    // Ordered like the sorted backends, so the newest version at or below a bound is one seek
+    values: BTreeMap<Vec<u8>, Span>,
+}
+
+impl MmapTreeStore {
+    /// Opens the store file at `path`, creating it if needed. A record cut short by a crash
+    /// mid-append is dropped from the end of the file.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let mut file = OpenOptions::new().read(true).append(true).create(true).open(path)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+
+        let mut mapped = Mapped { file, map: None, len: 0, nodes: HashMap::new(), values: BTreeMap::new() };
+        while let Some((key, span)) = read_record(&bytes, mapped.len) {
+            mapped.index(key.to_vec(), span);
+            mapped.len = span.offset + span.len;
+        }
+        if mapped.len < bytes.len() {
+            mapped.file.set_len(mapped.len as u64)?;
+        }
+        mapped.remap()?;
+        Ok(Self { inner: RwLock::new(mapped) })
+    }
+
+    fn read<T>(&self, f: impl FnOnce(&Mapped) -> Result<T, anyhow::Error>) -> Result<T, anyhow::Error> {
+        let mapped = self.inner.read().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        f(&mapped)
+    }
+}
+
+fn read_record(bytes: &[u8], offset: usize) -> Option<(&[u8], Span)> {
+    let header = bytes.get(offset..offset + HEADER_LEN)?;
+    let key_len = u32::from_le_bytes(header[..4].try_into().unwrap()) as usize;
+    let value_len = u32::from_le_bytes(header[4..].try_into().unwrap()) as usize;
+    let key_start = offset + HEADER_LEN;
+    let key = bytes.get(key_start..key_start + key_len)?;
+    let span = Span { offset: key_start + key_len, len: value_len };
+    bytes.get(span.offset..span.offset + span.len)?;
+    Some((key, span))
+}
+
+impl Mapped {
+    fn index(&mut self, key: Vec<u8>, span: Span) {
+        if key.first() == Some(&NODE_PREFIX) {
+            self.nodes.insert(key, span);
+        } else {
+            self.values.insert(key, span);
+        }
+    }
+
+    fn remap(&mut self) -> Result<(), anyhow::Error> {
+        // SAFETY: the file is only ever appended to, and only through this store, so the
+        // mapped bytes are never modified or truncated while the map is alive
+        self.map = if self.len == 0 { None } else { Some(unsafe { Mmap::map(&self.file)? }) };
+        Ok(())
+    }
+
+    fn bytes(&self, span: Span) -> &[u8] {
+        match &self.map {
+            Some(map) => &map[span.offset..span.offset + span.len],
+            None => &[],
+        }
+    }
+}
+
+// Pages are at least this large on every platform the store runs on
+const PAGE_LEN: usize = 4096;
+
+impl WarmUp for MmapTreeStore {
+    /// Touches one byte of every mapped page, so reads do not fault pages in while being timed.
+    fn warm_up(&self) -> Result<(), anyhow::Error> {
+        self.read(|mapped| {
+            if let Some(map) = &mapped.map {
+                let touched = map.iter().step_by(PAGE_LEN).fold(0u8, |acc, byte| acc ^ byte);
+                std::hint::black_box(touched);
+            }
+            Ok(())
+        })
+    }
+}
+
+impl TreeReader for MmapTreeStore {
+    fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node>, anyhow::Error> {
+        let key = node_storage_key(node_key)?;
+        self.read(|mapped| match mapped.nodes.get(&key) {
+            Some(span) => Ok(Some(bincode::deserialize(mapped.bytes(*span))?)),
+            None => Ok(None),
+        })
+    }
+
+    fn get_value_option(
+        &self,
+        max_version: Version,
+        key_hash: KeyHash,
+    ) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        let lowest = ordered_value_storage_key(0, key_hash);
+        let highest = ordered_value_storage_key(max_version, key_hash);
+        self.read(|mapped| match mapped.values.range(lowest..=highest).next_back() {
+            Some((_key, span)) => Ok(bincode::deserialize(mapped.bytes(*span))?),
+            None => Ok(None),
+        })
+    }
+
+    fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode)>, anyhow::Error> {
+        self.read(|mapped| {
+            let mut rightmost: Option<(NodeKey, LeafNode)> = None;
+            for (key_bytes, span) in &mapped.nodes {
+                let leaf = match bincode::deserialize(mapped.bytes(*span))? {
+                    Node::Leaf(leaf) => leaf,
+                    _ => continue,
+                };
+                let node_key: NodeKey = bincode::deserialize(&key_bytes[1..])?;
+                if is_further_right(&node_key, &leaf, rightmost.as_ref()) {
+                    rightmost = Some((node_key, leaf));
+                }
+            }
+            Ok(rightmost)
+        })
+    }
+}
+
+impl TreeWriter for MmapTreeStore {
+    /// Appends the whole batch with one write, then remaps the file and indexes the new records
+    /// under the same write lock, so readers see all of the batch or none of it. A failed
+    /// append is cut back off the file and leaves the index untouched.
+    fn write_node_batch(&self, node_batch: &NodeBatch) -> Result<(), anyhow::Error> {
+        let mut records = Vec::new();
+        for (node_key, node) in node_batch.nodes() {
+            records.push((node_storage_key(node_key)?, bincode::serialize(node)?));
+        }
+        for ((version, key_hash), value_option) in node_batch.values() {
+            records.push((ordered_value_storage_key(*version, *key_hash), bincode::serialize(value_option)?));
+        }
+
+        let mut mapped = self.inner.write().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        let mut buffer = Vec::new();
+        let mut spans = Vec::with_capacity(records.len());
+        for (key, value) in &records {
+            buffer.extend_from_slice(&u32::try_from(key.len())?.to_le_bytes());
+            buffer.extend_from_slice(&u32::try_from(value.len())?.to_le_bytes());
+            buffer.extend_from_slice(key);
+            let offset = mapped.len + buffer.len();
+            buffer.extend_from_slice(value);
+            spans.push(Span { offset, len: value.len() });
+        }
+
+        if let Err(e) = mapped.file.write_all(&buffer) {
+            mapped.file.set_len(mapped.len as u64)?;
+            return Err(e.into());
+        }
+        mapped.len += buffer.len();
+        mapped.remap()?;
+        for ((key, _value), span) in records.into_iter().zip(spans) {
+            mapped.index(key, span);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jmt::JellyfishMerkleTree;
+    use sha2::Sha256;
+    use tempfile::TempDir;
+
+    #[test]
+    fn reopened_store_serves_the_same_tree() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("tree");
+        let key_hash = KeyHash::with::<Sha256>(crate::workload::key(7));
+        let root = {
+            let store = MmapTreeStore::open(&path).unwrap();
+            assert_eq!(store.get_rightmost_leaf().unwrap(), None);
+            store.warm_up().unwrap();
+            for version in 0..3 {
+                let value_set = crate::workload::value_set_iter(50, 8)
+                    .map(|(key_hash, _value)| (key_hash, Some(crate::workload::value(version, 8))));
+                crate::workload::commit_streamed::<_, Sha256>(&store, value_set, version as Version).unwrap();
+            }
+            assert_eq!(store.get_value_option(0, key_hash).unwrap(), Some(crate::workload::value(0, 8)));
+            JellyfishMerkleTree::<_, Sha256>::new(&store).get_root_hash(2).unwrap()
+        };
+
+        let store = MmapTreeStore::open(&path).unwrap();
+        store.warm_up().unwrap();
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&store);
+        assert_eq!(tree.get_root_hash(2).unwrap(), root);
+        let (value, proof) = tree.get_with_proof(key_hash, 1).unwrap();
+        assert_eq!(value, Some(crate::workload::value(1, 8)));
+        proof.verify(tree.get_root_hash(1).unwrap(), key_hash, value).unwrap();
+    }
+}
@@ -0,0 +1,143 @@
+//! Storage backends implementing the JMT `TreeReader`/`TreeWriter` traits.
+
+use jmt::storage::{LeafNode, Node, NodeKey};
+use jmt::{KeyHash, Version};
+use std::cmp::Ordering;
+
+#[cfg(feature = "tokio")]
+mod async_reader;
+mod batch;
+mod bounded;
+mod boxed;
+mod btree;
+mod buffered;
+mod cache;
+mod codec;
+#[cfg(feature = "zstd")]
+mod compress;
+#[cfg(feature = "dashmap")]
+mod concurrent;
+mod dedup;
+mod error;
+mod export;
+mod instrumented;
+mod key_hash;
+mod latency;
+mod memory;
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(feature = "memmap")]
+mod mmap;
+mod preimage;
+mod recording;
+mod value;
+#[cfg(feature = "rocksdb")]
+mod rocksdb;
+mod sharded;
+#[cfg(feature = "sled")]
+mod sled;
+mod warm_up;
+
+#[cfg(feature = "tokio")]
+pub use async_reader::{get_with_proof_async, AsyncTreeReader, BlockingReader};
+pub use batch::get_many_with_proof;
+pub use bounded::BoundedTreeStore;
+pub use boxed::{BoxedStore, TreeStore};
+pub use btree::BTreeMapTreeStore;
+pub use buffered::BufferedTreeStore;
+pub use cache::CachingReader;
+#[cfg(feature = "postcard")]
+pub use codec::Postcard;
+pub use codec::{Bincode, NodeCodec};
+#[cfg(feature = "zstd")]
+pub use compress::CompressingTreeStore;
+#[cfg(feature = "dashmap")]
+pub use concurrent::ConcurrentTreeStore;
+pub use dedup::dedup_value_set;
+pub use error::StoreError;
+pub use export::{export_leaves, LeafChunks};
+pub use instrumented::{InstrumentedReader, ReadCounts, WriteCounts};
+pub use key_hash::hash_keys;
+#[cfg(feature = "rayon")]
+pub use key_hash::par_hash_keys;
+pub use latency::LatencyTreeStore;
+pub use memory::InMemoryTreeStore;
+#[cfg(feature = "memmap")]
+pub use mmap::MmapTreeStore;
+pub use preimage::PreimageStore;
+pub use recording::{replay, RecordedOp, RecordingTreeStore};
+pub use value::ValueStore;
+#[cfg(feature = "rocksdb")]
+pub use self::rocksdb::RocksDbTreeStore;
+pub use sharded::ShardedTreeStore;
+#[cfg(feature = "sled")]
+pub use self::sled::SledTreeStore;
+pub use warm_up::WarmUp;
+
+pub use jmt::storage::{TreeReader, TreeWriter};
+
+/// Structural metrics over every node a store holds, across all versions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize)]
+pub struct TreeStats {
+    /// The longest nibble path of any stored node.
+    pub height: usize,
+    pub internal_nodes: usize,
+    pub leaf_nodes: usize,
+}
+
+impl TreeStats {
+    fn record(&mut self, node_key: &NodeKey, node: &Node) {
+        match node {
+            Node::Internal(_) => self.internal_nodes += 1,
+            Node::Leaf(_) => self.leaf_nodes += 1,
+            // Only the root of an empty tree is null, and it says nothing about shape
+            Node::Null => return,
+        }
+        self.height = self.height.max(node_key.nibble_path().num_nibbles());
+    }
+}
+
+// Every stored key carries a namespace prefix, so nodes and values can never collide even
+// when a backend keeps them in one keyspace
+const NODE_PREFIX: u8 = b'n';
+const VALUE_PREFIX: u8 = b'v';
+
+fn node_storage_key(node_key: &NodeKey) -> Result<Vec<u8>, bincode::Error> {
+    let mut key = vec![NODE_PREFIX];
+    bincode::serialize_into(&mut key, node_key)?;
+    Ok(key)
+}
+
+fn value_storage_key(version: Version, key_hash: KeyHash) -> Result<Vec<u8>, bincode::Error> {
+    let mut key = vec![VALUE_PREFIX];
+    bincode::serialize_into(&mut key, &(version, key_hash))?;
+    Ok(key)
+}
+
+// Sorted backends key values by key hash and then big-endian version, so the newest version
+// at or below a bound is a single reverse seek away.
+fn ordered_value_storage_key(version: Version, key_hash: KeyHash) -> Vec<u8> {
+    let mut key = Vec::with_capacity(1 + 32 + 8);
+    key.push(VALUE_PREFIX);
+    key.extend_from_slice(&key_hash.0);
+    key.extend_from_slice(&version.to_be_bytes());
+    key
+}
+
+// Whether `leaf` at `node_key` lies to the right of the best candidate so far. `NibblePath`'s
+// derived ordering compares lengths first, so paths are compared nibble by nibble. Ties go to
+// the greater key hash, then to the newer version of the same leaf.
+fn is_further_right(node_key: &NodeKey, leaf: &LeafNode, best: Option<&(NodeKey, LeafNode)>) -> bool {
+    match best {
+        None => true,
+        Some((best_key, best_leaf)) => match node_key
+            .nibble_path()
+            .nibbles()
+            .cmp(best_key.nibble_path().nibbles())
+        {
+            Ordering::Greater => true,
+            Ordering::Equal => (leaf.key_hash(), node_key.version()) > (best_leaf.key_hash(), best_key.version()),
+            Ordering::Less => false,
+        },
+    }
+}
@@ -0,0 +1,90 @@
+use jmt::storage::{HasPreimage, LeafNode, Node, NodeBatch, NodeKey, TreeReader, TreeWriter};
+use jmt::{KeyHash, Version};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use super::WarmUp;
+
+/// Wraps any store with a map from key hash back to the key it was computed from. Node
+/// batches carry only hashes, so callers record each key as they insert it; the preimages are
+/// what lets the tree build ICS23 exclusion proofs.
+pub struct PreimageStore<S> {
+    inner: S,
+    preimages: RwLock<HashMap<KeyHash, Vec<u8>>>,
+}
+
+impl<S> PreimageStore<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            preimages: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Remembers `key` as the preimage of `key_hash`.
+    pub fn record_preimage(&self, key_hash: KeyHash, key: Vec<u8>) -> Result<(), anyhow::Error> {
+        let mut preimages = self.preimages.write().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        preimages.insert(key_hash, key);
+        Ok(())
+    }
+}
+
+impl<S> HasPreimage for PreimageStore<S> {
+    fn preimage(&self, key_hash: KeyHash) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        let preimages = self.preimages.read().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        Ok(preimages.get(&key_hash).cloned())
+    }
+}
+
+impl<S: TreeReader> TreeReader for PreimageStore<S> {
+    fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node>, anyhow::Error> {
+        self.inner.get_node_option(node_key)
+    }
+
+    fn get_value_option(
+        &self,
+        max_version: Version,
+        key_hash: KeyHash,
+    ) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        self.inner.get_value_option(max_version, key_hash)
+    }
+
+    fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode)>, anyhow::Error> {
+        self.inner.get_rightmost_leaf()
+    }
+}
+
+impl<S: WarmUp> WarmUp for PreimageStore<S> {
+    fn warm_up(&self) -> Result<(), anyhow::Error> {
+        self.inner.warm_up()
+    }
+}
+
+impl<S: TreeWriter> TreeWriter for PreimageStore<S> {
+    fn write_node_batch(&self, node_batch: &NodeBatch) -> Result<(), anyhow::Error> {
+        self.inner.write_node_batch(node_batch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::InMemoryTreeStore;
+    use jmt::JellyfishMerkleTree;
+    use sha2::Sha256;
+
+    #[test]
+    fn records_preimages_and_delegates_storage() {
+        let store = PreimageStore::new(InMemoryTreeStore::new());
+        let key_hash = KeyHash::with::<Sha256>(b"key");
+        store.record_preimage(key_hash, b"key".to_vec()).unwrap();
+
+        let jmt: JellyfishMerkleTree<'_, _, Sha256> = JellyfishMerkleTree::new(&store);
+        let (_root, batch) = jmt.put_value_set(vec![(key_hash, Some(b"value".to_vec()))], 0).unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+
+        assert_eq!(store.preimage(key_hash).unwrap(), Some(b"key".to_vec()));
+        assert_eq!(store.preimage(KeyHash::with::<Sha256>(b"other")).unwrap(), None);
+        assert_eq!(jmt.get(key_hash, 0).unwrap(), Some(b"value".to_vec()));
+    }
+}
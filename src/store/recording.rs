@@ -0,0 +1,128 @@
+use jmt::storage::{LeafNode, Node, NodeBatch, NodeKey, TreeReader, TreeWriter};
+use jmt::{KeyHash, Version};
+use std::sync::Mutex;
+
+use super::WarmUp;
+
+/// One store call made through a [`RecordingTreeStore`], with the arguments needed to make it
+/// again.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RecordedOp {
+    GetNode(NodeKey),
+    GetValue { max_version: Version, key_hash: KeyHash },
+    WriteNodeBatch(NodeBatch),
+}
+
+/// Logs every node read, value read and node batch written through any store, in the order
+/// the calls were made, so an interesting sequence can be captured once and [`replay`]ed
+/// against another store. `get_rightmost_leaf` is passed through unlogged, since only
+/// restores call it. Every written batch is cloned into the log, so a long recording holds a
+/// copy of everything it wrote.
+pub struct RecordingTreeStore<R> {
+    inner: R,
+    log: Mutex<Vec<RecordedOp>>,
+}
+
+impl<R> RecordingTreeStore<R> {
+    pub fn new(inner: R) -> Self {
+        Self { inner, log: Mutex::new(Vec::new()) }
+    }
+
+    /// The wrapped store, for calls that should not be logged.
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// Takes the calls logged so far, leaving the log empty.
+    pub fn drain_log(&self) -> Result<Vec<RecordedOp>, anyhow::Error> {
+        let mut log = self.log.lock().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        Ok(std::mem::take(&mut *log))
+    }
+
+    fn record(&self, op: RecordedOp) -> Result<(), anyhow::Error> {
+        self.log.lock().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?.push(op);
+        Ok(())
+    }
+}
+
+/// Makes every call in `ops` against `store`, in order. Reads are made and their results
+/// dropped, so replaying a log costs the store what the original run cost it, without the
+/// hashing and tree logic of the run that produced the log.
+pub fn replay<S: TreeReader + TreeWriter>(store: &S, ops: &[RecordedOp]) -> Result<(), anyhow::Error> {
+    for op in ops {
+        match op {
+            RecordedOp::GetNode(node_key) => {
+                store.get_node_option(node_key)?;
+            }
+            RecordedOp::GetValue { max_version, key_hash } => {
+                store.get_value_option(*max_version, *key_hash)?;
+            }
+            RecordedOp::WriteNodeBatch(node_batch) => store.write_node_batch(node_batch)?,
+        }
+    }
+    Ok(())
+}
+
+impl<R: TreeReader> TreeReader for RecordingTreeStore<R> {
+    fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node>, anyhow::Error> {
+        self.record(RecordedOp::GetNode(node_key.clone()))?;
+        self.inner.get_node_option(node_key)
+    }
+
+    fn get_value_option(
+        &self,
+        max_version: Version,
+        key_hash: KeyHash,
+    ) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        self.record(RecordedOp::GetValue { max_version, key_hash })?;
+        self.inner.get_value_option(max_version, key_hash)
+    }
+
+    fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode)>, anyhow::Error> {
+        self.inner.get_rightmost_leaf()
+    }
+}
+
+impl<R: TreeWriter> TreeWriter for RecordingTreeStore<R> {
+    fn write_node_batch(&self, node_batch: &NodeBatch) -> Result<(), anyhow::Error> {
+        self.record(RecordedOp::WriteNodeBatch(node_batch.clone()))?;
+        self.inner.write_node_batch(node_batch)
+    }
+}
+
+impl<R: WarmUp> WarmUp for RecordingTreeStore<R> {
+    fn warm_up(&self) -> Result<(), anyhow::Error> {
+        self.inner.warm_up()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::InMemoryTreeStore;
+    use crate::workload;
+    use jmt::JellyfishMerkleTree;
+    use sha2::Sha256;
+
+    #[test]
+    fn replay_rebuilds_the_recorded_tree() {
+        let recording = RecordingTreeStore::new(InMemoryTreeStore::new());
+        workload::commit_streamed::<_, Sha256>(&recording, workload::value_set_iter(100, 8), 0).unwrap();
+        let key_hash = KeyHash::with::<Sha256>(workload::key(7));
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&recording);
+        tree.get_with_proof(key_hash, 0).unwrap();
+        workload::commit_streamed::<_, Sha256>(&recording, vec![(key_hash, None)], 1).unwrap();
+
+        let log = recording.drain_log().unwrap();
+        assert!(recording.drain_log().unwrap().is_empty());
+        let writes = log.iter().filter(|op| matches!(op, RecordedOp::WriteNodeBatch(_))).count();
+        assert_eq!(writes, 2);
+        assert!(log.contains(&RecordedOp::GetValue { max_version: 0, key_hash }));
+
+        let replayed = InMemoryTreeStore::new();
+        replay(&replayed, &log).unwrap();
+        let replayed_tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&replayed);
+        assert_eq!(replayed_tree.get_root_hash(1).unwrap(), tree.get_root_hash(1).unwrap());
+        assert_eq!(replayed.tree_stats().unwrap(), recording.inner().tree_stats().unwrap());
+    }
+}
@@ -0,0 +1,126 @@
+use jmt::storage::{LeafNode, Node, NodeBatch, NodeKey, TreeReader, TreeWriter};
+use jmt::{KeyHash, Version};
+use rocksdb::{ColumnFamily, ColumnFamilyDescriptor, Direction, IteratorMode, Options, WriteBatch, DB};
+use std::path::Path;
+
+use super::{is_further_right, node_storage_key, ordered_value_storage_key, WarmUp, NODE_PREFIX};
+
+const NODES_CF: &str = "nodes";
+const VALUES_CF: &str = "values";
+
+/// A persistent JMT store on RocksDB, with nodes and values in separate column families.
+pub struct RocksDbTreeStore {
+    db: DB,
+}
+
+impl RocksDbTreeStore {
+    /// Opens the database at `path`, creating it and its column families if needed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        options.create_missing_column_families(true);
+        let column_families = [NODES_CF, VALUES_CF]
+            .map(|name| ColumnFamilyDescriptor::new(name, Options::default()));
+        let db = DB::open_cf_descriptors(&options, path, column_families)?;
+        Ok(Self { db })
+    }
+
+    fn column_family(&self, name: &str) -> Result<&ColumnFamily, anyhow::Error> {
+        self.db
+            .cf_handle(name)
+            .ok_or_else(|| anyhow::anyhow!("Missing column family {}", name))
+    }
+}
+
+impl WarmUp for RocksDbTreeStore {
+    /// Scans both column families once, loading their blocks into RocksDB's block cache and
+    /// opening every table file before anything is timed.
+    fn warm_up(&self) -> Result<(), anyhow::Error> {
+        for name in [NODES_CF, VALUES_CF] {
+            for item in self.db.iterator_cf(self.column_family(name)?, IteratorMode::Start) {
+                item?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TreeReader for RocksDbTreeStore {
+    fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node>, anyhow::Error> {
+        let nodes = self.column_family(NODES_CF)?;
+        match self.db.get_cf(nodes, node_storage_key(node_key)?)? {
+            Some(bytes) => Ok(Some(bincode::deserialize(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn get_value_option(
+        &self,
+        max_version: Version,
+        key_hash: KeyHash,
+    ) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        let values = self.column_family(VALUES_CF)?;
+        let seek_key = ordered_value_storage_key(max_version, key_hash);
+        let mut iter = self
+            .db
+            .iterator_cf(values, IteratorMode::From(&seek_key, Direction::Reverse));
+        match iter.next() {
+            // The seek can land on an older key hash when this one has no version <= max_version
+            Some(item) => {
+                let (key, bytes) = item?;
+                if key.len() == seek_key.len() && key[..33] == seek_key[..33] {
+                    Ok(bincode::deserialize(&bytes)?)
+                } else {
+                    Ok(None)
+                }
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode)>, anyhow::Error> {
+        let nodes = self.column_family(NODES_CF)?;
+
+        let mut rightmost: Option<(NodeKey, LeafNode)> = None;
+        for item in self.db.iterator_cf(nodes, IteratorMode::Start) {
+            let (key_bytes, node_bytes) = item?;
+            let node_key_bytes = match key_bytes.split_first() {
+                Some((&NODE_PREFIX, rest)) => rest,
+                _ => continue,
+            };
+            let leaf = match bincode::deserialize(&node_bytes)? {
+                Node::Leaf(leaf) => leaf,
+                _ => continue,
+            };
+            let node_key: NodeKey = bincode::deserialize(node_key_bytes)?;
+            if is_further_right(&node_key, &leaf, rightmost.as_ref()) {
+                rightmost = Some((node_key, leaf));
+            }
+        }
+
+        Ok(rightmost)
+    }
+}
+
+impl TreeWriter for RocksDbTreeStore {
+    fn write_node_batch(&self, node_batch: &NodeBatch) -> Result<(), anyhow::Error> {
+        let nodes = self.column_family(NODES_CF)?;
+        let values = self.column_family(VALUES_CF)?;
+
+        // One WriteBatch per NodeBatch, so a commit is either fully visible or not at all
+        let mut batch = WriteBatch::default();
+        for (node_key, node) in node_batch.nodes() {
+            batch.put_cf(nodes, node_storage_key(node_key)?, bincode::serialize(node)?);
+        }
+        for ((version, key_hash), value_option) in node_batch.values() {
+            batch.put_cf(
+                values,
+                ordered_value_storage_key(*version, *key_hash),
+                bincode::serialize(value_option)?,
+            );
+        }
+        self.db.write(batch)?;
+
+        Ok(())
+    }
+}
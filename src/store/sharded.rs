@@ -0,0 +1,181 @@
+use jmt::storage::{LeafNode, Node, NodeBatch, NodeKey, TreeReader, TreeWriter};
+use jmt::{KeyHash, Version};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::RwLock;
+
+use super::{is_further_right, node_storage_key, WarmUp};
+
+// One shard per value of a node path's leading nibble
+const SHARDS: usize = 16;
+
+#[derive(Default)]
+struct Shard {
+    nodes: HashMap<Vec<u8>, Vec<u8>>,
+    // Encoded values and tombstones by key hash, then by the version they were written at
+    values: HashMap<KeyHash, BTreeMap<Version, Vec<u8>>>,
+}
+
+/// An in-memory store split into 16 shards by the leading nibble of each node's path, each
+/// behind its own `RwLock`, so readers and writers working in different subtrees don't wait on
+/// each other. The root, whose path is empty, lives in shard 0. Values go to the shard of
+/// their key hash's leading nibble, the same one that holds their leaves. Nodes and values are
+/// encoded as `InMemoryTreeStore` encodes them.
+pub struct ShardedTreeStore {
+    shards: [RwLock<Shard>; SHARDS],
+}
+
+impl Default for ShardedTreeStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShardedTreeStore {
+    pub fn new() -> Self {
+        Self {
+            shards: std::array::from_fn(|_| RwLock::new(Shard::default())),
+        }
+    }
+}
+
+fn node_shard(node_key: &NodeKey) -> usize {
+    node_key.nibble_path().nibbles().next().map_or(0, |nibble| u8::from(nibble) as usize)
+}
+
+fn value_shard(key_hash: KeyHash) -> usize {
+    (key_hash.0[0] >> 4) as usize
+}
+
+impl TreeReader for ShardedTreeStore {
+    fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node>, anyhow::Error> {
+        let shard = self.shards[node_shard(node_key)]
+            .read()
+            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        match shard.nodes.get(&node_storage_key(node_key)?) {
+            Some(bytes) => Ok(Some(bincode::deserialize(bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn get_value_option(
+        &self,
+        max_version: Version,
+        key_hash: KeyHash,
+    ) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        let shard = self.shards[value_shard(key_hash)]
+            .read()
+            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        // The value visible at `max_version` is the one written at the greatest version <= it
+        match shard
+            .values
+            .get(&key_hash)
+            .and_then(|versions| versions.range(..=max_version).next_back())
+        {
+            Some((_version, bytes)) => Ok(bincode::deserialize(bytes)?),
+            None => Ok(None),
+        }
+    }
+
+    /// Every path in a shard lies to the right of every path in the shards below it, so the
+    /// shards are scanned from the highest nibble down, and the first one holding a leaf has
+    /// the answer. Only that shard, and any empty ones above it, are read.
+    fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode)>, anyhow::Error> {
+        for shard in self.shards.iter().rev() {
+            let shard = shard.read().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+            let mut rightmost: Option<(NodeKey, LeafNode)> = None;
+            for (key_bytes, node_bytes) in &shard.nodes {
+                let leaf = match bincode::deserialize(node_bytes)? {
+                    Node::Leaf(leaf) => leaf,
+                    _ => continue,
+                };
+                // Shards hold nothing but nodes, each key behind its one-byte prefix
+                let node_key: NodeKey = bincode::deserialize(&key_bytes[1..])?;
+                if is_further_right(&node_key, &leaf, rightmost.as_ref()) {
+                    rightmost = Some((node_key, leaf));
+                }
+            }
+            if rightmost.is_some() {
+                return Ok(rightmost);
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl WarmUp for ShardedTreeStore {}
+
+impl TreeWriter for ShardedTreeStore {
+    /// Splits the batch by shard and locks each shard once. Shard 0 goes last, so a new root
+    /// only becomes visible once every node below it is stored.
+    fn write_node_batch(&self, node_batch: &NodeBatch) -> Result<(), anyhow::Error> {
+        let mut nodes: [Vec<(Vec<u8>, Vec<u8>)>; SHARDS] = Default::default();
+        for (node_key, node) in node_batch.nodes() {
+            nodes[node_shard(node_key)].push((node_storage_key(node_key)?, bincode::serialize(node)?));
+        }
+        let mut values: [Vec<(KeyHash, Version, Vec<u8>)>; SHARDS] = Default::default();
+        for ((version, key_hash), value_option) in node_batch.values() {
+            values[value_shard(*key_hash)].push((*key_hash, *version, bincode::serialize(value_option)?));
+        }
+
+        for (i, (nodes, values)) in nodes.into_iter().zip(values).enumerate().rev() {
+            if nodes.is_empty() && values.is_empty() {
+                continue;
+            }
+            let mut shard = self.shards[i].write().map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+            shard.nodes.extend(nodes);
+            for (key_hash, version, bytes) in values {
+                shard.values.entry(key_hash).or_default().insert(version, bytes);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::InMemoryTreeStore;
+    use crate::workload;
+    use jmt::JellyfishMerkleTree;
+    use sha2::Sha256;
+
+    #[test]
+    fn spreads_a_tree_over_every_shard_and_reads_it_back() {
+        let sharded = ShardedTreeStore::new();
+        let memory = InMemoryTreeStore::new();
+        let root = workload::commit_streamed::<_, Sha256>(&sharded, workload::value_set_iter(1000, 8), 0).unwrap();
+        assert_eq!(workload::commit_streamed::<_, Sha256>(&memory, workload::value_set_iter(1000, 8), 0).unwrap(), root);
+        for shard in &sharded.shards {
+            assert!(!shard.read().unwrap().nodes.is_empty());
+        }
+
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&sharded);
+        for i in [0, 500, 999] {
+            let key_hash = KeyHash::with::<Sha256>(workload::key(i));
+            let (value, proof) = tree.get_with_proof(key_hash, 0).unwrap();
+            assert_eq!(value, Some(workload::value(i, 8)));
+            proof.verify(root, key_hash, value).unwrap();
+        }
+        assert_eq!(sharded.get_rightmost_leaf().unwrap(), memory.get_rightmost_leaf().unwrap());
+    }
+
+    #[test]
+    fn rightmost_leaf_falls_through_empty_high_shards() {
+        let sharded = ShardedTreeStore::new();
+        let memory = InMemoryTreeStore::new();
+        // Keys whose hashes start with nibbles 0 to 3 only, so shards 4 to 15 stay empty
+        let value_set: Vec<_> = (0..)
+            .map(|i| (KeyHash::with::<Sha256>(workload::key(i)), Some(workload::value(i, 8))))
+            .filter(|(key_hash, _value)| key_hash.0[0] >> 4 < 4)
+            .take(20)
+            .collect();
+        workload::commit_streamed::<_, Sha256>(&sharded, value_set.clone(), 0).unwrap();
+        workload::commit_streamed::<_, Sha256>(&memory, value_set, 0).unwrap();
+
+        assert!(sharded.shards[4..].iter().all(|shard| shard.read().unwrap().nodes.is_empty()));
+        let rightmost = sharded.get_rightmost_leaf().unwrap();
+        assert_eq!(rightmost, memory.get_rightmost_leaf().unwrap());
+        assert_eq!(rightmost.unwrap().1.key_hash().0[0] >> 4, 3);
+    }
+}
@@ -0,0 +1,153 @@
+use jmt::storage::{LeafNode, Node, NodeBatch, NodeKey, TreeReader, TreeWriter};
+use jmt::{KeyHash, Version};
+use sled::{Batch, Db, Tree};
+use std::path::Path;
+
+use super::{is_further_right, node_storage_key, ordered_value_storage_key, WarmUp, NODE_PREFIX};
+
+/// A persistent, pure-Rust JMT store on sled, with nodes and values in separate trees.
+pub struct SledTreeStore {
+    db: Db,
+    nodes: Tree,
+    values: Tree,
+}
+
+impl SledTreeStore {
+    /// Opens the database at `path`, creating it if needed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let db = sled::open(path)?;
+        let nodes = db.open_tree("nodes")?;
+        let values = db.open_tree("values")?;
+        Ok(Self { db, nodes, values })
+    }
+}
+
+impl WarmUp for SledTreeStore {
+    /// Reads every node and value once, so they sit in sled's page cache before anything is timed.
+    fn warm_up(&self) -> Result<(), anyhow::Error> {
+        for item in self.nodes.iter().chain(self.values.iter()) {
+            item?;
+        }
+        Ok(())
+    }
+}
+
+impl TreeReader for SledTreeStore {
+    fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node>, anyhow::Error> {
+        match self.nodes.get(node_storage_key(node_key)?)? {
+            Some(bytes) => Ok(Some(bincode::deserialize(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn get_value_option(
+        &self,
+        max_version: Version,
+        key_hash: KeyHash,
+    ) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        let lowest = ordered_value_storage_key(0, key_hash);
+        let highest = ordered_value_storage_key(max_version, key_hash);
+        match self.values.range(lowest..=highest).next_back() {
+            Some(item) => {
+                let (_key, bytes) = item?;
+                Ok(bincode::deserialize(&bytes)?)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode)>, anyhow::Error> {
+        let mut rightmost: Option<(NodeKey, LeafNode)> = None;
+        for item in self.nodes.iter() {
+            let (key_bytes, node_bytes) = item?;
+            let node_key_bytes = match key_bytes.split_first() {
+                Some((&NODE_PREFIX, rest)) => rest,
+                _ => continue,
+            };
+            let leaf = match bincode::deserialize(&node_bytes)? {
+                Node::Leaf(leaf) => leaf,
+                _ => continue,
+            };
+            let node_key: NodeKey = bincode::deserialize(node_key_bytes)?;
+            if is_further_right(&node_key, &leaf, rightmost.as_ref()) {
+                rightmost = Some((node_key, leaf));
+            }
+        }
+
+        Ok(rightmost)
+    }
+}
+
+impl TreeWriter for SledTreeStore {
+    fn write_node_batch(&self, node_batch: &NodeBatch) -> Result<(), anyhow::Error> {
+        let mut nodes = Batch::default();
+        for (node_key, node) in node_batch.nodes() {
+            nodes.insert(node_storage_key(node_key)?, bincode::serialize(node)?);
+        }
+        let mut values = Batch::default();
+        for ((version, key_hash), value_option) in node_batch.values() {
+            values.insert(
+                ordered_value_storage_key(*version, *key_hash),
+                bincode::serialize(value_option)?,
+            );
+        }
+
+        self.nodes.apply_batch(nodes)?;
+        self.values.apply_batch(values)?;
+        self.db.flush()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jmt::JellyfishMerkleTree;
+    use sha2::Sha256;
+    use tempfile::TempDir;
+
+    #[test]
+    fn node_and_value_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let store = SledTreeStore::open(dir.path()).unwrap();
+        let key_hash = KeyHash::with::<Sha256>(b"key");
+
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&store);
+        let (_root, batch) = tree
+            .put_value_set(vec![(key_hash, Some(b"value".to_vec()))], 3)
+            .unwrap();
+        store.write_node_batch(&batch.node_batch).unwrap();
+
+        for (node_key, node) in batch.node_batch.nodes() {
+            assert_eq!(store.get_node_option(node_key).unwrap().as_ref(), Some(node));
+        }
+        assert_eq!(store.get_value_option(2, key_hash).unwrap(), None);
+        assert_eq!(
+            store.get_value_option(3, key_hash).unwrap(),
+            Some(b"value".to_vec())
+        );
+        assert_eq!(
+            store.get_value_option(9, KeyHash::with::<Sha256>(b"other")).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn warm_up_is_idempotent() {
+        let dir = TempDir::new().unwrap();
+        let store = SledTreeStore::open(dir.path()).unwrap();
+        store.warm_up().unwrap();
+        let root = crate::workload::commit_streamed::<_, Sha256>(&store, crate::workload::value_set_iter(100, 8), 0).unwrap();
+        let key_hash = KeyHash::with::<Sha256>(crate::workload::key(42));
+        let rightmost = store.get_rightmost_leaf().unwrap();
+
+        for _ in 0..3 {
+            store.warm_up().unwrap();
+            let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&store);
+            assert_eq!(tree.get_root_hash(0).unwrap(), root);
+            assert_eq!(tree.get(key_hash, 0).unwrap(), Some(crate::workload::value(42, 8)));
+            assert_eq!(store.get_rightmost_leaf().unwrap(), rightmost);
+        }
+    }
+}
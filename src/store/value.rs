@@ -0,0 +1,91 @@
+use jmt::{JellyfishMerkleTree, KeyHash, OwnedValue, RootHash, SimpleHasher, Version};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::marker::PhantomData;
+
+use super::{TreeReader, TreeWriter};
+
+/// A typed front end over any store: values of type `V` are bincode-encoded on the way in and
+/// decoded on the way out, so the cost of serialization shows up where it happens instead of
+/// being paid by callers ahead of time.
+pub struct ValueStore<S, V> {
+    inner: S,
+    _value: PhantomData<fn() -> V>,
+}
+
+impl<S: TreeReader + TreeWriter, V: Serialize + DeserializeOwned> ValueStore<S, V> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            _value: PhantomData,
+        }
+    }
+
+    /// The wrapped store, for reading or writing raw `Vec<u8>` values.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Encodes `values` into the byte form the tree stores. `None` stays a deletion.
+    pub fn encode<I>(values: I) -> Result<Vec<(KeyHash, Option<OwnedValue>)>, anyhow::Error>
+    where
+        I: IntoIterator<Item = (KeyHash, Option<V>)>,
+    {
+        values
+            .into_iter()
+            .map(|(key_hash, value)| {
+                let bytes = value.map(|value| bincode::serialize(&value)).transpose()?;
+                Ok((key_hash, bytes))
+            })
+            .collect()
+    }
+
+    /// Encodes and commits `values` at `version`, writing the resulting node batch.
+    pub fn put_values<H, I>(&self, values: I, version: Version) -> Result<RootHash, anyhow::Error>
+    where
+        H: SimpleHasher,
+        I: IntoIterator<Item = (KeyHash, Option<V>)>,
+    {
+        let jmt: JellyfishMerkleTree<'_, S, H> = JellyfishMerkleTree::new(&self.inner);
+        let (root, batch) = jmt.put_value_set(Self::encode(values)?, version)?;
+        self.inner.write_node_batch(&batch.node_batch)?;
+        Ok(root)
+    }
+
+    /// Reads and decodes the value of `key_hash` at `version`.
+    pub fn get<H: SimpleHasher>(&self, key_hash: KeyHash, version: Version) -> Result<Option<V>, anyhow::Error> {
+        let jmt: JellyfishMerkleTree<'_, S, H> = JellyfishMerkleTree::new(&self.inner);
+        match jmt.get(key_hash, version)? {
+            Some(bytes) => Ok(Some(bincode::deserialize(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::InMemoryTreeStore;
+    use sha2::Sha256;
+
+    #[test]
+    fn typed_values_round_trip_and_match_the_raw_path() {
+        let store: ValueStore<InMemoryTreeStore, (u64, String)> = ValueStore::new(InMemoryTreeStore::new());
+        let key_hash = KeyHash::with::<Sha256>("account");
+        let account = (42, "alice".to_string());
+
+        let root = store.put_values::<Sha256, _>(vec![(key_hash, Some(account.clone()))], 0).unwrap();
+        assert_eq!(store.get::<Sha256>(key_hash, 0).unwrap(), Some(account.clone()));
+
+        // Committing the same bytes through the untyped path yields the same tree
+        let raw = InMemoryTreeStore::new();
+        let jmt: JellyfishMerkleTree<'_, _, Sha256> = JellyfishMerkleTree::new(&raw);
+        let (raw_root, _batch) = jmt
+            .put_value_set(vec![(key_hash, Some(bincode::serialize(&account).unwrap()))], 0)
+            .unwrap();
+        assert_eq!(root, raw_root);
+
+        store.put_values::<Sha256, _>(vec![(key_hash, None)], 1).unwrap();
+        assert_eq!(store.get::<Sha256>(key_hash, 1).unwrap(), None);
+    }
+}
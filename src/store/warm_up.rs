@@ -0,0 +1,12 @@
+/// Loads whatever a store initializes lazily, such as a backend's block cache or open file
+/// handles, so the first timed iteration does not pay for it. Benches call it on every store
+/// before a timed loop, and none of them time the call itself.
+///
+/// The default does nothing, which suits the in-memory stores. Wrappers forward to the store
+/// they wrap, without counting, delaying or caching anything. Calling it any number of times
+/// leaves the store's contents unchanged.
+pub trait WarmUp {
+    fn warm_up(&self) -> Result<(), anyhow::Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use jmt::{RootHash, SimpleHasher};
+use sha2::Sha256;
+use std::fs;
+use std::path::Path;
+
+use super::{commit_streamed, value};
+use crate::store::{hash_keys, StoreError, TreeReader, TreeWriter};
+
+/// Environment variable naming a dataset file for the benches to load with [`load_pairs`].
+pub const DATASET_VAR: &str = "TREE_BENCH_DATASET";
+
+// Raw key/value pairs, shaped like `super::pairs` returns them
+type Pairs = Vec<(Vec<u8>, Vec<u8>)>;
+
+/// Reads key/value pairs from a file with one pair per line, as `key,value` in hex. A line
+/// holding only a hex key gets the deterministic [`value`] of its line's index, counting from
+/// 0, `value_len` bytes long, so a plain list of real keys works too. Blank lines are skipped; any other
+/// line that is not one or two hex fields fails with [`StoreError::MalformedRow`].
+pub fn load_pairs(path: impl AsRef<Path>, value_len: usize) -> Result<Pairs, StoreError> {
+    parse_pairs(&fs::read_to_string(path)?, value_len)
+}
+
+fn parse_pairs(text: &str, value_len: usize) -> Result<Pairs, StoreError> {
+    let mut pairs = Vec::new();
+    for (i, row) in text.lines().enumerate() {
+        let row = row.trim();
+        if row.is_empty() {
+            continue;
+        }
+        let malformed = |reason: String| StoreError::MalformedRow { line: i + 1, reason };
+        let mut fields = row.split(',').map(str::trim);
+        let key = decode_hex(fields.next().unwrap_or_default()).map_err(|e| malformed(format!("key: {}", e)))?;
+        if key.is_empty() {
+            return Err(malformed("empty key".to_string()));
+        }
+        let value = match fields.next() {
+            Some(field) => decode_hex(field).map_err(|e| malformed(format!("value: {}", e)))?,
+            None => value(i, value_len),
+        };
+        if fields.next().is_some() {
+            return Err(malformed("expected `key` or `key,value`".to_string()));
+        }
+        pairs.push((key, value));
+    }
+    Ok(pairs)
+}
+
+fn decode_hex(field: &str) -> Result<Vec<u8>, String> {
+    let field = field.strip_prefix("0x").unwrap_or(field);
+    // Checked before slicing, which would panic inside a multi-byte character
+    if !field.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(format!("`{}` is not hex", field));
+    }
+    if !field.len().is_multiple_of(2) {
+        return Err(format!("odd number of hex digits in `{}`", field));
+    }
+    (0..field.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&field[i..i + 2], 16).map_err(|_| format!("`{}` is not hex", field)))
+        .collect()
+}
+
+/// Commits `pairs` into `store` as one version-0 batch, hashing keys with Sha256 as the
+/// generated workloads do, and returns the root.
+pub fn build_tree<S: TreeReader + TreeWriter, H: SimpleHasher>(
+    store: &S,
+    pairs: &[(Vec<u8>, Vec<u8>)],
+) -> Result<RootHash, anyhow::Error> {
+    let value_set = hash_keys::<Sha256>(pairs.iter().map(|(key, value)| (key.clone(), Some(value.clone()))))?;
+    commit_streamed::<S, H>(store, value_set, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::InMemoryTreeStore;
+    use jmt::{JellyfishMerkleTree, KeyHash};
+
+    #[test]
+    fn loads_hex_pairs_and_bare_keys_from_a_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("keys.csv");
+        fs::write(&path, "0a0b,1122\n\n  0xff , 33 \r\nc0de\n").unwrap();
+
+        let pairs = load_pairs(&path, 4).unwrap();
+        assert_eq!(
+            pairs,
+            vec![(vec![0x0a, 0x0b], vec![0x11, 0x22]), (vec![0xff], vec![0x33]), (vec![0xc0, 0xde], value(3, 4))]
+        );
+
+        let store = InMemoryTreeStore::new();
+        let root = build_tree::<_, Sha256>(&store, &pairs).unwrap();
+        let tree: JellyfishMerkleTree<_, Sha256> = JellyfishMerkleTree::new(&store);
+        let key_hash = KeyHash::with::<Sha256>([0xff]);
+        let (value, proof) = tree.get_with_proof(key_hash, 0).unwrap();
+        assert_eq!(value, Some(vec![0x33]));
+        proof.verify(root, key_hash, value).unwrap();
+    }
+
+    #[test]
+    fn malformed_rows_name_their_line() {
+        for (text, bad_line) in [("aa,bb\nzz,bb", 2), ("aa,bbb", 1), ("aa,bb,cc", 1), ("\n,bb", 2), ("aé0", 1), ("aa\nbb,é0", 2)] {
+            match parse_pairs(text, 8) {
+                Err(StoreError::MalformedRow { line, .. }) => assert_eq!(line, bad_line, "{:?}", text),
+                other => panic!("expected {:?} to be rejected, got {:?}", text, other),
+            }
+        }
+        assert!(matches!(load_pairs("/nonexistent/keys.csv", 8), Err(StoreError::Io(_))));
+    }
+}
@@ -0,0 +1,292 @@
+//! Deterministic key and value generators shared by the benchmarks, and the [`Workload`]
+//! scenarios built on them.
+
+use jmt::{KeyHash, OwnedValue};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rand_distr::Zipf;
+use sha2::Sha256;
+
+mod dataset;
+mod scenario;
+
+pub use dataset::{build_tree, load_pairs, DATASET_VAR};
+pub use scenario::{commit_streamed, CommitStrategy, Delete, Get, Insert, Update, ValueSet, Workload};
+
+/// Width of every generated key, chosen to match the 32-byte keys of typical state stores.
+pub const KEY_LEN: usize = 32;
+
+/// Returns the `i`-th benchmark key, zero-padded to exactly [`KEY_LEN`] bytes.
+pub fn key(i: usize) -> Vec<u8> {
+    format!("key{:0width$}", i, width = KEY_LEN - 3).into_bytes()
+}
+
+/// Returns a `len`-byte value that depends only on `i`, so every run writes the same bytes.
+pub fn value(i: usize, len: usize) -> Vec<u8> {
+    // splitmix64 keeps the bytes well mixed without pulling in an RNG
+    let mut state = i as u64;
+    let mut value = Vec::with_capacity(len + 8);
+    while value.len() < len {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        value.extend_from_slice(&(z ^ (z >> 31)).to_le_bytes());
+    }
+    value.truncate(len);
+    value
+}
+
+/// Returns `n` key/value pairs using [`key`] and [`value`].
+pub fn pairs(n: usize, value_len: usize) -> Vec<(Vec<u8>, Vec<u8>)> {
+    (0..n).map(|i| (key(i), value(i, value_len))).collect()
+}
+
+/// Yields the same pairs as [`pairs`], hashed and ready for `put_value_set`, generating each
+/// one only when it is pulled, so a million-key batch never exists as a `Vec` of its own.
+pub fn value_set_iter(n: usize, value_len: usize) -> impl Iterator<Item = (KeyHash, Option<OwnedValue>)> {
+    (0..n).map(move |i| (KeyHash::with::<Sha256>(key(i)), Some(value(i, value_len))))
+}
+
+/// Seed used by every benchmark that wants random keys.
+///
+/// It is fixed on purpose: the same seed produces the same key set on every run and every
+/// commit, so timings stay comparable while the keys still look uniformly random.
+pub const BENCH_SEED: u64 = 0x7472_6565_2d62_656e;
+
+/// Returns `n` random [`KEY_LEN`]-byte keys with random `value_len`-byte values.
+///
+/// Uses ChaCha8, whose output is stable across `rand` releases, so a seed always maps to
+/// the same pairs.
+pub fn gen_random_pairs(seed: u64, n: usize, value_len: usize) -> Vec<(Vec<u8>, Vec<u8>)> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    (0..n)
+        .map(|_| {
+            let mut key = vec![0u8; KEY_LEN];
+            rng.fill(&mut key[..]);
+            let mut value = vec![0u8; value_len];
+            rng.fill(&mut value[..]);
+            (key, value)
+        })
+        .collect()
+}
+
+/// Returns `n` distinct key hashes whose first `prefix_nibbles` nibbles are all zero, the
+/// shape of an adversarial key set built to force a deep tree.
+///
+/// An attacker has to mine such keys, at about `16^prefix_nibbles` hashes per key, which is out
+/// of reach for long prefixes in a benchmark. The tree only ever sees key hashes, so these are
+/// forged directly: the hash of [`key`]`(i)` with its prefix cleared.
+pub fn shared_prefix_key_hashes(n: usize, prefix_nibbles: usize) -> Vec<KeyHash> {
+    assert!(prefix_nibbles < 2 * KEY_LEN, "a prefix of {} nibbles leaves nothing to differ", prefix_nibbles);
+    (0..n)
+        .map(|i| {
+            let mut hash = KeyHash::with::<Sha256>(key(i)).0;
+            for nibble in 0..prefix_nibbles {
+                // Even nibbles are the high half of their byte
+                hash[nibble / 2] &= if nibble.is_multiple_of(2) { 0x0f } else { 0xf0 };
+            }
+            KeyHash(hash)
+        })
+        .collect()
+}
+
+/// Returns the hashes of the first `n` keys [`key`]`(i)` whose top nibble falls in the first
+/// `buckets` of the 16, so the root has at most `buckets` children.
+///
+/// Unlike [`shared_prefix_key_hashes`], these are real key hashes found by rejection sampling:
+/// keys outside the buckets are skipped, at a cost of about `16 / buckets` hashes per key.
+pub fn bucketed_key_hashes(n: usize, buckets: u8) -> Vec<KeyHash> {
+    assert!((1..=16).contains(&buckets), "a nibble has 16 buckets, not {}", buckets);
+    (0..)
+        .map(|i| KeyHash::with::<Sha256>(key(i)))
+        .filter(|hash| hash.0[0] >> 4 < buckets)
+        .take(n)
+        .collect()
+}
+
+/// How benchmark keys are chosen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyDistribution {
+    /// Zero-padded `key{i}` strings from [`pairs`].
+    Sequential,
+    /// Random keys from [`gen_random_pairs`] seeded with [`BENCH_SEED`].
+    Random,
+}
+
+impl KeyDistribution {
+    pub const ALL: [KeyDistribution; 2] = [KeyDistribution::Sequential, KeyDistribution::Random];
+
+    /// Short name used in benchmark IDs.
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyDistribution::Sequential => "sequential",
+            KeyDistribution::Random => "random",
+        }
+    }
+
+    /// Generates `n` pairs following this distribution.
+    pub fn pairs(self, n: usize, value_len: usize) -> Vec<(Vec<u8>, Vec<u8>)> {
+        match self {
+            KeyDistribution::Sequential => pairs(n, value_len),
+            KeyDistribution::Random => gen_random_pairs(BENCH_SEED, n, value_len),
+        }
+    }
+}
+
+/// Returns `n` indices drawn uniformly from `0..bound` by ChaCha8 seeded with `seed`.
+pub fn random_indices(seed: u64, n: usize, bound: usize) -> Vec<usize> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    (0..n).map(|_| rng.gen_range(0..bound)).collect()
+}
+
+/// Returns `n` indices into `0..bound` drawn by ChaCha8 seeded with `seed` from a Zipf
+/// distribution with exponent `skew`, so index 0 is the most popular and popularity falls off
+/// with rank. Larger skews concentrate more of the draws on the first few indices.
+///
+/// Panics if `bound` is zero or `skew` is negative.
+pub fn zipf_indices(seed: u64, n: usize, bound: usize, skew: f64) -> Vec<usize> {
+    let zipf = Zipf::new(bound as u64, skew).expect("Zipf needs a non-empty range and a non-negative skew");
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    // Zipf draws ranks from 1..=bound
+    (0..n).map(|_| rng.sample(zipf) as usize - 1).collect()
+}
+
+impl std::str::FromStr for KeyDistribution {
+    type Err = String;
+
+    /// Parses the name given by [`KeyDistribution::name`].
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        KeyDistribution::ALL
+            .into_iter()
+            .find(|distribution| distribution.name() == name)
+            .ok_or_else(|| format!("unknown key distribution `{}`", name))
+    }
+}
+
+/// One step of a mixed read/write workload, naming a key by its index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    Read(usize),
+    Write(usize),
+}
+
+/// Returns `n` operations over keys `0..key_count`, each a read with probability
+/// `read_percent`%, drawn from ChaCha8 seeded with `seed`.
+pub fn mixed_ops(seed: u64, n: usize, key_count: usize, read_percent: u32) -> Vec<Op> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    (0..n)
+        .map(|_| {
+            let key = rng.gen_range(0..key_count);
+            if rng.gen_range(0..100) < read_percent {
+                Op::Read(key)
+            } else {
+                Op::Write(key)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_fixed_width_and_distinct() {
+        assert_eq!(key(0).len(), KEY_LEN);
+        assert_eq!(key(123_456_789).len(), KEY_LEN);
+        assert_ne!(key(1), key(10));
+    }
+
+    #[test]
+    fn a_hundred_thousand_keys_hash_without_collisions() {
+        let key_hashes: std::collections::HashSet<KeyHash> = (0..100_000).map(|i| KeyHash::with::<Sha256>(key(i))).collect();
+        assert_eq!(key_hashes.len(), 100_000);
+    }
+
+    #[test]
+    fn values_are_deterministic_and_sized() {
+        for len in [0, 1, 8, 13, 1024] {
+            assert_eq!(value(7, len).len(), len);
+        }
+        assert_eq!(value(7, 256), value(7, 256));
+        assert_ne!(value(7, 256), value(8, 256));
+        assert_eq!(value(7, 256)[..8], value(7, 8)[..]);
+    }
+
+    #[test]
+    fn random_pairs_are_reproducible_for_a_seed() {
+        let pairs = gen_random_pairs(BENCH_SEED, 100, 16);
+        assert_eq!(pairs, gen_random_pairs(BENCH_SEED, 100, 16));
+        assert_ne!(pairs, gen_random_pairs(BENCH_SEED + 1, 100, 16));
+        assert!(pairs.iter().all(|(key, value)| key.len() == KEY_LEN && value.len() == 16));
+    }
+
+    #[test]
+    fn bucketed_key_hashes_fill_only_their_buckets() {
+        for buckets in [1, 2, 4, 16] {
+            let hashes = bucketed_key_hashes(500, buckets);
+            assert_eq!(hashes.len(), 500);
+            let used: std::collections::BTreeSet<u8> = hashes.iter().map(|hash| hash.0[0] >> 4).collect();
+            assert_eq!(used, (0..buckets).collect());
+        }
+    }
+
+    #[test]
+    fn shared_prefix_key_hashes_share_only_the_prefix() {
+        let nibble = |hash: &KeyHash, i: usize| (hash.0[i / 2] >> if i.is_multiple_of(2) { 4 } else { 0 }) & 0x0f;
+        for prefix_nibbles in [0, 3, 12] {
+            let hashes = shared_prefix_key_hashes(200, prefix_nibbles);
+            assert!(hashes.iter().all(|hash| (0..prefix_nibbles).all(|i| nibble(hash, i) == 0)));
+            // The first free nibble still varies, so the keys fan out right below the prefix
+            assert!(hashes.iter().any(|hash| nibble(hash, prefix_nibbles) != 0));
+
+            let mut distinct = hashes.clone();
+            distinct.sort();
+            distinct.dedup();
+            assert_eq!(distinct.len(), hashes.len());
+        }
+    }
+
+    #[test]
+    fn distributions_parse_from_their_names() {
+        for distribution in KeyDistribution::ALL {
+            assert_eq!(distribution.name().parse(), Ok(distribution));
+        }
+        assert!("zipf".parse::<KeyDistribution>().is_err());
+    }
+
+    #[test]
+    fn random_indices_are_reproducible_and_in_bounds() {
+        let indices = random_indices(BENCH_SEED, 1000, 7);
+        assert_eq!(indices, random_indices(BENCH_SEED, 1000, 7));
+        assert!(indices.iter().all(|i| *i < 7));
+        assert!((0..7).all(|i| indices.contains(&i)));
+    }
+
+    #[test]
+    fn zipf_indices_favour_low_ranks_more_as_skew_grows() {
+        let indices = zipf_indices(BENCH_SEED, 10_000, 1000, 0.99);
+        assert_eq!(indices, zipf_indices(BENCH_SEED, 10_000, 1000, 0.99));
+        assert!(indices.iter().all(|i| *i < 1000));
+
+        let top_ten = |skew: f64| zipf_indices(BENCH_SEED, 10_000, 1000, skew).iter().filter(|i| **i < 10).count();
+        assert!(top_ten(0.8) < top_ten(0.99));
+        assert!(top_ten(0.99) < top_ten(1.2));
+        // Uniform draws would put about 1% of them in the top ten
+        assert!(top_ten(0.8) > 1000);
+    }
+
+    #[test]
+    fn mixed_ops_are_reproducible_and_follow_the_ratio() {
+        let ops = mixed_ops(BENCH_SEED, 1000, 50, 90);
+        assert_eq!(ops, mixed_ops(BENCH_SEED, 1000, 50, 90));
+
+        let reads = ops.iter().filter(|op| matches!(op, Op::Read(_))).count();
+        assert!((850..950).contains(&reads), "{} reads", reads);
+        assert!(ops.iter().all(|op| match op {
+            Op::Read(key) | Op::Write(key) => *key < 50,
+        }));
+        assert!(mixed_ops(BENCH_SEED, 100, 50, 0).iter().all(|op| matches!(op, Op::Write(_))));
+    }
+}
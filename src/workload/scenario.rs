@@ -0,0 +1,268 @@
+use jmt::{JellyfishMerkleTree, KeyHash, OwnedValue, RootHash, SimpleHasher, Version};
+use sha2::Sha256;
+
+use super::{key, pairs, value, KeyDistribution};
+use crate::store::{hash_keys, TreeReader, TreeWriter};
+
+/// Updates handed to `put_value_set`, with keys already hashed.
+pub type ValueSet = Vec<(KeyHash, Option<OwnedValue>)>;
+
+/// One benchmark scenario: the tree it starts from and the operation timed against it.
+///
+/// A driver calls `setup` on an empty store and `prepare` for the inputs, outside the timing,
+/// then times `run`. Keys are always hashed with Sha256, so every tree hasher `H` sees the
+/// same tree shape.
+pub trait Workload {
+    /// Benchmark ID for this scenario over tree hasher `hasher`.
+    fn id(&self, hasher: &str) -> String;
+
+    /// Builds the updates or lookups `run` works through for a tree of `size` keys.
+    fn prepare(&self, size: usize) -> ValueSet;
+
+    /// Commits whatever the tree must hold before `run`. The default leaves it empty.
+    fn setup<S: TreeReader + TreeWriter, H: SimpleHasher>(&self, _store: &S, _size: usize) -> Result<(), anyhow::Error> {
+        Ok(())
+    }
+
+    /// The measured operation.
+    fn run<S: TreeReader + TreeWriter, H: SimpleHasher>(&self, store: &S, batch: ValueSet) -> Result<(), anyhow::Error>;
+}
+
+/// How the insert workload hands keys to the tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommitStrategy {
+    /// One `put_value_set` per key, each committed at its own version.
+    Sequential,
+    /// Every key in a single `put_value_set` at one version.
+    Batched,
+}
+
+impl CommitStrategy {
+    pub const ALL: [CommitStrategy; 2] = [CommitStrategy::Sequential, CommitStrategy::Batched];
+
+    /// Short name used in benchmark IDs.
+    pub fn name(self) -> &'static str {
+        match self {
+            CommitStrategy::Sequential => "sequential",
+            CommitStrategy::Batched => "batched",
+        }
+    }
+}
+
+impl std::str::FromStr for CommitStrategy {
+    type Err = String;
+
+    /// Parses the name given by [`CommitStrategy::name`].
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        CommitStrategy::ALL
+            .into_iter()
+            .find(|strategy| strategy.name() == name)
+            .ok_or_else(|| format!("unknown commit strategy `{}`", name))
+    }
+}
+
+fn hashed(pairs: Vec<(Vec<u8>, Vec<u8>)>) -> ValueSet {
+    let pairs = pairs.into_iter().map(|(key, value)| (key, Some(value)));
+    hash_keys::<Sha256>(pairs).expect("generated keys never collide under Sha256")
+}
+
+fn commit<S: TreeReader + TreeWriter, H: SimpleHasher>(
+    store: &S,
+    value_set: ValueSet,
+    version: Version,
+) -> Result<(), anyhow::Error> {
+    let jmt: JellyfishMerkleTree<'_, S, H> = JellyfishMerkleTree::new(store);
+    let (_root, batch) = jmt.put_value_set(value_set, version)?;
+    store.write_node_batch(&batch.node_batch)
+}
+
+/// Commits `value_set` at `version` as the iterator yields it, never collecting it into a
+/// [`ValueSet`]. jmt still sorts the batch into its own map, so a lazy source such as
+/// [`super::value_set_iter`] leaves one copy of a huge batch in memory instead of two.
+pub fn commit_streamed<S: TreeReader + TreeWriter, H: SimpleHasher>(
+    store: &S,
+    value_set: impl IntoIterator<Item = (KeyHash, Option<OwnedValue>)>,
+    version: Version,
+) -> Result<RootHash, anyhow::Error> {
+    let jmt: JellyfishMerkleTree<'_, S, H> = JellyfishMerkleTree::new(store);
+    let (root, batch) = jmt.put_value_set(value_set, version)?;
+    store.write_node_batch(&batch.node_batch)?;
+    Ok(root)
+}
+
+/// Inserts `size` fresh keys into an empty tree.
+pub struct Insert {
+    pub strategy: CommitStrategy,
+    pub distribution: KeyDistribution,
+    pub value_len: usize,
+}
+
+impl Workload for Insert {
+    fn id(&self, hasher: &str) -> String {
+        format!(
+            "insert/{}/{}/{}/value_{}",
+            self.strategy.name(),
+            hasher,
+            self.distribution.name(),
+            self.value_len
+        )
+    }
+
+    fn prepare(&self, size: usize) -> ValueSet {
+        hashed(self.distribution.pairs(size, self.value_len))
+    }
+
+    fn run<S: TreeReader + TreeWriter, H: SimpleHasher>(&self, store: &S, batch: ValueSet) -> Result<(), anyhow::Error> {
+        match self.strategy {
+            CommitStrategy::Sequential => {
+                for (version, update) in batch.into_iter().enumerate() {
+                    commit::<S, H>(store, vec![update], version as Version)?;
+                }
+                Ok(())
+            }
+            CommitStrategy::Batched => commit::<S, H>(store, batch, 0),
+        }
+    }
+}
+
+/// Proves every key of a `size`-key tree at version 0.
+pub struct Get {
+    pub distribution: KeyDistribution,
+    pub value_len: usize,
+}
+
+impl Workload for Get {
+    fn id(&self, hasher: &str) -> String {
+        format!("get/{}/{}", hasher, self.distribution.name())
+    }
+
+    fn prepare(&self, size: usize) -> ValueSet {
+        hashed(self.distribution.pairs(size, self.value_len))
+    }
+
+    fn setup<S: TreeReader + TreeWriter, H: SimpleHasher>(&self, store: &S, size: usize) -> Result<(), anyhow::Error> {
+        commit::<S, H>(store, self.prepare(size), 0)
+    }
+
+    fn run<S: TreeReader + TreeWriter, H: SimpleHasher>(&self, store: &S, batch: ValueSet) -> Result<(), anyhow::Error> {
+        let jmt: JellyfishMerkleTree<'_, S, H> = JellyfishMerkleTree::new(store);
+        for (key_hash, _value) in batch {
+            jmt.get_with_proof(key_hash, 0)?;
+        }
+        Ok(())
+    }
+}
+
+/// Overwrites every key of a `size`-key tree with a new value in one commit at version 1.
+pub struct Update {
+    pub value_len: usize,
+}
+
+impl Workload for Update {
+    fn id(&self, hasher: &str) -> String {
+        format!("update/{}", hasher)
+    }
+
+    fn prepare(&self, size: usize) -> ValueSet {
+        (0..size)
+            .map(|i| (KeyHash::with::<Sha256>(key(i)), Some(value(size + i, self.value_len))))
+            .collect()
+    }
+
+    fn setup<S: TreeReader + TreeWriter, H: SimpleHasher>(&self, store: &S, size: usize) -> Result<(), anyhow::Error> {
+        commit::<S, H>(store, hashed(pairs(size, self.value_len)), 0)
+    }
+
+    fn run<S: TreeReader + TreeWriter, H: SimpleHasher>(&self, store: &S, batch: ValueSet) -> Result<(), anyhow::Error> {
+        commit::<S, H>(store, batch, 1)
+    }
+}
+
+/// Removes every key of a `size`-key tree in one commit at version 1.
+pub struct Delete {
+    pub value_len: usize,
+}
+
+impl Workload for Delete {
+    fn id(&self, hasher: &str) -> String {
+        format!("delete/{}", hasher)
+    }
+
+    fn prepare(&self, size: usize) -> ValueSet {
+        (0..size).map(|i| (KeyHash::with::<Sha256>(key(i)), None)).collect()
+    }
+
+    fn setup<S: TreeReader + TreeWriter, H: SimpleHasher>(&self, store: &S, size: usize) -> Result<(), anyhow::Error> {
+        commit::<S, H>(store, hashed(pairs(size, self.value_len)), 0)
+    }
+
+    fn run<S: TreeReader + TreeWriter, H: SimpleHasher>(&self, store: &S, batch: ValueSet) -> Result<(), anyhow::Error> {
+        commit::<S, H>(store, batch, 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::InMemoryTreeStore;
+
+    fn run_on_fresh_store<W: Workload>(workload: &W, size: usize) -> InMemoryTreeStore {
+        let store = InMemoryTreeStore::new();
+        workload.setup::<_, Sha256>(&store, size).unwrap();
+        workload.run::<_, Sha256>(&store, workload.prepare(size)).unwrap();
+        store
+    }
+
+    #[test]
+    fn streamed_commit_matches_the_eager_insert() {
+        let size = 200;
+        let insert = Insert { strategy: CommitStrategy::Batched, distribution: KeyDistribution::Sequential, value_len: 8 };
+        let eager = run_on_fresh_store(&insert, size);
+        let eager_root = JellyfishMerkleTree::<'_, _, Sha256>::new(&eager).get_root_hash(0).unwrap();
+
+        let streamed = InMemoryTreeStore::new();
+        let root = commit_streamed::<_, Sha256>(&streamed, crate::workload::value_set_iter(size, 8), 0).unwrap();
+        assert_eq!(root, eager_root);
+        assert_eq!(streamed.tree_stats().unwrap(), eager.tree_stats().unwrap());
+    }
+
+    #[test]
+    fn workloads_leave_the_expected_tree() {
+        let size = 20;
+        let key_hash = KeyHash::with::<Sha256>(key(3));
+
+        for strategy in CommitStrategy::ALL {
+            let insert = Insert { strategy, distribution: KeyDistribution::Sequential, value_len: 8 };
+            let store = run_on_fresh_store(&insert, size);
+            let latest = match strategy {
+                CommitStrategy::Sequential => size as Version - 1,
+                CommitStrategy::Batched => 0,
+            };
+            let jmt: JellyfishMerkleTree<'_, _, Sha256> = JellyfishMerkleTree::new(&store);
+            assert_eq!(jmt.get(key_hash, latest).unwrap(), Some(value(3, 8)));
+        }
+
+        let store = run_on_fresh_store(&Update { value_len: 8 }, size);
+        let jmt: JellyfishMerkleTree<'_, _, Sha256> = JellyfishMerkleTree::new(&store);
+        assert_eq!(jmt.get(key_hash, 1).unwrap(), Some(value(size + 3, 8)));
+
+        let store = run_on_fresh_store(&Delete { value_len: 8 }, size);
+        let jmt: JellyfishMerkleTree<'_, _, Sha256> = JellyfishMerkleTree::new(&store);
+        assert_eq!(jmt.get(key_hash, 0).unwrap(), Some(value(3, 8)));
+        assert_eq!(jmt.get(key_hash, 1).unwrap(), None);
+
+        run_on_fresh_store(&Get { distribution: KeyDistribution::Random, value_len: 8 }, size);
+    }
+
+    #[test]
+    fn prepared_batches_hold_one_entry_per_operation() {
+        // Benchmarks count throughput from these batches, one element per key `run` touches
+        let size = 20;
+        let insert = Insert { strategy: CommitStrategy::Sequential, distribution: KeyDistribution::Random, value_len: 8 };
+        assert_eq!(insert.prepare(size).len(), size);
+        assert!(insert.prepare(size).iter().all(|(_key_hash, value)| value.as_ref().map(Vec::len) == Some(8)));
+        assert_eq!(Get { distribution: KeyDistribution::Sequential, value_len: 8 }.prepare(size).len(), size);
+        assert_eq!(Update { value_len: 8 }.prepare(size).len(), size);
+        assert_eq!(Delete { value_len: 8 }.prepare(size).len(), size);
+    }
+}